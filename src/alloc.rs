@@ -0,0 +1,174 @@
+//! Counting how much memory each phase of a day allocates.
+//!
+//! Building with the `alloc-stats` feature installs [`Counting`] as the global
+//! allocator, after which [`measured`] reports the peak and total bytes allocated by
+//! the current thread while running a closure. Without the feature nothing is counted
+//! and [`measured`] returns `None`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    fmt::{self, Display},
+};
+
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// Counts for one thread, so that days run at once on different threads do not count
+/// each other's allocations.
+struct Counts {
+    /// Bytes allocated and not yet freed. Can go below zero when this thread frees
+    /// memory another thread allocated.
+    live: Cell<isize>,
+    peak: Cell<isize>,
+    total: Cell<usize>,
+}
+
+thread_local! {
+    static COUNTS: Counts = const {
+        Counts {
+            live: Cell::new(0),
+            peak: Cell::new(0),
+            total: Cell::new(0),
+        }
+    };
+}
+
+fn record(allocated: usize, freed: usize) {
+    // Threads which are shutting down have no counts left to update.
+    let _ = COUNTS.try_with(|counts| {
+        let live = counts.live.get() + allocated as isize - freed as isize;
+        counts.live.set(live);
+        counts.peak.set(counts.peak.get().max(live));
+        counts.total.set(counts.total.get() + allocated);
+    });
+}
+
+/// The [`System`] allocator, counting what it allocates.
+pub struct Counting;
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record(layout.size(), 0);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record(layout.size(), 0);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record(0, layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record(new_size, layout.size());
+        }
+        new_ptr
+    }
+}
+
+/// Memory allocated while running something.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Usage {
+    /// The most bytes held at once, over what was held at the start.
+    pub peak: usize,
+    /// Every byte allocated, including those freed again. Growing an allocation
+    /// counts its new size.
+    pub total: usize,
+}
+
+/// Formats a number of bytes with a binary prefix.
+pub(crate) fn bytes(n: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = n as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", n)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+impl Display for Usage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "peak {}, total {}", bytes(self.peak), bytes(self.total))
+    }
+}
+
+/// Whether allocations are being counted.
+pub fn enabled() -> bool {
+    cfg!(feature = "alloc-stats")
+}
+
+/// Runs `f`, returning its result along with what it allocated on this thread if
+/// allocations are being counted.
+pub fn measured<T>(f: impl FnOnce() -> T) -> (T, Option<Usage>) {
+    if !enabled() {
+        return (f(), None);
+    }
+    let (start, total) = COUNTS.with(|counts| {
+        counts.peak.set(counts.live.get());
+        (counts.live.get(), counts.total.get())
+    });
+    let result = f();
+    let usage = COUNTS.with(|counts| Usage {
+        peak: (counts.peak.get() - start).max(0) as usize,
+        total: counts.total.get() - total,
+    });
+    (result, Some(usage))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytes() {
+        assert_eq!(bytes(512), "512 B");
+        assert_eq!(bytes(1536), "1.5 KiB");
+        assert_eq!(bytes(30 << 20), "30.0 MiB");
+        assert_eq!(
+            Usage {
+                peak: 2048,
+                total: 3 << 30
+            }
+            .to_string(),
+            "peak 2.0 KiB, total 3.0 GiB"
+        );
+    }
+
+    #[test]
+    fn test_measured() {
+        let (sum, usage) = measured(|| {
+            let small = vec![1u8; 1000];
+            drop(small);
+            let large = vec![1u8; 1 << 20];
+            large.iter().map(|&x| x as usize).sum::<usize>()
+        });
+        assert_eq!(sum, 1 << 20);
+        match usage {
+            Some(usage) => {
+                assert!(enabled());
+                assert!((1 << 20..(1 << 20) + 1000).contains(&usage.peak));
+                assert!(usage.total >= (1 << 20) + 1000);
+            }
+            None => assert!(!enabled()),
+        }
+    }
+}
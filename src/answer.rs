@@ -0,0 +1,80 @@
+use std::fmt::Display;
+
+/// The result of solving one part of a puzzle.
+///
+/// Most puzzles produce a number but some ask for a string (day 21 part two) or
+/// have nothing to compute at all (day 25 part two).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Number(usize),
+    BigNumber(u128),
+    Text(String),
+    /// Multi-line answers, such as letters drawn on a grid, one entry per row.
+    Grid(Vec<String>),
+}
+
+impl Answer {
+    /// Whether the answer needs more than one line to be displayed.
+    pub fn is_multiline(&self) -> bool {
+        matches!(self, Answer::Grid(rows) if rows.len() > 1)
+    }
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Number(value) => write!(f, "{}", value),
+            Answer::BigNumber(value) => write!(f, "{}", value),
+            Answer::Text(value) => write!(f, "{}", value),
+            Answer::Grid(rows) => write!(f, "{}", rows.join("\n")),
+        }
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(value: usize) -> Self {
+        Answer::Number(value)
+    }
+}
+
+impl From<u128> for Answer {
+    fn from(value: u128) -> Self {
+        Answer::BigNumber(value)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Self {
+        Answer::Text(value.to_string())
+    }
+}
+
+impl From<Vec<String>> for Answer {
+    fn from(rows: Vec<String>) -> Self {
+        Answer::Grid(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Answer::Number(42).to_string(), "42");
+        assert_eq!(
+            Answer::BigNumber(u128::MAX).to_string(),
+            "340282366920938463463374607431768211455"
+        );
+        assert_eq!(Answer::from("a,b,c").to_string(), "a,b,c");
+        let grid = Answer::from(vec!["#..".to_string(), ".#.".to_string()]);
+        assert!(grid.is_multiline());
+        assert_eq!(grid.to_string(), "#..\n.#.");
+    }
+}
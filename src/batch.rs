@@ -0,0 +1,209 @@
+//! Running one day against every input in a directory, used by
+//! `main run <day> --inputs DIR`.
+//!
+//! This is for comparing several people's inputs for the same day. Every file in the
+//! directory not starting with a `.` is taken to be an input. Inputs which can not be
+//! read or parsed, which take too long or which make the solver panic are flagged in
+//! the table rather than stopping the others from running.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    cancel::Token,
+    cli::{Part, Source},
+    pool,
+    runner::{self, DayResult, PartResult, RunError},
+    Puzzle,
+};
+
+/// Why an input could not be solved.
+#[derive(Debug)]
+pub enum Failure {
+    Run(RunError),
+    /// The solver panicked, with the panic's message.
+    Panicked(String),
+}
+
+impl Failure {
+    /// A short description for the table.
+    pub fn status(&self) -> &'static str {
+        match self {
+            Failure::Run(RunError::Io { .. }) => "unreadable",
+            Failure::Run(RunError::Parse { .. }) => "parse error",
+            Failure::Run(RunError::TimedOut { .. }) => "timed out",
+            Failure::Panicked(_) => "panicked",
+        }
+    }
+
+    /// A diagnostic suitable for printing to stderr.
+    pub fn report(&self, input: &Path) -> String {
+        match self {
+            Failure::Run(error) => error.report(),
+            Failure::Panicked(message) => {
+                format!("error: Panicked on {}: {}\n", input.display(), message)
+            }
+        }
+    }
+}
+
+/// What happened when running the day on one input.
+#[derive(Debug)]
+pub struct Row {
+    pub input: PathBuf,
+    pub outcome: Result<DayResult, Failure>,
+}
+
+/// The inputs in `dir`, sorted by name.
+pub fn inputs(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut inputs = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with('.') || !entry.path().is_file() {
+            continue;
+        }
+        inputs.push(entry.path());
+    }
+    inputs.sort_unstable();
+    Ok(inputs)
+}
+
+/// Runs `puzzle` on each input in `dir` on up to `jobs` threads, giving up on an input
+/// after `timeout`.
+pub fn run(
+    puzzle: &dyn Puzzle,
+    dir: &Path,
+    part: Option<Part>,
+    jobs: usize,
+    timeout: Option<Duration>,
+) -> io::Result<Vec<Row>> {
+    let inputs = inputs(dir)?;
+    let mut rows = Vec::with_capacity(inputs.len());
+    pool::map_ordered(
+        &inputs,
+        jobs,
+        |input| {
+            let source = Source::Path(input.clone());
+            runner::run_until(puzzle, &source, part, &Token::with_timeout(timeout))
+        },
+        |input, result| {
+            rows.push(Row {
+                input: input.clone(),
+                outcome: match result {
+                    Ok(Ok(result)) => Ok(result),
+                    Ok(Err(error)) => Err(Failure::Run(error)),
+                    Err(message) => Err(Failure::Panicked(message)),
+                },
+            })
+        },
+    );
+    Ok(rows)
+}
+
+/// An answer as it appears in the table.
+fn cell(part: &Option<PartResult>) -> String {
+    match part {
+        Some(part) if part.answer.is_multiline() => {
+            format!("({} lines)", part.answer.to_string().lines().count())
+        }
+        Some(part) => part.answer.to_string(),
+        None => "-".to_string(),
+    }
+}
+
+/// Lines the rows up into a table of the input's file name, the answers, the time
+/// taken to parse and solve, and whether it worked.
+pub fn table(rows: &[Row]) -> String {
+    let mut lines = vec![["Input", "Part One", "Part Two", "Time", "Status"].map(String::from)];
+    for row in rows {
+        let name = row.input.file_name().map_or_else(
+            || row.input.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        lines.push(match &row.outcome {
+            Ok(result) => {
+                let time = result.parse_time
+                    + [&result.part_one, &result.part_two]
+                        .into_iter()
+                        .flatten()
+                        .map(|part| part.elapsed)
+                        .sum::<Duration>();
+                [
+                    name,
+                    cell(&result.part_one),
+                    cell(&result.part_two),
+                    format!("{:?}", time),
+                    "ok".to_string(),
+                ]
+            }
+            Err(failure) => [
+                name,
+                "-".to_string(),
+                "-".to_string(),
+                "-".to_string(),
+                failure.status().to_string(),
+            ],
+        });
+    }
+    let mut widths = [0; 5];
+    for line in &lines {
+        for (width, cell) in widths.iter_mut().zip(line) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut text = String::new();
+    for line in &lines {
+        let cells: Vec<String> = line
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect();
+        text += cells.join("  ").trim_end();
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+    use std::env;
+
+    #[test]
+    fn test_run() {
+        let dir = env::temp_dir().join(format!("batch-{}", std::process::id()));
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("alice.txt"), "1721\n979\n366\n299\n675\n1456\n").unwrap();
+        fs::write(dir.join("bob.txt"), "1721\nnine\n").unwrap();
+        fs::write(dir.join("carol.txt"), "1\n2\n").unwrap();
+        fs::write(dir.join(".hidden"), "").unwrap();
+        assert_eq!(
+            inputs(&dir).unwrap(),
+            ["alice.txt", "bob.txt", "carol.txt"].map(|name| dir.join(name))
+        );
+
+        let puzzle = registry::find(2020, 1).unwrap();
+        let rows = run(puzzle, &dir, None, 2, None).unwrap();
+        let statuses: Vec<_> = rows
+            .iter()
+            .map(|row| row.outcome.as_ref().map_err(Failure::status).map(|_| ()))
+            .collect();
+        assert_eq!(statuses, [Ok(()), Err("parse error"), Err("panicked")]);
+        let table = table(&rows);
+        let lines: Vec<_> = table.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("Input      Part One  Part Two   Time"));
+        assert!(lines[1].starts_with("alice.txt  514579    241861950  "));
+        assert!(lines[1].ends_with("  ok"));
+        assert!(lines[2].starts_with("bob.txt    -         -          -"));
+        assert!(lines[2].ends_with("  parse error"));
+        assert!(lines[3].ends_with("  panicked"));
+
+        assert!(run(puzzle, &dir.join("missing"), None, 1, None).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
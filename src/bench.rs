@@ -0,0 +1,434 @@
+//! Repeatedly timing each phase of a puzzle, and comparing the timings with a
+//! baseline saved from an earlier run.
+//!
+//! A baseline has the median time of each phase, one per line:
+//!
+//! ```text
+//! <year> <day> parse|one|two <nanoseconds>
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Write},
+    fs, io,
+    io::Cursor,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crate::{ParseError, Puzzle};
+
+/// Runs `f`, returning its result along with how long it took.
+pub fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+/// Summary statistics over a set of timings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    /// The sample standard deviation, zero for a single timing.
+    pub stddev: Duration,
+}
+
+impl Stats {
+    /// Summarises `timings`, which must not be empty.
+    pub fn new(timings: &[Duration]) -> Self {
+        let mut sorted = timings.to_vec();
+        sorted.sort_unstable();
+        let n = sorted.len();
+        let median = if n.is_multiple_of(2) {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2
+        } else {
+            sorted[n / 2]
+        };
+        let mean = sorted.iter().sum::<Duration>() / n as u32;
+        let variance = if n > 1 {
+            sorted
+                .iter()
+                .map(|t| (t.as_secs_f64() - mean.as_secs_f64()).powi(2))
+                .sum::<f64>()
+                / (n - 1) as f64
+        } else {
+            0.0
+        };
+        Stats {
+            min: sorted[0],
+            median,
+            mean,
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+}
+
+/// Timings for each phase of one puzzle.
+#[derive(Debug, Clone)]
+pub struct Bench {
+    pub day: usize,
+    pub name: &'static str,
+    pub iterations: usize,
+    pub parse: Stats,
+    pub part_one: Stats,
+    pub part_two: Stats,
+}
+
+impl Bench {
+    /// Parses `input` and solves both parts `iterations` times.
+    ///
+    /// The input is read up front so that only parsing is timed, not disk access.
+    pub fn run(puzzle: &dyn Puzzle, input: &[u8], iterations: usize) -> Result<Self, ParseError> {
+        let iterations = iterations.max(1);
+        let mut parse = Vec::with_capacity(iterations);
+        let mut part_one = Vec::with_capacity(iterations);
+        let mut part_two = Vec::with_capacity(iterations);
+        for _ in 0..iterations {
+            let (parsed, elapsed) = timed(|| puzzle.parse(&mut Cursor::new(input)));
+            let parsed = parsed?;
+            parse.push(elapsed);
+            part_one.push(timed(|| parsed.part_one()).1);
+            part_two.push(timed(|| parsed.part_two()).1);
+        }
+        Ok(Bench {
+            day: puzzle.day(),
+            name: puzzle.name(),
+            iterations,
+            parse: Stats::new(&parse),
+            part_one: Stats::new(&part_one),
+            part_two: Stats::new(&part_two),
+        })
+    }
+
+    /// The median time to parse and solve both parts.
+    pub fn total(&self) -> Duration {
+        self.parse.median + self.part_one.median + self.part_two.median
+    }
+
+    /// A breakdown of each phase.
+    pub fn report(&self) -> String {
+        let mut report = format!(
+            "Day {}: {} ({} iterations)\n",
+            self.day, self.name, self.iterations
+        );
+        report += &format!(
+            "    {:<10}{:>12}{:>12}{:>12}{:>12}\n",
+            "Phase", "Min", "Median", "Mean", "Std dev"
+        );
+        for (phase, stats) in [
+            ("Parse", &self.parse),
+            ("Star One", &self.part_one),
+            ("Star Two", &self.part_two),
+        ] {
+            report += &format!(
+                "    {:<10}{:>12.2?}{:>12.2?}{:>12.2?}{:>12.2?}\n",
+                phase, stats.min, stats.median, stats.mean, stats.stddev
+            );
+        }
+        report
+    }
+}
+
+/// A table comparing the median times of each puzzle.
+pub fn summary(benches: &[Bench]) -> String {
+    let mut table = format!(
+        "{:>3}  {:<24}{:>12}{:>12}{:>12}{:>12}\n",
+        "Day", "Name", "Parse", "Star One", "Star Two", "Total"
+    );
+    for bench in benches {
+        writeln!(
+            table,
+            "{:>3}  {:<24}{:>12.2?}{:>12.2?}{:>12.2?}{:>12.2?}",
+            bench.day,
+            bench.name,
+            bench.parse.median,
+            bench.part_one.median,
+            bench.part_two.median,
+            bench.total()
+        )
+        .unwrap();
+    }
+    let total: Duration = benches.iter().map(Bench::total).sum();
+    writeln!(table, "{:>3}  {:<24}{:>48.2?}", "", "Total", total).unwrap();
+    table
+}
+
+/// How much slower a phase may get, in percent, before it counts as a regression
+/// unless told otherwise.
+pub const DEFAULT_THRESHOLD: u32 = 10;
+
+/// How much slower a phase must get before it can count as a regression at all, so
+/// that jitter in phases which take a few microseconds is not flagged.
+const MIN_REGRESSION: Duration = Duration::from_micros(50);
+
+/// One of the phases that is timed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    Parse,
+    PartOne,
+    PartTwo,
+}
+
+impl Phase {
+    const ALL: [Phase; 3] = [Phase::Parse, Phase::PartOne, Phase::PartTwo];
+
+    /// How the phase is written in a baseline.
+    fn key(self) -> &'static str {
+        match self {
+            Phase::Parse => "parse",
+            Phase::PartOne => "one",
+            Phase::PartTwo => "two",
+        }
+    }
+
+    fn stats(self, bench: &Bench) -> &Stats {
+        match self {
+            Phase::Parse => &bench.parse,
+            Phase::PartOne => &bench.part_one,
+            Phase::PartTwo => &bench.part_two,
+        }
+    }
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Phase::Parse => "Parse",
+            Phase::PartOne => "Star One",
+            Phase::PartTwo => "Star Two",
+        })
+    }
+}
+
+/// Median timings to compare later runs with, by year, day and phase.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Baseline {
+    pub timings: BTreeMap<(usize, usize, Phase), Duration>,
+}
+
+impl Baseline {
+    /// Reads a baseline, skipping blank lines and lines starting with `#`.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        Baseline::parse(&text)
+            .map_err(|line| format!("{}:{}: Unexpected line", path.display(), line))
+    }
+
+    /// Reads a baseline, or gives the number of the first line that cannot be read.
+    fn parse(text: &str) -> Result<Self, usize> {
+        let mut baseline = Baseline::default();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let entry = match fields[..] {
+                [year, day, phase, nanos] => (|| {
+                    let phase = *Phase::ALL.iter().find(|p| p.key() == phase)?;
+                    let nanos = Duration::from_nanos(nanos.parse().ok()?);
+                    Some(((year.parse().ok()?, day.parse().ok()?, phase), nanos))
+                })(),
+                _ => None,
+            };
+            let (key, nanos) = entry.ok_or(i + 1)?;
+            baseline.timings.insert(key, nanos);
+        }
+        Ok(baseline)
+    }
+
+    /// Adds the median timings of `benches`, for days of `year`, replacing any there
+    /// were for those days.
+    pub fn record(&mut self, year: usize, benches: &[Bench]) {
+        for bench in benches {
+            for phase in Phase::ALL {
+                self.timings
+                    .insert((year, bench.day, phase), phase.stats(bench).median);
+            }
+        }
+    }
+
+    /// Writes the baseline, one phase to a line.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut text = String::new();
+        for ((year, day, phase), median) in &self.timings {
+            writeln!(
+                text,
+                "{} {} {} {}",
+                year,
+                day,
+                phase.key(),
+                median.as_nanos()
+            )
+            .unwrap();
+        }
+        fs::write(path, text)
+    }
+}
+
+/// How a phase's median time compares with the baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Change {
+    pub day: usize,
+    pub phase: Phase,
+    pub was: Duration,
+    pub now: Duration,
+}
+
+impl Change {
+    /// How much slower the phase got, in percent, or negative if it got quicker.
+    pub fn percent(&self) -> f64 {
+        if self.was.is_zero() {
+            return 0.0;
+        }
+        (self.now.as_secs_f64() / self.was.as_secs_f64() - 1.0) * 100.0
+    }
+
+    /// Whether the phase got more than `threshold` percent slower.
+    pub fn regressed(&self, threshold: u32) -> bool {
+        self.now > self.was + MIN_REGRESSION && self.percent() > threshold as f64
+    }
+}
+
+/// Compares each phase of `benches`, for days of `year`, with the baseline. Phases not
+/// in the baseline are left out.
+pub fn compare(baseline: &Baseline, year: usize, benches: &[Bench]) -> Vec<Change> {
+    benches
+        .iter()
+        .flat_map(|bench| {
+            Phase::ALL.into_iter().filter_map(move |phase| {
+                let was = *baseline.timings.get(&(year, bench.day, phase))?;
+                Some(Change {
+                    day: bench.day,
+                    phase,
+                    was,
+                    now: phase.stats(bench).median,
+                })
+            })
+        })
+        .collect()
+}
+
+/// A table of the changes, flagging those slower by more than `threshold` percent.
+pub fn comparison(changes: &[Change], threshold: u32) -> String {
+    let mut table = format!(
+        "{:>3}  {:<10}{:>12}{:>12}{:>9}\n",
+        "Day", "Phase", "Baseline", "Now", "Change"
+    );
+    for change in changes {
+        let line = format!(
+            "{:>3}  {:<10}{:>12.2?}{:>12.2?}{:>+8.0}%",
+            change.day,
+            change.phase,
+            change.was,
+            change.now,
+            change.percent()
+        );
+        if change.regressed(threshold) {
+            writeln!(table, "{}  REGRESSED", line).unwrap();
+        } else {
+            writeln!(table, "{}", line).unwrap();
+        }
+    }
+    let regressed = changes.iter().filter(|c| c.regressed(threshold)).count();
+    writeln!(
+        table,
+        "{} of {} phases slower by more than {}%",
+        regressed,
+        changes.len(),
+        threshold
+    )
+    .unwrap();
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(timings: &[u64]) -> Vec<Duration> {
+        timings.iter().map(|&t| Duration::from_millis(t)).collect()
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = Stats::new(&ms(&[4, 1, 3, 2]));
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.median, Duration::from_micros(2500));
+        assert_eq!(stats.mean, Duration::from_micros(2500));
+        assert_eq!(stats.stddev.as_micros(), 1290);
+
+        let stats = Stats::new(&ms(&[7]));
+        assert_eq!(stats.median, Duration::from_millis(7));
+        assert_eq!(stats.stddev, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_run() {
+        let puzzle = crate::registry::get(1).unwrap();
+        let bench = Bench::run(puzzle, b"1721\n979\n366\n299\n675\n1456", 3).unwrap();
+        assert_eq!(bench.iterations, 3);
+        assert!(bench.parse.min <= bench.parse.median);
+        assert!(summary(&[bench]).contains("Report Repair"));
+
+        assert!(Bench::run(puzzle, b"1721\nx", 3).is_err());
+    }
+
+    #[test]
+    fn test_baseline() {
+        let puzzle = crate::registry::get(1).unwrap();
+        let bench = Bench::run(puzzle, b"1721\n979\n366\n299\n675\n1456", 1).unwrap();
+        let mut baseline = Baseline::default();
+        baseline.record(2020, std::slice::from_ref(&bench));
+        assert_eq!(baseline.timings.len(), 3);
+        let path = std::env::temp_dir().join(format!("baseline-{}.txt", std::process::id()));
+        baseline.save(&path).unwrap();
+        assert_eq!(Baseline::load(&path), Ok(baseline));
+        fs::remove_file(&path).unwrap();
+
+        let baseline = Baseline::parse("# old\n\n2020 1 parse 1000\n2020 1 two 1000000\n").unwrap();
+        let changes = compare(&baseline, 2020, &[bench]);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].phase, Phase::Parse);
+        assert_eq!(changes[1].was, Duration::from_millis(1));
+        assert_eq!(Baseline::parse("2020 1 parse\n"), Err(1));
+        assert_eq!(Baseline::parse("\n2020 1 three 5\n"), Err(2));
+    }
+
+    #[test]
+    fn test_comparison() {
+        let change = |phase, was, now| Change {
+            day: 15,
+            phase,
+            was: Duration::from_millis(was),
+            now: Duration::from_millis(now),
+        };
+        let changes = [
+            change(Phase::Parse, 0, 0),
+            change(Phase::PartOne, 100, 105),
+            change(Phase::PartTwo, 800, 1000),
+        ];
+        assert_eq!(changes[2].percent(), 25.0);
+        assert!(!changes[1].regressed(DEFAULT_THRESHOLD));
+        assert!(changes[1].regressed(4));
+        assert!(changes[2].regressed(DEFAULT_THRESHOLD));
+        // Too small a difference to tell from noise, however large in proportion.
+        assert!(!Change {
+            was: Duration::from_micros(2),
+            now: Duration::from_micros(4),
+            ..changes[0]
+        }
+        .regressed(DEFAULT_THRESHOLD));
+        assert_eq!(
+            comparison(&changes, DEFAULT_THRESHOLD),
+            "Day  Phase         Baseline         Now   Change\n \
+             15  Parse           0.00ns      0.00ns      +0%\n \
+             15  Star One      100.00ms    105.00ms      +5%\n \
+             15  Star Two      800.00ms       1.00s     +25%  REGRESSED\n\
+             1 of 3 phases slower by more than 10%\n"
+        );
+    }
+}
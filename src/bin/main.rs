@@ -1,26 +1,394 @@
 use std::env;
 use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::process;
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use std::io::BufReader;
-
+use advent_of_code_2020::bench::{self, Baseline, Bench};
+use advent_of_code_2020::cache::{self, Cache};
+use advent_of_code_2020::cancel::Token;
+use advent_of_code_2020::cli::{self, Command, Format, Options, Part, Source};
+use advent_of_code_2020::manifest::{self, Manifest, Outcome};
+use advent_of_code_2020::progress;
 use advent_of_code_2020::*;
 
-fn main() {
-    let args: Vec<String> = env::args().skip(1).collect();
-    let days = if args.len() == 1 && args[0] == "all" {
-        (1..=25).map(|x| x.to_string()).collect()
+/// Solves each day for the input in its source and prints the results in order,
+/// running up to `options.jobs` days at once. Days which fail are reported on stderr
+/// and make the process exit with an error once every day has finished. When only
+/// one day runs at a time and stderr is a terminal, its progress is shown as it runs.
+/// Answers are taken from the cache when allowed, and new ones added to it.
+fn run(runs: &[(usize, Source, Option<Part>)], options: &Options) {
+    let mut failed = false;
+    let mut cache = if options.use_cache {
+        Cache::load(cache::DEFAULT_PATH)
+            .map_err(|e| eprintln!("warning: Not using the cache: {}", e))
+            .ok()
     } else {
-        args
+        None
     };
-    for day in days {
-        println!("Day {}:", day);
+    let mut solved = Vec::new();
+    let show_progress = options.jobs == 1 && io::stderr().is_terminal();
+    pool::map_ordered(
+        runs,
+        options.jobs,
+        |(day, source, part)| {
+            let puzzle = registry::find(options.year, *day).unwrap();
+            let token = Token::with_timeout(options.timeout);
+            let run = || runner::run_cached(puzzle, source, *part, &token, cache.as_ref());
+            if !show_progress {
+                return run();
+            }
+            let mut line = progress::Line::new(format!("Day {}", day));
+            let result = progress::run(move |update| line.update(update), run);
+            progress::Line::clear();
+            result
+        },
+        |(day, _, _), result| match result {
+            Ok(Ok(result)) => {
+                match options.format {
+                    Format::Text => print!("{}", result.to_text()),
+                    Format::Json => println!("{}", result.to_json()),
+                }
+                if !result.is_cached() {
+                    solved.push(result);
+                }
+            }
+            Ok(Err(e)) => {
+                eprint!("{}", e.report());
+                failed = true;
+            }
+            Err(message) => {
+                eprintln!("error: day {:02} panicked: {}", day, message);
+                failed = true;
+            }
+        },
+    );
+    if let Some(cache) = &mut cache {
+        for result in &solved {
+            if let Err(e) = cache.store(options.year, result) {
+                eprintln!("warning: Not caching day {}: {}", result.day, e);
+                break;
+            }
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
 
-        let (star_one, star_two, filename) = get_day(day.parse::<usize>().unwrap());
-        let f = fs::File::open(&filename).unwrap();
-        let input = BufReader::new(f);
-        println!("Star One: {}", star_one(Box::new(input)));
-        let f = fs::File::open(&filename).unwrap();
-        let input = BufReader::new(f);
-        println!("Star Two: {}", star_two(Box::new(input)));
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (command, options) = cli::parse_args(&args).unwrap_or_else(|e| {
+        eprintln!("error: {}\n\n{}", e, cli::USAGE);
+        process::exit(2);
+    });
+    log::set_level(options.verbosity);
+    match command {
+        Command::Days(days) => {
+            let runs: Vec<_> = days
+                .into_iter()
+                .map(|day| (day, Source::Default, None))
+                .collect();
+            run(&runs, &options);
+        }
+        Command::Run { day, source, part } => run(&[(day, source, part)], &options),
+        Command::Batch { day, dir, part } => {
+            let puzzle = registry::find(options.year, day).unwrap();
+            let rows = batch::run(puzzle, &dir, part, options.jobs, options.timeout)
+                .unwrap_or_else(|e| {
+                    eprintln!("error: Could not list {}: {}", dir.display(), e);
+                    process::exit(1);
+                });
+            print!("{}", batch::table(&rows));
+            let mut failed = false;
+            for row in &rows {
+                if let Err(failure) = &row.outcome {
+                    eprint!("{}", failure.report(&row.input));
+                    failed = true;
+                }
+            }
+            if failed {
+                process::exit(1);
+            }
+        }
+        Command::Bench {
+            days,
+            iterations,
+            save,
+            compare,
+            threshold,
+        } => {
+            // Read the baselines first, so as not to find they are missing only after
+            // all the timing.
+            let load = |path: &Path| {
+                Baseline::load(path).unwrap_or_else(|e| {
+                    eprintln!("error: {}", e);
+                    process::exit(1);
+                })
+            };
+            let compare = compare.map(|path| load(&path));
+            let save = save.map(|path| {
+                let baseline = if path.exists() {
+                    load(&path)
+                } else {
+                    Baseline::default()
+                };
+                (path, baseline)
+            });
+            let benches: Vec<Bench> = days
+                .into_iter()
+                .map(|day| {
+                    let puzzle = registry::find(options.year, day).unwrap();
+                    let path = puzzle.input_path();
+                    let input = fs::read(&path).unwrap_or_else(|e| {
+                        eprintln!("error: Could not open {}: {}", path.display(), e);
+                        process::exit(1);
+                    });
+                    let bench = Bench::run(puzzle, &input, iterations).unwrap_or_else(|e| {
+                        eprint!("{}", e.report(&path.display().to_string()));
+                        process::exit(1);
+                    });
+                    println!("{}", bench.report());
+                    bench
+                })
+                .collect();
+            print!("{}", bench::summary(&benches));
+            let mut regressed = false;
+            if let Some(baseline) = compare {
+                let changes = bench::compare(&baseline, options.year, &benches);
+                print!("\n{}", bench::comparison(&changes, threshold));
+                regressed = changes.iter().any(|change| change.regressed(threshold));
+            }
+            if let Some((path, mut baseline)) = save {
+                baseline.record(options.year, &benches);
+                baseline.save(&path).unwrap_or_else(|e| {
+                    eprintln!("error: Could not write {}: {}", path.display(), e);
+                    process::exit(1);
+                });
+                println!("saved baseline to {}", path.display());
+            }
+            if regressed {
+                process::exit(1);
+            }
+        }
+        Command::Verify { days, manifest } => {
+            let manifest = Manifest::load(&manifest).unwrap_or_else(|e| {
+                eprint!("{}", e.report(&manifest.display().to_string()));
+                process::exit(1);
+            });
+            let missing: Vec<String> = manifest
+                .missing(&days)
+                .iter()
+                .map(|day| day.to_string())
+                .collect();
+            let checks = manifest::verify(
+                &manifest,
+                options.year,
+                &days,
+                options.jobs,
+                options.timeout,
+            );
+            // Checking nothing must not look like everything passing.
+            if checks.is_empty() {
+                eprintln!(
+                    "error: The manifest has no answers for day {}",
+                    missing.join(", ")
+                );
+                process::exit(1);
+            }
+            if !missing.is_empty() {
+                eprintln!(
+                    "warning: The manifest has no answers for day {}",
+                    missing.join(", ")
+                );
+            }
+            let mut counts = [0; 3];
+            for check in &checks {
+                println!("{}", check.to_text());
+                counts[match check.outcome {
+                    Outcome::Pass => 0,
+                    Outcome::Mismatch { .. } => 1,
+                    Outcome::Fail(_) => 2,
+                }] += 1;
+            }
+            println!(
+                "{} passed, {} mismatched, {} failed",
+                counts[0], counts[1], counts[2]
+            );
+            if counts[0] != checks.len() {
+                process::exit(1);
+            }
+        }
+        Command::Report {
+            days,
+            redact,
+            output,
+        } => {
+            // Always solved rather than cached, so that the timings and memory are for
+            // this build.
+            let mut results = Vec::new();
+            let mut failed = false;
+            pool::map_ordered(
+                &days,
+                options.jobs,
+                |&day| {
+                    let puzzle = registry::find(options.year, day).unwrap();
+                    let token = Token::with_timeout(options.timeout);
+                    runner::run_until(puzzle, &Source::Default, None, &token)
+                },
+                |day, result| match result {
+                    Ok(Ok(result)) => results.push(result),
+                    Ok(Err(e)) => {
+                        eprint!("{}", e.report());
+                        failed = true;
+                    }
+                    Err(message) => {
+                        eprintln!("error: day {:02} panicked: {}", day, message);
+                        failed = true;
+                    }
+                },
+            );
+            let table = report::markdown(options.year, &results, redact);
+            match output {
+                Some(path) => fs::write(&path, table).unwrap_or_else(|e| {
+                    eprintln!("error: Could not write {}: {}", path.display(), e);
+                    process::exit(1);
+                }),
+                None => print!("{}", table),
+            }
+            if failed {
+                process::exit(1);
+            }
+        }
+        Command::Generate {
+            day,
+            seed,
+            size,
+            output,
+        } => {
+            // The day was checked when parsing the arguments.
+            let puzzle = registry::find(options.year, day).unwrap();
+            let input = generate::generate(puzzle, seed, size);
+            match output {
+                Some(path) => fs::write(&path, input).unwrap_or_else(|e| {
+                    eprintln!("error: Could not write {}: {}", path.display(), e);
+                    process::exit(1);
+                }),
+                None => print!("{}", input),
+            }
+        }
+        Command::New { year, day } => {
+            let files = scaffold::scaffold(Path::new("."), year, day).unwrap_or_else(|e| {
+                eprintln!("error: {}", e);
+                process::exit(1);
+            });
+            for file in files {
+                println!("wrote {}", file.display());
+            }
+        }
+        Command::Fetch {
+            day,
+            base_url,
+            cache,
+        } => {
+            let fetcher = fetch::Fetcher::new(base_url, cache);
+            let fetched = fetcher.fetch(options.year, day).unwrap_or_else(|e| {
+                eprintln!("error: {}", e);
+                process::exit(1);
+            });
+            match &fetched {
+                fetch::Fetched::Cached(path) => println!("cached {}", path.display()),
+                fetch::Fetched::Downloaded(path) => println!("downloaded {}", path.display()),
+            }
+            let input_path = registry::data_dir(options.year).join(format!("day{:02}.txt", day));
+            if !input_path.exists() {
+                fs::create_dir_all(input_path.parent().unwrap())
+                    .and_then(|_| fs::copy(fetched.path(), &input_path))
+                    .unwrap_or_else(|e| {
+                        eprintln!("error: Could not write {}: {}", input_path.display(), e);
+                        process::exit(1);
+                    });
+                println!("wrote {}", input_path.display());
+            }
+        }
+        Command::Submit {
+            day,
+            part,
+            base_url,
+            history,
+        } => {
+            let puzzle = registry::find(options.year, day).unwrap();
+            let token = Token::with_timeout(options.timeout);
+            let result = runner::run_until(puzzle, &Source::Default, Some(part), &token)
+                .unwrap_or_else(|e| {
+                    eprint!("{}", e.report());
+                    process::exit(1);
+                });
+            let answer = match part {
+                Part::One => result.part_one,
+                Part::Two => result.part_two,
+            }
+            .unwrap()
+            .answer;
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            let submitter = submit::Submitter::new(base_url, history);
+            match submitter.submit(options.year, day, part, &answer, now) {
+                Ok(Ok(attempt)) if attempt.outcome == submit::Outcome::Right => {
+                    println!("{}: {}", attempt.answer, attempt.outcome)
+                }
+                Ok(Ok(attempt)) => {
+                    println!("{}: {}", attempt.answer, attempt.outcome);
+                    if !attempt.wait.is_zero() {
+                        println!("wait {}s before trying again", attempt.wait.as_secs());
+                    }
+                    process::exit(1);
+                }
+                Ok(Err(refusal)) => {
+                    eprintln!("error: Not sending {}: {}", answer, refusal);
+                    process::exit(1);
+                }
+                Err(e) => {
+                    eprintln!("error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        Command::Watch { day, interval } => {
+            let puzzle = registry::find(options.year, day).unwrap();
+            let mut session = watch::Session::new(puzzle, puzzle.input_path(), options.timeout);
+            println!(
+                "watching {} and its examples",
+                puzzle.input_path().display()
+            );
+            loop {
+                let printed = session.poll(|run| {
+                    let mut stdout = io::stdout().lock();
+                    writeln!(stdout, "== {} ==", run.file.display())?;
+                    match (&run.result, &run.diff) {
+                        (Ok(_), Some(diff)) => write!(stdout, "{}", diff)?,
+                        (Ok(result), None) => write!(stdout, "{}", result.to_text())?,
+                        (Err(e), _) => eprint!("{}", e.report()),
+                    }
+                    stdout.flush()
+                });
+                if let Err(e) = printed {
+                    eprintln!("error: Could not write the results: {}", e);
+                    process::exit(1);
+                }
+                thread::sleep(interval);
+            }
+        }
+        Command::ClearCache => match cache::clear(Path::new(cache::DEFAULT_PATH)) {
+            Ok(count) => println!("removed {} cached answers", count),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                process::exit(1);
+            }
+        },
     }
 }
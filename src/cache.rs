@@ -0,0 +1,303 @@
+//! Remembering answers so that days are not solved again for the same input.
+//!
+//! Each answer is appended to a file, one line each:
+//!
+//! ```text
+//! <year> <day> <part> <input hash> <version> <parse ns> <solve ns> <kind> <answer>
+//! ```
+//!
+//! The version is the crate's, so bumping it is how to throw away answers from
+//! solvers that have changed since. Lines from other versions are ignored. The kind is
+//! which [`Answer`] it is, and newlines and backslashes in the answer are escaped.
+
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{cli::Part, runner::DayResult, Answer};
+
+/// Where answers are cached unless told otherwise.
+pub const DEFAULT_PATH: &str = ".cache/results.txt";
+
+/// The version of the solvers, which cached answers must have been found by.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// What an answer is cached under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub year: usize,
+    pub day: usize,
+    pub part: Part,
+    /// The [hash](crate::input::hash) of the input.
+    pub input_hash: u64,
+}
+
+/// An answer from an earlier run, and how long finding it took then.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cached {
+    pub answer: Answer,
+    pub parse_time: Duration,
+    pub elapsed: Duration,
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(s: &str) -> String {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                unescaped.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                unescaped.push('\\');
+                chars.next();
+            }
+            (c, _) => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+fn to_line(key: &Key, cached: &Cached) -> String {
+    let (kind, answer) = match &cached.answer {
+        Answer::Number(n) => ("number", n.to_string()),
+        Answer::BigNumber(n) => ("big", n.to_string()),
+        Answer::Text(text) => ("text", text.clone()),
+        Answer::Grid(rows) => ("grid", rows.join("\n")),
+    };
+    format!(
+        "{} {} {} {:016x} {} {} {} {} {}",
+        key.year,
+        key.day,
+        match key.part {
+            Part::One => 1,
+            Part::Two => 2,
+        },
+        key.input_hash,
+        VERSION,
+        cached.parse_time.as_nanos(),
+        cached.elapsed.as_nanos(),
+        kind,
+        escape(&answer)
+    )
+}
+
+/// Reads a line written by [`to_line`], unless it is for another version.
+fn from_line(line: &str) -> Option<(Key, Cached)> {
+    let mut fields = line.splitn(9, ' ');
+    let mut next = || fields.next();
+    let key = Key {
+        year: next()?.parse().ok()?,
+        day: next()?.parse().ok()?,
+        part: match next()? {
+            "1" => Part::One,
+            "2" => Part::Two,
+            _ => return None,
+        },
+        input_hash: u64::from_str_radix(next()?, 16).ok()?,
+    };
+    if next()? != VERSION {
+        return None;
+    }
+    let parse_time = Duration::from_nanos(next()?.parse().ok()?);
+    let elapsed = Duration::from_nanos(next()?.parse().ok()?);
+    let kind = next()?;
+    let answer = unescape(next()?);
+    let answer = match kind {
+        "number" => Answer::Number(answer.parse().ok()?),
+        "big" => Answer::BigNumber(answer.parse().ok()?),
+        "text" => Answer::Text(answer),
+        "grid" => Answer::Grid(answer.lines().map(String::from).collect()),
+        _ => return None,
+    };
+    Some((
+        key,
+        Cached {
+            answer,
+            parse_time,
+            elapsed,
+        },
+    ))
+}
+
+/// The answers cached in a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cache {
+    path: PathBuf,
+    entries: HashMap<Key, Cached>,
+}
+
+impl Cache {
+    /// Reads the answers cached at `path` for this version, of which there are none if
+    /// the file does not exist. Lines that cannot be read are skipped.
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        let entries = match fs::read_to_string(&path) {
+            Ok(text) => text.lines().filter_map(from_line).collect(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(format!("Could not read {}: {}", path.display(), e)),
+        };
+        Ok(Cache { path, entries })
+    }
+
+    pub fn get(&self, key: &Key) -> Option<&Cached> {
+        self.entries.get(key)
+    }
+
+    /// Adds the answers in `result`, for a day of `year`, that are not already cached.
+    pub fn store(&mut self, year: usize, result: &DayResult) -> Result<(), String> {
+        let mut lines = String::new();
+        for (part, found) in [(Part::One, &result.part_one), (Part::Two, &result.part_two)] {
+            let Some(found) = found else {
+                continue;
+            };
+            let key = Key {
+                year,
+                day: result.day,
+                part,
+                input_hash: result.input_hash,
+            };
+            if found.cached || self.entries.contains_key(&key) {
+                continue;
+            }
+            let cached = Cached {
+                answer: found.answer.clone(),
+                parse_time: result.parse_time,
+                elapsed: found.elapsed,
+            };
+            lines += &to_line(&key, &cached);
+            lines.push('\n');
+            self.entries.insert(key, cached);
+        }
+        if lines.is_empty() {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Could not create {}: {}", parent.display(), e))?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(lines.as_bytes()))
+            .map_err(|e| format!("Could not write {}: {}", self.path.display(), e))
+    }
+}
+
+/// Removes the cache at `path`, returning how many answers it held for any version.
+pub fn clear(path: &Path) -> Result<usize, String> {
+    match fs::read_to_string(path) {
+        Ok(text) => {
+            fs::remove_file(path)
+                .map_err(|e| format!("Could not remove {}: {}", path.display(), e))?;
+            Ok(text.lines().count())
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(format!("Could not read {}: {}", path.display(), e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::PartResult;
+    use std::env;
+
+    #[test]
+    fn test_line() {
+        let key = Key {
+            year: 2020,
+            day: 20,
+            part: Part::Two,
+            input_hash: 0xab,
+        };
+        for answer in [
+            Answer::Number(5),
+            Answer::BigNumber(1 << 100),
+            Answer::Text("mxmxvkd,sqjhc fvjkl".to_string()),
+            Answer::Grid(vec!["#.\\".to_string(), "\\n#".to_string()]),
+        ] {
+            let cached = Cached {
+                answer,
+                parse_time: Duration::from_micros(3),
+                elapsed: Duration::from_secs(2),
+            };
+            let line = to_line(&key, &cached);
+            assert!(!line.contains('\n'));
+            assert_eq!(from_line(&line), Some((key, cached)));
+        }
+        assert_eq!(
+            to_line(
+                &key,
+                &Cached {
+                    answer: Answer::Number(5),
+                    parse_time: Duration::from_micros(3),
+                    elapsed: Duration::from_nanos(70),
+                }
+            ),
+            format!("2020 20 2 00000000000000ab {} 3000 70 number 5", VERSION)
+        );
+        assert_eq!(
+            from_line("2020 20 2 00000000000000ab 0.0.0 3000 70 number 5"),
+            None
+        );
+        assert_eq!(from_line("2020 20 3"), None);
+    }
+
+    #[test]
+    fn test_store() {
+        let path = env::temp_dir()
+            .join(format!("cache-{}", std::process::id()))
+            .join("results.txt");
+        let mut cache = Cache::load(&path).unwrap();
+        let result = DayResult {
+            day: 21,
+            name: "Allergen Assessment",
+            input: "data/day21.txt".to_string(),
+            input_hash: 0xab,
+            parse_time: Duration::from_micros(3),
+            parse_memory: None,
+            part_one: Some(PartResult {
+                answer: Answer::Number(5),
+                elapsed: Duration::from_nanos(70),
+                memory: None,
+                cached: false,
+            }),
+            part_two: None,
+        };
+        cache.store(2020, &result).unwrap();
+        cache.store(2020, &result).unwrap();
+        let key = Key {
+            year: 2020,
+            day: 21,
+            part: Part::One,
+            input_hash: 0xab,
+        };
+        let loaded = Cache::load(&path).unwrap();
+        assert_eq!(loaded, cache);
+        assert_eq!(
+            loaded.get(&key),
+            Some(&Cached {
+                answer: Answer::Number(5),
+                parse_time: Duration::from_micros(3),
+                elapsed: Duration::from_nanos(70),
+            })
+        );
+        assert_eq!(loaded.get(&Key { year: 2021, ..key }), None);
+
+        assert_eq!(clear(&path), Ok(1));
+        assert_eq!(clear(&path), Ok(0));
+        assert_eq!(Cache::load(&path).unwrap().get(&key), None);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,129 @@
+//! Stopping solvers which run for too long.
+//!
+//! Days with long running loops are handed a [`Token`], and check it as they go with
+//! [`Token::check`] or [`Token::check_every`], returning [`Cancelled`] with `?` once it
+//! has been cancelled or is past its deadline. The rest of the days never look at a
+//! token, so they always run to the end; the [runner](crate::runner) still reports
+//! them as having timed out if they took too long.
+
+use std::{
+    fmt::{self, Display},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// How many steps go by between looks at the token in [`Token::check_every`], which
+/// keeps checks cheap enough for the innermost loops.
+const CHECK_EVERY: usize = 1024;
+
+/// Tells solvers to stop, either when asked or once a deadline has passed. Clones
+/// share whether they have been cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct Token {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl Token {
+    /// A token which is only cancelled when asked.
+    pub fn new() -> Self {
+        Token::default()
+    }
+
+    /// A token which is cancelled once `timeout` has passed, if there is one. A
+    /// timeout too long to ever pass is no timeout at all.
+    pub fn with_timeout(timeout: Option<Duration>) -> Self {
+        Token {
+            cancelled: Arc::default(),
+            deadline: timeout.and_then(|timeout| Instant::now().checked_add(timeout)),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Fails once the token has been cancelled.
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Like [`check`](Token::check), but only looks at the token on every
+    /// [`CHECK_EVERY`]th `step`, for loops too tight to look every time.
+    pub fn check_every(&self, step: usize) -> Result<(), Cancelled> {
+        if step.is_multiple_of(CHECK_EVERY) {
+            self.check()
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A solver was stopped before it finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cancelled")
+    }
+}
+
+/// Runs `f` with a token which is never cancelled, for calling a solver that takes
+/// one when there is no reason to stop it.
+pub fn uncancelled<T>(f: impl FnOnce(&Token) -> Result<T, Cancelled>) -> T {
+    match f(&Token::new()) {
+        Ok(value) => value,
+        Err(Cancelled) => unreachable!("Nothing cancels a new token"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spin(token: &Token) -> Result<usize, Cancelled> {
+        let mut i = 0;
+        loop {
+            token.check_every(i)?;
+            i += 1;
+            if i == 10_000 {
+                return Ok(i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        assert_eq!(spin(&Token::new()), Ok(10_000));
+        assert_eq!(uncancelled(spin), 10_000);
+        let token = Token::new();
+        token.clone().cancel();
+        assert!(token.is_cancelled());
+        assert_eq!(token.check(), Err(Cancelled));
+        assert_eq!(spin(&token), Err(Cancelled));
+        // Only every so often is the token looked at.
+        assert_eq!(token.check_every(1), Ok(()));
+
+        let token = Token::with_timeout(Some(Duration::from_millis(10)));
+        assert_eq!(token.check(), Ok(()));
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(token.check(), Err(Cancelled));
+        assert!(!Token::with_timeout(None).is_cancelled());
+        assert!(!Token::with_timeout(Some(Duration::MAX)).is_cancelled());
+    }
+}
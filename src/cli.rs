@@ -0,0 +1,891 @@
+//! Command line handling for the `main` binary.
+
+use std::{
+    fs,
+    io::{self, BufRead, BufReader},
+    path::PathBuf,
+    time::Duration,
+};
+
+use crate::{bench, log::Level, manifest, registry, watch, Puzzle};
+
+/// The first year there were puzzles.
+const FIRST_YEAR: usize = 2015;
+
+pub const USAGE: &str = "Usage:
+    main all                                  Run every day against its own input
+    main <day>...                             Run the given days against their own input
+    main run <day> [--input PATH | - | --inputs DIR] [--part 1|2]
+                                              Run one day, reading PATH or `-` for stdin,
+                                              or every file in DIR and showing a table
+    main bench [all | <day>...] [--iterations N] [--save PATH]
+               [--compare PATH [--threshold PCT]]
+                                              Time each phase of the given days, N times
+                                              each. Save the medians as a baseline to
+                                              PATH, or fail if any phase is more than PCT
+                                              percent slower than in the baseline at PATH,
+                                              by default 10
+    main verify [all | <day>...] [--manifest PATH]
+                                              Check answers against a manifest, by default
+                                              data/answers.txt
+    main generate <day> [--seed N] [--size N] [--output PATH]
+                                              Write a random input for a day, the same one
+                                              for the same seed and size
+    main new <year> <day>                     Add a skeleton for a new day, run from the
+                                              root of the crate
+    main fetch <day> [--base-url URL] [--cache DIR]
+                                              Download a day's input into DIR, by default
+                                              .cache/inputs, and copy it to the day's
+                                              input path if that is missing. Needs a
+                                              session token in AOC_SESSION unless cached
+    main submit <day> 1|2 [--base-url URL] [--history PATH]
+                                              Send a part's answer for the day's input,
+                                              unless PATH, by default
+                                              .cache/submissions.txt, shows it is wrong or
+                                              that the site asked us to wait
+    main watch <day> [--interval MS]          Run a day again whenever its input or the
+                                              examples next to it change, every 500ms
+                                              unless given MS
+    main cache clear                          Forget every answer in .cache/results.txt
+    main report [all | <day>...] [--redact] [--output PATH]
+                                              Run the given days and write a Markdown
+                                              table of their answers, timings and peak
+                                              memory when built with alloc-stats, to
+                                              stdout unless given PATH
+
+Options:
+    --year YEAR                               Which year's days to use, by default 2020
+    --format text|json                        How to print results, one JSON object per
+                                              line for json
+    --jobs N                                  Run up to N days at once when running or
+                                              verifying several days. Running one at a
+                                              time shows a progress line on a terminal
+    --timeout SECS                            Give up on a day that runs for longer than
+                                              SECS when running, verifying, watching,
+                                              reporting or submitting. Parsing and days
+                                              which do not check for this can not be
+                                              stopped early, so they run to the end and
+                                              are then reported as timed out
+    --no-cache                                Solve days again when running them, even if
+                                              their answers for the input are cached
+    -v, -vv, -vvv                             Show progress, intermediate results or every
+                                              step of the solvers on stderr";
+
+/// How many times `bench` runs each day unless told otherwise.
+pub const DEFAULT_ITERATIONS: usize = 10;
+
+/// Where to read a puzzle input from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// The puzzle's own [input path](Puzzle::input_path).
+    Default,
+    Path(PathBuf),
+    Stdin,
+}
+
+impl Source {
+    pub fn open(&self, puzzle: &dyn Puzzle) -> io::Result<Box<dyn BufRead>> {
+        Ok(match self {
+            Source::Default => Box::new(BufReader::new(fs::File::open(puzzle.input_path())?)),
+            Source::Path(path) => Box::new(BufReader::new(fs::File::open(path)?)),
+            Source::Stdin => Box::new(io::stdin().lock()),
+        })
+    }
+
+    /// How to refer to the input in messages.
+    pub fn describe(&self, puzzle: &dyn Puzzle) -> String {
+        match self {
+            Source::Default => puzzle.input_path().display().to_string(),
+            Source::Path(path) => path.display().to_string(),
+            Source::Stdin => "<stdin>".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Options that apply to every command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The year that days are looked up in.
+    pub year: usize,
+    pub format: Format,
+    /// How many days to run at once.
+    pub jobs: usize,
+    /// How long a day may run before it is [cancelled](crate::cancel). Only some days
+    /// check for this, the others are reported as timed out once they finish.
+    pub timeout: Option<Duration>,
+    /// Whether running days may take answers from the [cache](crate::cache).
+    pub use_cache: bool,
+    /// How much the solvers should [log](crate::log).
+    pub verbosity: Level,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            year: registry::DEFAULT_YEAR,
+            format: Format::Text,
+            jobs: 1,
+            timeout: None,
+            use_cache: true,
+            verbosity: Level::Off,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Run both parts of each day against its own input.
+    Days(Vec<usize>),
+    /// Run a single day, optionally only one part of it.
+    Run {
+        day: usize,
+        source: Source,
+        part: Option<Part>,
+    },
+    /// Run a single day against every input in a directory, as a [batch](crate::batch).
+    Batch {
+        day: usize,
+        dir: PathBuf,
+        part: Option<Part>,
+    },
+    /// Time each phase of the given days.
+    Bench {
+        days: Vec<usize>,
+        iterations: usize,
+        /// Where to save the timings as a [baseline](crate::bench::Baseline).
+        save: Option<PathBuf>,
+        /// A baseline to compare the timings with.
+        compare: Option<PathBuf>,
+        /// How many percent slower a phase may get before it counts as a regression.
+        threshold: u32,
+    },
+    /// Check the given days against the answers in a [manifest](crate::manifest).
+    Verify { days: Vec<usize>, manifest: PathBuf },
+    /// Write a random input for a day, to stdout unless given a path.
+    Generate {
+        day: usize,
+        seed: u64,
+        size: Option<usize>,
+        output: Option<PathBuf>,
+    },
+    /// Add the [scaffolding](crate::scaffold) for a day that does not exist yet.
+    New { year: usize, day: usize },
+    /// [Fetch](crate::fetch) the input for a day, which does not need to exist yet.
+    Fetch {
+        day: usize,
+        base_url: Option<String>,
+        cache: Option<PathBuf>,
+    },
+    /// [Submit](crate::submit) the answer to one part of a day.
+    Submit {
+        day: usize,
+        part: Part,
+        base_url: Option<String>,
+        history: Option<PathBuf>,
+    },
+    /// [Watch](crate::watch) a day's inputs, running it again when they change.
+    Watch { day: usize, interval: Duration },
+    /// Remove every answer from the [cache](crate::cache).
+    ClearCache,
+    /// Run the given days and write a [report](crate::report) of the results, to
+    /// stdout unless given a path.
+    Report {
+        days: Vec<usize>,
+        redact: bool,
+        output: Option<PathBuf>,
+    },
+}
+
+fn parse_day(arg: &str, year: usize) -> Result<usize, String> {
+    let day = arg
+        .parse::<usize>()
+        .map_err(|_| format!("Expected a day number, found {:?}", arg))?;
+    match registry::find(year, day) {
+        Some(_) => Ok(day),
+        None => Err(format!("Have not implemented day {} of {}", day, year)),
+    }
+}
+
+fn parse_run(args: &[String], year: usize) -> Result<Command, String> {
+    let mut args = args.iter();
+    let day = parse_day(args.next().ok_or("Expected a day to run")?, year)?;
+    let mut source = Source::Default;
+    let mut dir = None;
+    let mut part = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--inputs" => {
+                dir = Some(PathBuf::from(
+                    args.next().ok_or("Expected a directory after --inputs")?,
+                ))
+            }
+            "--input" => {
+                source = match args.next().map(String::as_str) {
+                    Some("-") => Source::Stdin,
+                    Some(path) => Source::Path(PathBuf::from(path)),
+                    None => return Err("Expected a path after --input".to_string()),
+                }
+            }
+            "-" => source = Source::Stdin,
+            "--part" => {
+                part = match args.next().map(String::as_str) {
+                    Some("1") => Some(Part::One),
+                    Some("2") => Some(Part::Two),
+                    _ => return Err("Expected 1 or 2 after --part".to_string()),
+                }
+            }
+            arg => return Err(format!("Unexpected argument {:?}", arg)),
+        }
+    }
+    match dir {
+        Some(_) if source != Source::Default => {
+            Err("Expected only one of --input and --inputs".to_string())
+        }
+        Some(dir) => Ok(Command::Batch { day, dir, part }),
+        None => Ok(Command::Run { day, source, part }),
+    }
+}
+
+/// Parses a list of days, where `all` or no days at all means every day of `year`.
+fn parse_days(args: &[&String], year: usize) -> Result<Vec<usize>, String> {
+    let all = || {
+        let days: Vec<usize> = registry::year(year).map(|p| p.day()).collect();
+        if days.is_empty() {
+            Err(format!("Have not implemented any days of {}", year))
+        } else {
+            Ok(days)
+        }
+    };
+    match args {
+        [] => all(),
+        [arg] if arg.as_str() == "all" => all(),
+        _ => args.iter().map(|arg| parse_day(arg, year)).collect(),
+    }
+}
+
+fn parse_bench(args: &[String], year: usize) -> Result<Command, String> {
+    let mut days = Vec::new();
+    let mut iterations = DEFAULT_ITERATIONS;
+    let mut save = None;
+    let mut compare = None;
+    let mut threshold = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--iterations" => {
+                iterations = match args.next().map(|n| n.parse::<usize>()) {
+                    Some(Ok(n)) if n > 0 => n,
+                    _ => return Err("Expected a positive number after --iterations".to_string()),
+                }
+            }
+            "--save" => {
+                save = Some(PathBuf::from(
+                    args.next().ok_or("Expected a path after --save")?,
+                ))
+            }
+            "--compare" => {
+                compare = Some(PathBuf::from(
+                    args.next().ok_or("Expected a path after --compare")?,
+                ))
+            }
+            "--threshold" => {
+                threshold = match args.next().map(|n| n.parse::<u32>()) {
+                    Some(Ok(n)) => Some(n),
+                    _ => {
+                        return Err(
+                            "Expected a whole number of percent after --threshold".to_string()
+                        )
+                    }
+                }
+            }
+            _ => days.push(arg),
+        }
+    }
+    if threshold.is_some() && compare.is_none() {
+        return Err("--threshold needs a baseline to --compare with".to_string());
+    }
+    let days = parse_days(&days, year)?;
+    Ok(Command::Bench {
+        days,
+        iterations,
+        save,
+        compare,
+        threshold: threshold.unwrap_or(bench::DEFAULT_THRESHOLD),
+    })
+}
+
+fn parse_verify(args: &[String], year: usize) -> Result<Command, String> {
+    let mut days = Vec::new();
+    let mut manifest = manifest::default_path(year);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--manifest" => {
+                manifest = PathBuf::from(args.next().ok_or("Expected a path after --manifest")?)
+            }
+            _ => days.push(arg),
+        }
+    }
+    let days = parse_days(&days, year)?;
+    Ok(Command::Verify { days, manifest })
+}
+
+fn parse_report(args: &[String], year: usize) -> Result<Command, String> {
+    let mut days = Vec::new();
+    let mut redact = false;
+    let mut output = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--redact" => redact = true,
+            "--output" => {
+                output = Some(PathBuf::from(
+                    args.next().ok_or("Expected a path after --output")?,
+                ))
+            }
+            _ => days.push(arg),
+        }
+    }
+    let days = parse_days(&days, year)?;
+    Ok(Command::Report {
+        days,
+        redact,
+        output,
+    })
+}
+
+fn parse_generate(args: &[String], year: usize) -> Result<Command, String> {
+    let mut args = args.iter();
+    let day = parse_day(
+        args.next()
+            .ok_or("Expected a day to generate an input for")?,
+        year,
+    )?;
+    let mut seed = 0;
+    let mut size = None;
+    let mut output = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => {
+                seed = match args.next().map(|n| n.parse::<u64>()) {
+                    Some(Ok(n)) => n,
+                    _ => return Err("Expected a number after --seed".to_string()),
+                }
+            }
+            "--size" => {
+                size = match args.next().map(|n| n.parse::<usize>()) {
+                    Some(Ok(n)) if n > 0 => Some(n),
+                    _ => return Err("Expected a positive number after --size".to_string()),
+                }
+            }
+            "--output" => {
+                output = Some(PathBuf::from(
+                    args.next().ok_or("Expected a path after --output")?,
+                ))
+            }
+            arg => return Err(format!("Unexpected argument {:?}", arg)),
+        }
+    }
+    Ok(Command::Generate {
+        day,
+        seed,
+        size,
+        output,
+    })
+}
+
+fn parse_year(arg: Option<&String>) -> Result<usize, String> {
+    match arg.map(|year| year.parse::<usize>()) {
+        Some(Ok(year)) if year >= FIRST_YEAR => Ok(year),
+        _ => Err(format!("Expected a year from {} on", FIRST_YEAR)),
+    }
+}
+
+/// Parses a day which does not have to be implemented yet.
+fn parse_any_day(arg: Option<&String>) -> Result<usize, String> {
+    match arg.map(|day| day.parse::<usize>()) {
+        Some(Ok(day)) if (1..=25).contains(&day) => Ok(day),
+        _ => Err("Expected a day from 1 to 25".to_string()),
+    }
+}
+
+fn parse_new(args: &[String]) -> Result<Command, String> {
+    let year = parse_year(args.first())?;
+    let day = parse_any_day(args.get(1))?;
+    if let Some(arg) = args.get(2) {
+        return Err(format!("Unexpected argument {:?}", arg));
+    }
+    Ok(Command::New { year, day })
+}
+
+fn parse_fetch(args: &[String]) -> Result<Command, String> {
+    let mut args = args.iter();
+    let day = parse_any_day(args.next())?;
+    let mut base_url = None;
+    let mut cache = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--base-url" => {
+                base_url = Some(
+                    args.next()
+                        .ok_or("Expected a URL after --base-url")?
+                        .clone(),
+                )
+            }
+            "--cache" => {
+                cache = Some(PathBuf::from(
+                    args.next().ok_or("Expected a path after --cache")?,
+                ))
+            }
+            arg => return Err(format!("Unexpected argument {:?}", arg)),
+        }
+    }
+    Ok(Command::Fetch {
+        day,
+        base_url,
+        cache,
+    })
+}
+
+fn parse_submit(args: &[String], year: usize) -> Result<Command, String> {
+    let mut args = args.iter();
+    let day = parse_day(args.next().ok_or("Expected a day to submit")?, year)?;
+    let part = match args.next().map(String::as_str) {
+        Some("1") => Part::One,
+        Some("2") => Part::Two,
+        _ => return Err("Expected a part to submit, 1 or 2".to_string()),
+    };
+    let mut base_url = None;
+    let mut history = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--base-url" => {
+                base_url = Some(
+                    args.next()
+                        .ok_or("Expected a URL after --base-url")?
+                        .clone(),
+                )
+            }
+            "--history" => {
+                history = Some(PathBuf::from(
+                    args.next().ok_or("Expected a path after --history")?,
+                ))
+            }
+            arg => return Err(format!("Unexpected argument {:?}", arg)),
+        }
+    }
+    Ok(Command::Submit {
+        day,
+        part,
+        base_url,
+        history,
+    })
+}
+
+fn parse_watch(args: &[String], year: usize) -> Result<Command, String> {
+    let mut args = args.iter();
+    let day = parse_day(args.next().ok_or("Expected a day to watch")?, year)?;
+    let mut interval = watch::DEFAULT_INTERVAL;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--interval" => {
+                interval = match args.next().map(|n| n.parse::<u64>()) {
+                    Some(Ok(n)) if n > 0 => Duration::from_millis(n),
+                    _ => return Err("Expected a positive number after --interval".to_string()),
+                }
+            }
+            arg => return Err(format!("Unexpected argument {:?}", arg)),
+        }
+    }
+    Ok(Command::Watch { day, interval })
+}
+
+/// Removes the options that apply to every command from `args`.
+fn parse_options(args: &[String]) -> Result<(Options, Vec<String>), String> {
+    let mut options = Options::default();
+    let mut rest = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--year" => options.year = parse_year(args.next())?,
+            "--format" => {
+                options.format = match args.next().map(String::as_str) {
+                    Some("text") => Format::Text,
+                    Some("json") => Format::Json,
+                    _ => return Err("Expected text or json after --format".to_string()),
+                }
+            }
+            "--jobs" => {
+                options.jobs = match args.next().map(|n| n.parse::<usize>()) {
+                    Some(Ok(n)) if n > 0 => n,
+                    _ => return Err("Expected a positive number after --jobs".to_string()),
+                }
+            }
+            "--timeout" => {
+                let timeout = args
+                    .next()
+                    .and_then(|n| n.parse::<f64>().ok())
+                    .filter(|&n| n > 0.0)
+                    .and_then(|n| Duration::try_from_secs_f64(n).ok())
+                    .ok_or("Expected a positive number of seconds after --timeout")?;
+                options.timeout = Some(timeout);
+            }
+            "--no-cache" => options.use_cache = false,
+            "-v" | "-vv" | "-vvv" => {
+                options.verbosity = match options.verbosity as usize + arg.len() - 1 {
+                    0 => Level::Off,
+                    1 => Level::Info,
+                    2 => Level::Debug,
+                    _ => Level::Trace,
+                }
+            }
+            _ => rest.push(arg.clone()),
+        }
+    }
+    Ok((options, rest))
+}
+
+/// Parses the arguments given to `main`, not including the program name.
+pub fn parse_args(args: &[String]) -> Result<(Command, Options), String> {
+    let (options, args) = parse_options(args)?;
+    let command = parse_command(&args, options.year)?;
+    if options.format == Format::Json && !matches!(command, Command::Days(_) | Command::Run { .. })
+    {
+        return Err("--format json is only supported when running days".to_string());
+    }
+    if options.timeout.is_some()
+        && matches!(
+            command,
+            Command::Bench { .. }
+                | Command::Generate { .. }
+                | Command::New { .. }
+                | Command::Fetch { .. }
+        )
+    {
+        return Err(
+            "--timeout is not supported when benchmarking, generating, scaffolding \
+                    or fetching"
+                .to_string(),
+        );
+    }
+    Ok((command, options))
+}
+
+fn parse_command(args: &[String], year: usize) -> Result<Command, String> {
+    match args.first().map(String::as_str) {
+        None => Err("Expected a command".to_string()),
+        Some("run") => parse_run(&args[1..], year),
+        Some("bench") => parse_bench(&args[1..], year),
+        Some("verify") => parse_verify(&args[1..], year),
+        Some("report") => parse_report(&args[1..], year),
+        Some("generate") => parse_generate(&args[1..], year),
+        Some("new") => parse_new(&args[1..]),
+        Some("fetch") => parse_fetch(&args[1..]),
+        Some("submit") => parse_submit(&args[1..], year),
+        Some("watch") => parse_watch(&args[1..], year),
+        Some("cache") => match &args[1..] {
+            [clear] if clear == "clear" => Ok(Command::ClearCache),
+            _ => Err("Expected `cache clear`".to_string()),
+        },
+        Some(_) => Ok(Command::Days(parse_days(
+            &args.iter().collect::<Vec<_>>(),
+            year,
+        )?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &str) -> Vec<String> {
+        args.split_whitespace().map(|s| s.to_string()).collect()
+    }
+
+    fn parse_args(args: &[String]) -> Result<Command, String> {
+        super::parse_args(args).map(|(command, _)| command)
+    }
+
+    #[test]
+    fn test_parse_days() {
+        assert_eq!(parse_args(&args("3 14")), Ok(Command::Days(vec![3, 14])));
+        assert_eq!(
+            parse_args(&args("all")),
+            Ok(Command::Days((1..=25).collect()))
+        );
+        assert!(parse_args(&args("26")).is_err());
+        assert!(parse_args(&args("")).is_err());
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let (command, options) = super::parse_args(&args("run 3 --format json")).unwrap();
+        assert_eq!(options.format, Format::Json);
+        assert!(matches!(command, Command::Run { day: 3, .. }));
+        let (_, options) = super::parse_args(&args("--format text all")).unwrap();
+        assert_eq!(options.format, Format::Text);
+        assert!(super::parse_args(&args("all --format yaml")).is_err());
+        let (_, options) = super::parse_args(&args("all --jobs 4")).unwrap();
+        assert_eq!(options.jobs, 4);
+        assert!(super::parse_args(&args("all --jobs 0")).is_err());
+        let (_, options) = super::parse_args(&args("-vv run 23")).unwrap();
+        assert_eq!(options.verbosity, Level::Debug);
+        let (_, options) = super::parse_args(&args("-v 23 -vv")).unwrap();
+        assert_eq!(options.verbosity, Level::Trace);
+        assert!(super::parse_args(&args("bench --format json")).is_err());
+        let (_, options) = super::parse_args(&args("all --timeout 2.5")).unwrap();
+        assert_eq!(options.timeout, Some(Duration::from_millis(2500)));
+        assert!(super::parse_args(&args("all --timeout 0")).is_err());
+        assert!(super::parse_args(&args("all --timeout soon")).is_err());
+        assert!(super::parse_args(&args("all --timeout 1e20")).is_err());
+        assert!(super::parse_args(&args("bench --timeout 5")).is_err());
+        let (_, options) = super::parse_args(&args("submit 20 1 --timeout 5")).unwrap();
+        assert_eq!(options.timeout, Some(Duration::from_secs(5)));
+        let (_, options) = super::parse_args(&args("all")).unwrap();
+        assert!(options.use_cache);
+        let (_, options) = super::parse_args(&args("all --no-cache")).unwrap();
+        assert!(!options.use_cache);
+        assert_eq!(parse_args(&args("cache clear")), Ok(Command::ClearCache));
+        assert!(parse_args(&args("cache")).is_err());
+        assert!(parse_args(&args("cache clear all")).is_err());
+        let (_, options) = super::parse_args(&args("--year 2020 all")).unwrap();
+        assert_eq!(options.year, 2020);
+        assert!(super::parse_args(&args("--year 2014 all")).is_err());
+        assert!(super::parse_args(&args("--year 2021 all")).is_err());
+    }
+
+    #[test]
+    fn test_parse_bench() {
+        assert_eq!(
+            parse_args(&args("bench")),
+            Ok(Command::Bench {
+                days: (1..=25).collect(),
+                iterations: DEFAULT_ITERATIONS,
+                save: None,
+                compare: None,
+                threshold: bench::DEFAULT_THRESHOLD
+            })
+        );
+        assert_eq!(
+            parse_args(&args("bench 1 2 --iterations 3")),
+            Ok(Command::Bench {
+                days: vec![1, 2],
+                iterations: 3,
+                save: None,
+                compare: None,
+                threshold: bench::DEFAULT_THRESHOLD
+            })
+        );
+        assert_eq!(
+            parse_args(&args(
+                "bench 15 --compare base.txt --threshold 25 --save new.txt"
+            )),
+            Ok(Command::Bench {
+                days: vec![15],
+                iterations: DEFAULT_ITERATIONS,
+                save: Some(PathBuf::from("new.txt")),
+                compare: Some(PathBuf::from("base.txt")),
+                threshold: 25
+            })
+        );
+        assert!(parse_args(&args("bench --iterations 0")).is_err());
+        assert!(parse_args(&args("bench --threshold 25")).is_err());
+        assert!(parse_args(&args("bench --compare base.txt --threshold -5")).is_err());
+        assert!(parse_args(&args("bench --save")).is_err());
+    }
+
+    #[test]
+    fn test_parse_verify() {
+        assert_eq!(
+            parse_args(&args("verify 4")),
+            Ok(Command::Verify {
+                days: vec![4],
+                manifest: PathBuf::from("data/answers.txt")
+            })
+        );
+        assert_eq!(
+            parse_args(&args("verify --manifest theirs/answers.txt")),
+            Ok(Command::Verify {
+                days: (1..=25).collect(),
+                manifest: PathBuf::from("theirs/answers.txt")
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_report() {
+        assert_eq!(
+            parse_args(&args("report")),
+            Ok(Command::Report {
+                days: (1..=25).collect(),
+                redact: false,
+                output: None
+            })
+        );
+        assert_eq!(
+            parse_args(&args("report 1 15 --redact --output results.md")),
+            Ok(Command::Report {
+                days: vec![1, 15],
+                redact: true,
+                output: Some(PathBuf::from("results.md"))
+            })
+        );
+        assert!(parse_args(&args("report --output")).is_err());
+        assert!(parse_args(&args("report 26")).is_err());
+    }
+
+    #[test]
+    fn test_parse_generate() {
+        assert_eq!(
+            parse_args(&args("generate 7")),
+            Ok(Command::Generate {
+                day: 7,
+                seed: 0,
+                size: None,
+                output: None
+            })
+        );
+        assert_eq!(
+            parse_args(&args("generate 7 --seed 42 --size 10 --output day07.txt")),
+            Ok(Command::Generate {
+                day: 7,
+                seed: 42,
+                size: Some(10),
+                output: Some(PathBuf::from("day07.txt"))
+            })
+        );
+        assert!(parse_args(&args("generate 7 --seed -1")).is_err());
+        assert!(parse_args(&args("generate 7 --size 0")).is_err());
+        assert!(parse_args(&args("generate")).is_err());
+    }
+
+    #[test]
+    fn test_parse_run() {
+        assert_eq!(
+            parse_args(&args("run 5")),
+            Ok(Command::Run {
+                day: 5,
+                source: Source::Default,
+                part: None
+            })
+        );
+        assert_eq!(
+            parse_args(&args("run 5 --input mine.txt --part 2")),
+            Ok(Command::Run {
+                day: 5,
+                source: Source::Path(PathBuf::from("mine.txt")),
+                part: Some(Part::Two)
+            })
+        );
+        assert_eq!(
+            parse_args(&args("run 5 --part 1 -")),
+            Ok(Command::Run {
+                day: 5,
+                source: Source::Stdin,
+                part: Some(Part::One)
+            })
+        );
+        assert_eq!(
+            parse_args(&args("run 5 --inputs team --part 1")),
+            Ok(Command::Batch {
+                day: 5,
+                dir: PathBuf::from("team"),
+                part: Some(Part::One)
+            })
+        );
+        assert!(parse_args(&args("run 5 --inputs team --input mine.txt")).is_err());
+        assert!(parse_args(&args("run 5 --inputs")).is_err());
+        assert!(parse_args(&args("--format json run 5 --inputs team")).is_err());
+        assert!(parse_args(&args("run 5 --part 3")).is_err());
+        assert!(parse_args(&args("run 5 --input")).is_err());
+        assert!(parse_args(&args("run")).is_err());
+    }
+
+    #[test]
+    fn test_parse_new() {
+        assert_eq!(
+            parse_args(&args("new 2021 1")),
+            Ok(Command::New { year: 2021, day: 1 })
+        );
+        assert!(parse_args(&args("new 2021 26")).is_err());
+        assert!(parse_args(&args("new 2014 1")).is_err());
+        assert!(parse_args(&args("new 2021")).is_err());
+        assert!(parse_args(&args("new 2021 1 2")).is_err());
+    }
+
+    #[test]
+    fn test_parse_fetch() {
+        assert_eq!(
+            parse_args(&args("fetch 26")),
+            Err("Expected a day from 1 to 25".to_string())
+        );
+        assert_eq!(
+            parse_args(&args("--year 2016 fetch 3")),
+            Ok(Command::Fetch {
+                day: 3,
+                base_url: None,
+                cache: None
+            })
+        );
+        assert_eq!(
+            parse_args(&args(
+                "fetch 3 --base-url http://localhost:8000 --cache inputs"
+            )),
+            Ok(Command::Fetch {
+                day: 3,
+                base_url: Some("http://localhost:8000".to_string()),
+                cache: Some(PathBuf::from("inputs"))
+            })
+        );
+        assert!(parse_args(&args("fetch 3 --cache")).is_err());
+    }
+
+    #[test]
+    fn test_parse_submit() {
+        assert_eq!(
+            parse_args(&args("submit 3 2 --history mine.txt")),
+            Ok(Command::Submit {
+                day: 3,
+                part: Part::Two,
+                base_url: None,
+                history: Some(PathBuf::from("mine.txt"))
+            })
+        );
+        assert!(parse_args(&args("submit 3")).is_err());
+        assert!(parse_args(&args("submit 3 3")).is_err());
+        assert!(parse_args(&args("submit 26 1")).is_err());
+    }
+
+    #[test]
+    fn test_parse_watch() {
+        assert_eq!(
+            parse_args(&args("watch 20")),
+            Ok(Command::Watch {
+                day: 20,
+                interval: watch::DEFAULT_INTERVAL
+            })
+        );
+        assert_eq!(
+            parse_args(&args("watch 20 --interval 100")),
+            Ok(Command::Watch {
+                day: 20,
+                interval: Duration::from_millis(100)
+            })
+        );
+        assert!(parse_args(&args("watch 20 --interval 0")).is_err());
+        assert!(parse_args(&args("watch")).is_err());
+    }
+}
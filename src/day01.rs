@@ -1,33 +1,83 @@
 use itertools::Itertools;
 use std::io::BufRead;
 
-fn get_product_matching_sum(input: impl BufRead, length: usize, sum: usize) -> usize {
+use crate::generate::Rng;
+use crate::input::parse_lines;
+use crate::{Answer, ParseError, Solution};
+
+pub fn parse(input: impl BufRead) -> Result<Vec<usize>, ParseError> {
+    parse_lines(input, |line| Ok(line.parse::<usize>()?))
+}
+
+fn get_product_matching_sum(input: &[usize], length: usize, sum: usize) -> usize {
     input
-        .lines()
-        .map(|x| x.unwrap().parse::<usize>().unwrap())
+        .iter()
         .permutations(length)
-        .find(|x| x.iter().sum::<usize>() == sum)
-        .map(|x| x.iter().product())
+        .find(|x| x.iter().copied().sum::<usize>() == sum)
+        .map(|x| x.into_iter().product())
         .expect("Could not find numbers")
 }
 
-pub fn star_one(input: impl BufRead) -> usize {
+pub fn star_one(input: &[usize]) -> usize {
     get_product_matching_sum(input, 2, 2020)
 }
 
-pub fn star_two(input: impl BufRead) -> usize {
+pub fn star_two(input: &[usize]) -> usize {
     get_product_matching_sum(input, 3, 2020)
 }
 
+/// Generates `size` expenses where two of them add up to 2020, as do three others.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let pair = rng.range(1..2020);
+    let first = rng.range(1..2018);
+    let second = rng.range(1..2019 - first);
+    let mut expenses = vec![pair, 2020 - pair, first, second, 2020 - first - second];
+    while expenses.len() < size {
+        expenses.push(rng.range(1..2020));
+    }
+    rng.shuffle(&mut expenses);
+    expenses.iter().map(|x| format!("{}\n", x)).collect()
+}
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    type Input = Vec<usize>;
+
+    fn name(&self) -> &'static str {
+        "Report Repair"
+    }
+
+    fn day(&self) -> usize {
+        1
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(200))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two};
+    use super::{parse, star_one, star_two};
     use std::io::Cursor;
 
     #[test]
     fn test_star_one() {
         assert_eq!(
-            star_one(Cursor::new(b"1721\n979\n366\n299\n675\n1456")),
+            star_one(&parse(Cursor::new(b"1721\n979\n366\n299\n675\n1456")).unwrap()),
             514579
         );
     }
@@ -35,7 +85,7 @@ mod tests {
     #[test]
     fn test_star_two() {
         assert_eq!(
-            star_two(Cursor::new(b"1721\n979\n366\n299\n675\n1456")),
+            star_two(&parse(Cursor::new(b"1721\n979\n366\n299\n675\n1456")).unwrap()),
             241861950
         );
     }
@@ -1,63 +1,133 @@
 use regex::Regex;
 use std::io::BufRead;
 
+use crate::generate::Rng;
+use crate::input::parse_lines;
+use crate::{Answer, ParseError, Solution};
+
 lazy_static! {
     static ref RE: Regex =
         Regex::new(r"(?P<min>\d{1,})-(?P<max>\d{1,}) (?P<letter>\w): (?P<password>\w+)").unwrap();
 }
 
-fn process_passwords(input: impl BufRead) -> impl Iterator<Item = (usize, usize, char, String)> {
-    input.lines().map(|line| {
-        let line = line.unwrap();
-        let captures = RE
-            .captures(&line)
-            .expect("Bad line that does not match regex.");
-        let letter = captures["letter"].as_bytes()[0] as char;
-        let password = captures.get(4).unwrap().as_str();
-        let max = captures["max"].parse().unwrap();
-        let min = captures["min"].parse().unwrap();
-        (min, max, letter, password.to_string())
+#[derive(Debug)]
+pub struct Password {
+    min: usize,
+    max: usize,
+    letter: char,
+    password: String,
+}
+
+pub fn parse(input: impl BufRead) -> Result<Vec<Password>, ParseError> {
+    parse_lines(input, |line| {
+        let captures = RE.captures(line).ok_or_else(|| {
+            ParseError::new("Expected a password policy like `1-3 a: abcde`").with_column(1)
+        })?;
+        Ok(Password {
+            min: captures["min"].parse()?,
+            max: captures["max"].parse()?,
+            letter: captures["letter"].chars().next().unwrap(),
+            password: captures["password"].to_string(),
+        })
     })
 }
 
-pub fn star_one(input: impl BufRead) -> usize {
-    process_passwords(input)
-        .filter(|(min, max, letter, password)| {
+pub fn star_one(input: &[Password]) -> usize {
+    input
+        .iter()
+        .filter(|entry| {
             let mut found_letters = 0;
-            for x in password.chars() {
-                if x == *letter {
+            for x in entry.password.chars() {
+                if x == entry.letter {
                     found_letters += 1;
                 }
-                if found_letters > *max {
+                if found_letters > entry.max {
                     return false;
                 }
             }
-            found_letters >= *min
+            found_letters >= entry.min
         })
         .count()
 }
 
-pub fn star_two(input: impl BufRead) -> usize {
-    process_passwords(input)
-        .filter(|(min, max, letter, password)| {
-            let chars: Vec<char> = password.chars().collect();
-            if *min > chars.len() || *max > chars.len() {
-                panic!("Bad line: {}", password);
+pub fn star_two(input: &[Password]) -> usize {
+    input
+        .iter()
+        .filter(|entry| {
+            let chars: Vec<char> = entry.password.chars().collect();
+            if entry.min > chars.len() || entry.max > chars.len() {
+                panic!("Bad line: {}", entry.password);
             }
-            (chars[*min - 1] == *letter) ^ (chars[*max - 1] == *letter)
+            (chars[entry.min - 1] == entry.letter) ^ (chars[entry.max - 1] == entry.letter)
         })
         .count()
 }
 
+fn letter(rng: &mut Rng) -> char {
+    (b'a' + rng.range(0..26) as u8) as char
+}
+
+/// Generates `size` passwords and their policies.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let letter = letter(rng);
+            let length = rng.range(3..20);
+            let min = rng.range(1..length);
+            let max = rng.range(min + 1..length + 1);
+            let password: String = (0..length)
+                .map(|_| {
+                    if rng.chance(1, 3) {
+                        letter
+                    } else {
+                        self::letter(rng)
+                    }
+                })
+                .collect();
+            format!("{}-{} {}: {}\n", min, max, letter, password)
+        })
+        .collect()
+}
+
+pub struct Day02;
+
+impl Solution for Day02 {
+    type Input = Vec<Password>;
+
+    fn name(&self) -> &'static str {
+        "Password Philosophy"
+    }
+
+    fn day(&self) -> usize {
+        2
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(1000))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two};
+    use super::{parse, star_one, star_two};
     use std::io::Cursor;
 
     #[test]
     fn test_star_one() {
         assert_eq!(
-            star_one(Cursor::new(b"1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc")),
+            star_one(&parse(Cursor::new(b"1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc")).unwrap()),
             2
         );
     }
@@ -65,7 +135,7 @@ mod tests {
     #[test]
     fn test_star_two() {
         assert_eq!(
-            star_two(Cursor::new("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc")),
+            star_two(&parse(Cursor::new("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc")).unwrap()),
             1
         );
     }
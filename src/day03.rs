@@ -1,58 +1,131 @@
-use std::io::BufRead;
+use std::{convert::TryFrom, fmt::Display, io::BufRead};
 
+use crate::generate::Rng;
+use crate::grid::Grid;
+use crate::{Answer, ParseError, Solution};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Square {
+    Open,
+    Tree,
+}
+
+impl TryFrom<char> for Square {
+    type Error = String;
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '.' => Ok(Square::Open),
+            '#' => Ok(Square::Tree),
+            _ => Err(format!("Unexpected square {:?}", c)),
+        }
+    }
+}
+
+impl Display for Square {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = match self {
+            Square::Open => '.',
+            Square::Tree => '#',
+        };
+        f.write_fmt(format_args!("{}", c))
+    }
+}
+
+/// The squares passed through going `delta_y` down and `delta_x` right at a time,
+/// remembering that the map repeats to the right.
 fn get_path(
-    map: &'_ [Vec<char>],
+    map: &Grid<Square>,
     delta_y: usize,
     delta_x: usize,
-) -> impl Iterator<Item = char> + '_ {
-    map.iter()
-        .enumerate()
-        .filter(move |y| y.0 % delta_y == 0)
-        .map(move |(y, row)| row[(y / delta_y * delta_x) % row.len()])
+) -> impl Iterator<Item = Square> + '_ {
+    (0..map.rows())
+        .step_by(delta_y)
+        .map(move |y| map[(y, (y / delta_y * delta_x) % map.columns())])
 }
 
-pub fn star_one(input: impl BufRead) -> usize {
-    let map: Vec<Vec<char>> = input
-        .lines()
-        .map(|line| line.unwrap().chars().collect())
-        .collect();
+pub fn parse(input: impl BufRead) -> Result<Grid<Square>, ParseError> {
+    Grid::parse(input)
+}
 
-    (1..map[0].len())
-        .map(|movement| get_path(&map, 1, movement).filter(|&x| x == '#').count())
+pub fn star_one(map: &Grid<Square>) -> usize {
+    (1..map.columns())
+        .map(|movement| {
+            get_path(map, 1, movement)
+                .filter(|&x| x == Square::Tree)
+                .count()
+        })
         .max()
         .unwrap()
 }
 
-pub fn star_two(input: impl BufRead) -> usize {
+pub fn star_two(map: &Grid<Square>) -> usize {
     let movements = vec![(1, 1), (1, 3), (1, 5), (1, 7), (2, 1)];
-    let map: Vec<Vec<char>> = input
-        .lines()
-        .map(|line| line.unwrap().chars().collect())
-        .collect();
     movements
         .into_iter()
         .map(|movement| {
-            get_path(&map, movement.0, movement.1)
-                .filter(|&x| x == '#')
+            get_path(map, movement.0, movement.1)
+                .filter(|&x| x == Square::Tree)
                 .count()
         })
         .product()
 }
 
+/// Generates a map `size` rows tall and 31 columns wide.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let map = Grid::from_fn(size.max(1), 31, |_| {
+        if rng.chance(1, 4) {
+            Square::Tree
+        } else {
+            Square::Open
+        }
+    });
+    format!("{}\n", map)
+}
+
+pub struct Day03;
+
+impl Solution for Day03 {
+    type Input = Grid<Square>;
+
+    fn name(&self) -> &'static str {
+        "Toboggan Trajectory"
+    }
+
+    fn day(&self) -> usize {
+        3
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(323))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two};
+    use super::{parse, star_one, star_two};
     use std::io::Cursor;
 
     const INPUT: &[u8; 131] = b"..##.......\n#...#...#..\n.#....#..#.\n..#.#...#.#\n.#...##..#.\n..#.##.....\n.#.#.#....#\n.#........#\n#.##...#...\n#...##....#\n.#..#...#.#";
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(Cursor::new(INPUT)), 7);
+        assert_eq!(star_one(&parse(Cursor::new(INPUT)).unwrap()), 7);
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(Cursor::new(INPUT)), 336);
+        assert_eq!(star_two(&parse(Cursor::new(INPUT)).unwrap()), 336);
     }
 }
@@ -1,8 +1,13 @@
 use regex::Regex;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::io::BufRead;
 use std::str::FromStr;
 
+use crate::generate::Rng;
+use crate::input::sections;
+use crate::{Answer, ParseError, Solution};
+
 lazy_static! {
     static ref HEIGHT_REGEX: Regex = Regex::new(r"(?P<value>\d{1,})(?P<unit>cm|in)").unwrap();
     static ref COLOUR: Regex = Regex::new(r"^#(?P<value>[0-9a-f]{6})$").unwrap();
@@ -105,19 +110,12 @@ impl Passport {
     }
 }
 
-impl FromStr for Passport {
-    type Err = String;
+type Fields = HashMap<String, String>;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let hm: HashMap<&str, &str> = s
-            .split_whitespace()
-            .map(|section| {
-                let mut iter = section.splitn(2, ':');
-                let first = iter.next().unwrap();
-
-                (first, iter.next().expect(section))
-            })
-            .collect();
+impl TryFrom<&Fields> for Passport {
+    type Error = String;
+
+    fn try_from(hm: &Fields) -> Result<Self, Self::Error> {
         Ok(Passport {
             birth_year: hm
                 .get("byr")
@@ -190,39 +188,139 @@ const VALID_SECTIONS: [&str; 7] = [
            // "cid", // (Country ID)
 ];
 
-pub fn star_one(mut input: impl BufRead) -> usize {
-    let mut input_str = String::new();
-    input
-        .read_to_string(&mut input_str)
-        .expect("Could not read all of string");
-    input_str
-        .split("\n\n")
-        .filter(|&passport| {
-            let keys: HashSet<&str> = passport
-                .split_whitespace()
-                .map(|section| section.split(':').next().unwrap())
-                .collect();
-            VALID_SECTIONS.iter().all(|s| keys.contains(s))
+/// Parses each batch of passport fields, without validating them.
+pub fn parse(input: impl BufRead) -> Result<Vec<Fields>, ParseError> {
+    sections(input)
+        .map(|passport| {
+            let passport = passport?;
+            let mut fields = Fields::new();
+            for (number, line) in passport.lines() {
+                for section in line.split_whitespace() {
+                    let (key, value) = section.split_once(':').ok_or_else(|| {
+                        ParseError::at_token(line, section, "Expected a field like `key:value`")
+                            .in_line(number, line)
+                    })?;
+                    fields.insert(key.to_string(), value.to_string());
+                }
+            }
+            Ok(fields)
         })
+        .collect()
+}
+
+pub fn star_one(input: &[Fields]) -> usize {
+    input
+        .iter()
+        .filter(|&passport| VALID_SECTIONS.iter().all(|s| passport.contains_key(*s)))
         .count()
 }
 
-pub fn star_two(mut input: impl BufRead) -> usize {
-    let mut input_str = String::new();
+pub fn star_two(input: &[Fields]) -> usize {
     input
-        .read_to_string(&mut input_str)
-        .expect("Could not read all of string");
-    input_str
-        .split("\n\n")
-        .filter_map(|passport| passport.parse::<Passport>().ok())
+        .iter()
+        .filter_map(|passport| Passport::try_from(passport).ok())
         .filter(|p| p.is_valid())
         .count()
 }
 
+const EYE_COLOURS: [&str; 7] = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
+
+fn generate_value(rng: &mut Rng, field: &str, valid: bool) -> String {
+    match (field, valid) {
+        ("byr", true) => rng.range(1920..2003).to_string(),
+        ("byr", false) => rng.range(1900..1920).to_string(),
+        ("iyr", true) => rng.range(2010..2021).to_string(),
+        ("iyr", false) => rng.range(2000..2010).to_string(),
+        ("eyr", true) => rng.range(2020..2031).to_string(),
+        ("eyr", false) => rng.range(2031..2040).to_string(),
+        ("hgt", true) if rng.chance(1, 2) => format!("{}cm", rng.range(150..194)),
+        ("hgt", true) => format!("{}in", rng.range(59..77)),
+        ("hgt", false) if rng.chance(1, 2) => format!("{}cm", rng.range(100..150)),
+        ("hgt", false) => rng.range(150..194).to_string(),
+        ("hcl", true) => format!("#{:06x}", rng.range(0..1 << 24)),
+        ("hcl", false) => format!("{:06x}", rng.range(0..1 << 24)),
+        ("ecl", true) => rng.choose(&EYE_COLOURS).to_string(),
+        ("ecl", false) => rng.choose(&["gmt", "xry", "lzr", "zzz"]).to_string(),
+        ("pid", true) => format!("{:09}", rng.range(0..1_000_000_000)),
+        ("pid", false) => rng.range(0..100_000_000).to_string(),
+        _ => rng.range(50..350).to_string(),
+    }
+}
+
+/// Generates `size` passports, some of which are missing fields or have invalid
+/// values.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let passports: Vec<String> = (0..size)
+        .map(|_| {
+            let mut fields: Vec<&str> = VALID_SECTIONS.to_vec();
+            if rng.chance(1, 2) {
+                fields.push("cid");
+            }
+            if rng.chance(1, 4) {
+                fields.remove(rng.range(0..VALID_SECTIONS.len()));
+            }
+            let invalid = if rng.chance(1, 4) {
+                Some(rng.range(0..fields.len()))
+            } else {
+                None
+            };
+            let mut fields: Vec<String> = fields
+                .iter()
+                .enumerate()
+                .map(|(i, field)| {
+                    format!(
+                        "{}:{}",
+                        field,
+                        generate_value(rng, field, invalid != Some(i))
+                    )
+                })
+                .collect();
+            rng.shuffle(&mut fields);
+            let mut passport = fields[0].clone();
+            for field in &fields[1..] {
+                passport.push(if rng.chance(1, 4) { '\n' } else { ' ' });
+                passport += field;
+            }
+            passport
+        })
+        .collect();
+    format!("{}\n", passports.join("\n\n"))
+}
+
+pub struct Day04;
+
+impl Solution for Day04 {
+    type Input = Vec<Fields>;
+
+    fn name(&self) -> &'static str {
+        "Passport Processing"
+    }
+
+    fn day(&self) -> usize {
+        4
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(290))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two};
-    use std::io::Cursor;
+    use super::{parse, star_one, star_two};
+    use std::io::{Cursor, Read};
 
     const INPUT: &[u8; 282] = b"ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
 byr:1937 iyr:2017 cid:147 hgt:183cm
@@ -240,7 +338,15 @@ iyr:2011 ecl:brn hgt:59in";
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(Cursor::new(INPUT)), 2);
+        assert_eq!(star_one(&parse(Cursor::new(INPUT)).unwrap()), 2);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = parse(Cursor::new(INPUT.to_vec()).chain(Cursor::new("\n\nbyr:1937 iyr\n")))
+            .unwrap_err();
+        assert_eq!(error.line(), Some(15));
+        assert_eq!(error.column(), Some(10));
     }
 
     #[test]
@@ -258,7 +364,7 @@ ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277
 hgt:59cm ecl:zzz
 eyr:2038 hcl:74454a iyr:2023
 pid:3556412378 byr:2007";
-        assert_eq!(star_two(Cursor::new(input)), 0);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 0);
     }
 
     #[test]
@@ -275,6 +381,6 @@ pid:545766238 ecl:hzl
 eyr:2022
 
 iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719";
-        assert_eq!(star_two(Cursor::new(input)), 4);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 4);
     }
 }
@@ -1,8 +1,12 @@
 use std::io::BufRead;
 use std::str::FromStr;
 
+use crate::generate::Rng;
+use crate::input::parse_lines;
+use crate::{Answer, ParseError, Solution};
+
 #[derive(Debug, PartialEq)]
-struct Seat(usize, usize);
+pub struct Seat(usize, usize);
 
 impl Seat {
     fn get_id(&self) -> usize {
@@ -10,45 +14,57 @@ impl Seat {
     }
 }
 
+/// Narrows `range` down to a single value using one character per halving.
+fn binary_partition(
+    s: &str,
+    offset: usize,
+    range: (usize, usize),
+    lower: char,
+    upper: char,
+) -> Result<usize, ParseError> {
+    s.chars()
+        .enumerate()
+        .try_fold(range, |(low, high), (i, c)| {
+            let mid = low + (high - low) / 2;
+            if c == lower {
+                Ok((low, mid))
+            } else if c == upper {
+                Ok((mid + 1, high))
+            } else {
+                Err(
+                    ParseError::new(format!("Expected {} or {} but found {:?}", lower, upper, c))
+                        .with_column(offset + i + 1),
+                )
+            }
+        })
+        .map(|(low, _high)| low)
+}
+
 impl FromStr for Seat {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let row = s.chars().take(7).fold((0, 127), |(low, high), c| {
-            let mid = low + (high - low) / 2;
-            match c {
-                'F' => (low, mid),
-                'B' => (mid + 1, high),
-                _ => unreachable!(),
-            }
-        });
-        let column = s.chars().skip(7).fold((0, 7), |(low, high), c| {
-            let mid = low + (high - low) / 2;
-            match c {
-                'L' => (low, mid),
-                'R' => (mid + 1, high),
-                _ => unreachable!(),
-            }
-        });
-        Ok(Seat(row.0, column.0))
+        if s.len() != 10 || !s.is_ascii() {
+            return Err(ParseError::new(
+                "A seat should be 10 characters like `FBFBBFFRLR`",
+            ));
+        }
+        let row = binary_partition(&s[..7], 0, (0, 127), 'F', 'B')?;
+        let column = binary_partition(&s[7..], 7, (0, 7), 'L', 'R')?;
+        Ok(Seat(row, column))
     }
 }
 
-pub fn star_one(input: impl BufRead) -> usize {
-    input
-        .lines()
-        .filter_map(|s| s.unwrap().parse::<Seat>().ok())
-        .map(|seat| seat.get_id())
-        .max()
-        .unwrap()
+pub fn parse(input: impl BufRead) -> Result<Vec<Seat>, ParseError> {
+    parse_lines(input, |line| line.parse::<Seat>())
+}
+
+pub fn star_one(input: &[Seat]) -> usize {
+    input.iter().map(|seat| seat.get_id()).max().unwrap()
 }
 
-pub fn star_two(input: impl BufRead) -> usize {
-    let mut sorted_seats: Vec<usize> = input
-        .lines()
-        .map(|s| s.unwrap().parse::<Seat>().unwrap())
-        .map(|seat| seat.get_id())
-        .collect();
+pub fn star_two(input: &[Seat]) -> usize {
+    let mut sorted_seats: Vec<usize> = input.iter().map(|seat| seat.get_id()).collect();
     sorted_seats.sort_unstable();
 
     sorted_seats
@@ -58,9 +74,69 @@ pub fn star_two(input: impl BufRead) -> usize {
         + 1
 }
 
+/// Generates the boarding passes for `size` seats with neighbouring ids, leaving out
+/// one seat in the middle.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.clamp(2, 1000);
+    let first = rng.range(0..1024 - size);
+    let missing = rng.range(first + 1..first + size);
+    let mut seats: Vec<usize> = (first..=first + size).filter(|&id| id != missing).collect();
+    rng.shuffle(&mut seats);
+    seats
+        .iter()
+        .map(|id| {
+            let row: String = (0..7)
+                .rev()
+                .map(|bit| {
+                    if (id >> 3) & (1 << bit) == 0 {
+                        'F'
+                    } else {
+                        'B'
+                    }
+                })
+                .collect();
+            let column: String = (0..3)
+                .rev()
+                .map(|bit| if id & (1 << bit) == 0 { 'L' } else { 'R' })
+                .collect();
+            format!("{}{}\n", row, column)
+        })
+        .collect()
+}
+
+pub struct Day05;
+
+impl Solution for Day05 {
+    type Input = Vec<Seat>;
+
+    fn name(&self) -> &'static str {
+        "Binary Boarding"
+    }
+
+    fn day(&self) -> usize {
+        5
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(800))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two, Seat};
+    use super::{parse, star_one, star_two, Seat};
     use std::io::Cursor;
 
     #[test]
@@ -71,15 +147,22 @@ mod tests {
         assert_eq!("BBFFBBFRLL".parse::<Seat>(), Ok(Seat(102, 4)));
     }
 
+    #[test]
+    fn test_parsing_bad_seat() {
+        assert_eq!("BBFFBBFRL".parse::<Seat>().unwrap_err().column(), None);
+        assert_eq!("BBFFXBFRLL".parse::<Seat>().unwrap_err().column(), Some(5));
+        assert_eq!("BBFFBBFRLF".parse::<Seat>().unwrap_err().column(), Some(10));
+    }
+
     #[test]
     #[ignore]
     fn test_star_one() {
-        assert_eq!(star_one(Cursor::new("INPUT")), 7);
+        assert_eq!(star_one(&parse(Cursor::new("INPUT")).unwrap()), 7);
     }
 
     #[test]
     #[ignore]
     fn test_star_two() {
-        assert_eq!(star_two(Cursor::new("INPUT")), 336);
+        assert_eq!(star_two(&parse(Cursor::new("INPUT")).unwrap()), 336);
     }
 }
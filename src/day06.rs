@@ -1,16 +1,39 @@
 use std::collections::HashSet;
 use std::io::BufRead;
 
-pub fn star_one(mut input: impl BufRead) -> usize {
-    let mut input_str = String::new();
+use crate::generate::Rng;
+use crate::input::sections;
+use crate::{Answer, ParseError, Solution};
+
+/// Each group is a list of the questions each person answered yes to.
+pub fn parse(input: impl BufRead) -> Result<Vec<Vec<String>>, ParseError> {
+    sections(input)
+        .map(|group| {
+            group?
+                .lines()
+                .map(|(number, person)| {
+                    match person.char_indices().find(|(_, c)| !c.is_ascii_lowercase()) {
+                        Some((i, c)) => Err(ParseError::new(format!(
+                            "Questions should be a-z but found {:?}",
+                            c
+                        ))
+                        .with_column(i + 1)
+                        .in_line(number, person)),
+                        None => Ok(person.to_string()),
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+pub fn star_one(input: &[Vec<String>]) -> usize {
     input
-        .read_to_string(&mut input_str)
-        .expect("Could not read all of string");
-    input_str
-        .split("\n\n")
+        .iter()
         .map(|group| {
             group
-                .chars()
+                .iter()
+                .flat_map(|person| person.chars())
                 .filter(|c| !c.is_whitespace())
                 .collect::<HashSet<char>>()
         })
@@ -18,16 +41,12 @@ pub fn star_one(mut input: impl BufRead) -> usize {
         .sum()
 }
 
-pub fn star_two(mut input: impl BufRead) -> usize {
-    let mut input_str = String::new();
+pub fn star_two(input: &[Vec<String>]) -> usize {
     input
-        .read_to_string(&mut input_str)
-        .expect("Could not read all of string");
-    input_str
-        .split("\n\n")
+        .iter()
         .map(|group| {
             group
-                .lines()
+                .iter()
                 .map(|person| person.chars().collect::<HashSet<char>>())
                 // Wish there was fold_first on stable but this does the same job.
                 .fold(None, |acc: Option<HashSet<char>>, person| match acc {
@@ -39,16 +58,76 @@ pub fn star_two(mut input: impl BufRead) -> usize {
         .sum()
 }
 
+/// Generates the answers of `size` groups of people.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let groups: Vec<String> = (0..size)
+        .map(|_| {
+            // Questions everyone in the group answered yes to.
+            let count = rng.range(0..6);
+            let shared = rng.sample(0..26, count);
+            let people: Vec<String> = (0..rng.range(1..6))
+                .map(|_| {
+                    let mut questions = shared.clone();
+                    let count = rng.range(1..8);
+                    for question in rng.sample(0..26, count) {
+                        if !questions.contains(&question) {
+                            questions.push(question);
+                        }
+                    }
+                    rng.shuffle(&mut questions);
+                    questions
+                        .iter()
+                        .map(|&q| (b'a' + q as u8) as char)
+                        .collect()
+                })
+                .collect();
+            people.join("\n")
+        })
+        .collect();
+    format!("{}\n", groups.join("\n\n"))
+}
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    type Input = Vec<Vec<String>>;
+
+    fn name(&self) -> &'static str {
+        "Custom Customs"
+    }
+
+    fn day(&self) -> usize {
+        6
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(490))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two};
+    use super::{parse, star_one, star_two};
     use std::io::Cursor;
 
     #[test]
     fn test_star_one() {
         assert_eq!(
-            star_one(Cursor::new(
-                "abc
+            star_one(
+                &parse(Cursor::new(
+                    "abc
 
 a
 b
@@ -63,7 +142,9 @@ a
 a
 
 b"
-            )),
+                ))
+                .unwrap()
+            ),
             11
         );
     }
@@ -71,6 +152,6 @@ b"
     #[test]
     #[ignore]
     fn test_star_two() {
-        assert_eq!(star_two(Cursor::new("INPUT")), 336);
+        assert_eq!(star_two(&parse(Cursor::new("INPUT")).unwrap()), 336);
     }
 }
@@ -2,40 +2,56 @@ use regex::Regex;
 use std::collections::{HashMap, HashSet};
 use std::io::BufRead;
 
+use crate::generate::Rng;
+use crate::input::parse_lines;
+use crate::{Answer, ParseError, Solution};
+
 // (?P<min>\w{1,}) bags contain 1 bright white bag(s)?, 2 muted yellow bags.
 
 lazy_static! {
     static ref RE: Regex = Regex::new(r"(?P<amount>\d{1,}) (?P<colour>.+) bag(s)?").unwrap();
 }
 
-pub fn star_one(input: impl BufRead) -> usize {
-    let hashmap = input
-        .lines()
-        .map_while(Result::ok)
-        .map(|x| {
-            let mut iter = x.splitn(2, " bags contain ");
-            let parent = iter.next().unwrap();
-            let children = iter
-                .next()
-                .unwrap_or_else(|| panic!("Expected {} (parent: {})", x, parent));
-            (parent.to_string(), children.to_string())
-        })
-        .filter(|(_parent, children)| children != "no other bags.")
-        .fold(
-            HashMap::new(),
-            |mut hm: HashMap<String, Vec<String>>, (parent, children)| {
-                for child in children.split(',') {
-                    let captures = RE
-                        .captures(child)
-                        .expect("Bad line that does not match regex.");
-
-                    hm.entry(captures.name("colour").unwrap().as_str().to_string())
-                        .or_default()
-                        .push(parent.trim().to_string());
-                }
-                hm
-            },
-        );
+type Rules = HashMap<String, Vec<(String, usize)>>;
+
+/// Maps each bag colour to the colours and number of bags it must contain.
+pub fn parse(input: impl BufRead) -> Result<Rules, ParseError> {
+    Ok(parse_lines(input, |x| {
+        let (parent, children) = x.split_once(" bags contain ").ok_or_else(|| {
+            ParseError::new("Expected a rule like `<colour> bags contain ...`").with_column(1)
+        })?;
+        if children == "no other bags." {
+            return Ok((parent.trim().to_string(), vec![]));
+        }
+        let children = children
+            .split(',')
+            .map(|child| {
+                let captures = RE.captures(child).ok_or_else(|| {
+                    ParseError::at_token(x, child, "Expected a bag like `2 muted yellow bags`")
+                })?;
+
+                Ok((
+                    captures["colour"].to_string(),
+                    captures["amount"].parse::<usize>()?,
+                ))
+            })
+            .collect::<Result<Vec<_>, ParseError>>()?;
+        Ok((parent.trim().to_string(), children))
+    })?
+    .into_iter()
+    .collect())
+}
+
+pub fn star_one(input: &Rules) -> usize {
+    let hashmap = input.iter().fold(
+        HashMap::new(),
+        |mut hm: HashMap<&str, Vec<&str>>, (parent, children)| {
+            for (child, _amount) in children {
+                hm.entry(child).or_default().push(parent);
+            }
+            hm
+        },
+    );
     let mut stack = vec!["shiny gold"];
     let mut visited = HashSet::new();
     while let Some(node) = stack.pop() {
@@ -51,7 +67,7 @@ pub fn star_one(input: impl BufRead) -> usize {
     visited.len() - 1
 }
 
-fn get_number_of_bags(map: &HashMap<String, Vec<(String, usize)>>, id: &str) -> usize {
+fn get_number_of_bags(map: &Rules, id: &str) -> usize {
     match map.get(id) {
         None => 1,
         Some(children) => {
@@ -64,50 +80,151 @@ fn get_number_of_bags(map: &HashMap<String, Vec<(String, usize)>>, id: &str) ->
     }
 }
 
-pub fn star_two(input: impl BufRead) -> usize {
-    let hashmap: HashMap<_, Vec<(String, usize)>> = input
-        .lines()
-        .map_while(Result::ok)
-        .map(|x| {
-            let mut iter = x.splitn(2, " bags contain ");
-            let parent = iter.next().unwrap();
-            let children = iter
-                .next()
-                .unwrap_or_else(|| panic!("Expected {} (parent: {})", x, parent));
-            (parent.trim().to_string(), children.to_string())
+pub fn star_two(input: &Rules) -> usize {
+    get_number_of_bags(input, "shiny gold") - 1
+}
+
+const ADJECTIVES: [&str; 24] = [
+    "bright", "clear", "dark", "dim", "dotted", "drab", "dull", "faded", "light", "mirrored",
+    "muted", "pale", "plaid", "posh", "shiny", "striped", "vibrant", "wavy", "dusky", "pale",
+    "bold", "soft", "deep", "warm",
+];
+
+const COLOURS: [&str; 30] = [
+    "aqua",
+    "beige",
+    "black",
+    "blue",
+    "bronze",
+    "brown",
+    "chartreuse",
+    "coral",
+    "crimson",
+    "cyan",
+    "fuchsia",
+    "gold",
+    "gray",
+    "green",
+    "indigo",
+    "lavender",
+    "lime",
+    "magenta",
+    "maroon",
+    "olive",
+    "orange",
+    "plum",
+    "purple",
+    "red",
+    "salmon",
+    "silver",
+    "tan",
+    "teal",
+    "violet",
+    "white",
+];
+
+/// How deep bags can be nested, which keeps the number of bags inside small enough
+/// to count.
+const LEVELS: usize = 8;
+
+/// Generates rules for `size` colours of bag, including shiny gold.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut colours = vec!["shiny gold".to_string()];
+    while colours.len() < size.clamp(2, 600) {
+        let colour = format!("{} {}", rng.choose(&ADJECTIVES), rng.choose(&COLOURS));
+        if !colours.contains(&colour) {
+            colours.push(colour);
+        }
+    }
+    // Bags only hold bags from deeper levels so that no bag ends up inside itself.
+    let levels: Vec<usize> = (0..colours.len())
+        .map(|i| if i == 0 { 3 } else { rng.range(0..LEVELS) })
+        .collect();
+    let mut rules: Vec<Vec<(usize, usize)>> = (0..colours.len())
+        .map(|i| {
+            let deeper: Vec<usize> = (0..colours.len())
+                .filter(|&j| levels[j] > levels[i])
+                .collect();
+            if deeper.is_empty() || rng.chance(1, 5) {
+                return vec![];
+            }
+            let count = rng.range(1..5.min(deeper.len() + 1));
+            rng.sample(0..deeper.len(), count)
+                .into_iter()
+                .map(|j| (deeper[j], rng.range(1..6)))
+                .collect()
         })
-        .filter(|(_parent, children)| children != "no other bags.")
-        .map(|(parent, children)| {
-            let children = children
-                .split(',')
-                .map(|child| {
-                    let captures = RE
-                        .captures(child)
-                        .expect("Bad line that does not match regex.");
-
-                    (
-                        captures.name("colour").unwrap().as_str().to_string(),
-                        captures
-                            .name("amount")
-                            .unwrap()
-                            .as_str()
-                            .parse::<usize>()
-                            .unwrap(),
-                    )
-
-                    // .or_insert_with(Vec::new)
-                    // .push(parent.trim().to_string());
-                })
-                .collect::<Vec<_>>();
-            (parent, children)
+        .collect();
+    // Make sure shiny gold bags hold and are held by other bags.
+    if rules[0].is_empty() {
+        if let Some(j) = (1..colours.len()).find(|&j| levels[j] > levels[0]) {
+            rules[0].push((j, rng.range(1..6)));
+        }
+    }
+    if rules
+        .iter()
+        .all(|children| children.iter().all(|&(j, _)| j != 0))
+    {
+        if let Some(i) = (1..colours.len()).find(|&i| levels[i] < levels[0]) {
+            rules[i].push((0, rng.range(1..6)));
+        }
+    }
+    let mut lines: Vec<String> = rules
+        .iter()
+        .enumerate()
+        .map(|(i, children)| {
+            let children = if children.is_empty() {
+                "no other bags".to_string()
+            } else {
+                children
+                    .iter()
+                    .map(|&(j, amount)| {
+                        let plural = if amount == 1 { "" } else { "s" };
+                        format!("{} {} bag{}", amount, colours[j], plural)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            format!("{} bags contain {}.\n", colours[i], children)
         })
         .collect();
-    get_number_of_bags(&hashmap, "shiny gold") - 1
+    rng.shuffle(&mut lines);
+    lines.concat()
+}
+
+pub struct Day07;
+
+impl Solution for Day07 {
+    type Input = Rules;
+
+    fn name(&self) -> &'static str {
+        "Handy Haversacks"
+    }
+
+    fn day(&self) -> usize {
+        7
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(600))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two};
+    use super::{parse, star_one, star_two};
     use std::io::Cursor;
 
     const INPUT: &[u8] = b"light red bags contain 1 bright white bag, 2 muted yellow bags.
@@ -122,12 +239,12 @@ dotted black bags contain no other bags.";
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(Cursor::new(INPUT)), 4);
+        assert_eq!(star_one(&parse(Cursor::new(INPUT)).unwrap()), 4);
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(Cursor::new(INPUT)), 32);
+        assert_eq!(star_two(&parse(Cursor::new(INPUT)).unwrap()), 32);
         let input = "shiny gold bags contain 2 dark red bags.
 dark red bags contain 2 dark orange bags.
 dark orange bags contain 2 dark yellow bags.
@@ -135,6 +252,6 @@ dark yellow bags contain 2 dark green bags.
 dark green bags contain 2 dark blue bags.
 dark blue bags contain 2 dark violet bags.
 dark violet bags contain no other bags.";
-        assert_eq!(star_two(Cursor::new(input)), 126);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 126);
     }
 }
@@ -2,27 +2,36 @@ use std::collections::HashSet;
 use std::io::BufRead;
 use std::str::FromStr;
 
+use crate::generate::Rng;
+use crate::input::parse_lines;
+use crate::{Answer, ParseError, Solution};
+
 #[derive(Debug)]
-enum Instruction {
+pub enum Instruction {
     Accumulate(isize),
     Jump(isize),
     NoOperation(isize),
 }
 
 impl FromStr for Instruction {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let chars: Vec<&str> = s.split(' ').collect();
-        match chars[0] {
-            "acc" => Ok(Instruction::Accumulate(
-                chars[1]
-                    .parse::<isize>()
-                    .unwrap_or_else(|_| panic!("Could not convert {} to usize", chars[1])),
-            )),
-            "jmp" => Ok(Instruction::Jump(chars[1].parse().unwrap())),
-            "nop" => Ok(Instruction::NoOperation(chars[1].parse().unwrap())),
-            _ => panic!("Could not parse {} to Instruction", s),
+        let (operation, argument) = s.split_once(' ').ok_or_else(|| {
+            ParseError::new("Expected an instruction like `acc +1`").with_column(1)
+        })?;
+        let value = argument.parse::<isize>().map_err(|_| {
+            ParseError::at_token(
+                s,
+                argument,
+                format!("Could not parse {} as an argument", argument),
+            )
+        })?;
+        match operation {
+            "acc" => Ok(Instruction::Accumulate(value)),
+            "jmp" => Ok(Instruction::Jump(value)),
+            "nop" => Ok(Instruction::NoOperation(value)),
+            _ => Err(ParseError::new(format!("Unknown operation {}", operation)).with_column(1)),
         }
     }
 }
@@ -32,14 +41,12 @@ enum Error {
     PointerOverflow,
 }
 
-pub fn star_one(input: impl BufRead) -> usize {
-    let instructions: Vec<Instruction> = input
-        .lines()
-        .map_while(Result::ok)
-        .map(|x| x.parse().unwrap())
-        .collect();
+pub fn parse(input: impl BufRead) -> Result<Vec<Instruction>, ParseError> {
+    parse_lines(input, |line| line.parse())
+}
 
-    run(&instructions, instructions.len())
+pub fn star_one(instructions: &[Instruction]) -> usize {
+    run(instructions, instructions.len())
         .map_err(|err| match err {
             Error::InfiniteLoop(x) => x,
             _ => panic!(),
@@ -76,25 +83,120 @@ fn run(instructions: &[Instruction], flip: usize) -> Result<usize, Error> {
     }
 }
 
-pub fn star_two(input: impl BufRead) -> usize {
-    let instructions: Vec<Instruction> = input
-        .lines()
-        .map_while(Result::ok)
-        .map(|x| x.parse().unwrap())
-        .collect();
+pub fn star_two(instructions: &[Instruction]) -> usize {
     instructions
         .iter()
         .enumerate()
         // Remove acculumator instructions
         .filter(|(_index, instruction)| !matches!(instruction, Instruction::Accumulate(_)))
         .map(|x| x.0)
-        .find_map(|index| run(&instructions, index).ok())
+        .find_map(|index| run(instructions, index).ok())
         .unwrap()
 }
 
+fn offset(rng: &mut Rng) -> isize {
+    let offset = rng.range(1..50) as isize;
+    if rng.chance(1, 2) {
+        offset
+    } else {
+        -offset
+    }
+}
+
+/// Generates a program of `size` instructions which loops forever, but finishes when
+/// one of its `nop`s is changed to a `jmp`.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(8);
+    // The instruction to repair, which is followed by a jump straight back to it.
+    let broken = rng.range(1..size - 2);
+    let trap = broken + 1;
+    // The order that the repaired program runs its instructions in, jumping between
+    // runs of instructions so that there is something to accumulate.
+    let rest: Vec<usize> = (1..size).filter(|&i| i != broken && i != trap).collect();
+    let mut runs: Vec<&[usize]> = Vec::new();
+    let mut rest = &rest[..];
+    while !rest.is_empty() {
+        let (run, tail) = rest.split_at(rng.range(1..6).min(rest.len()));
+        runs.push(run);
+        rest = tail;
+    }
+    rng.shuffle(&mut runs);
+    runs.truncate(rng.range(runs.len() / 2..runs.len()));
+    let mut path = runs.concat();
+    path.insert(rng.range(0..path.len() + 1), broken);
+    path.insert(0, 0);
+
+    let mut program: Vec<Option<String>> = vec![None; size];
+    // The accumulator must never go below zero on the way.
+    let mut accumulator = 0;
+    for (i, &position) in path.iter().enumerate() {
+        let next = path.get(i + 1).copied().unwrap_or(size);
+        let jump = next as isize - position as isize;
+        program[position] = Some(if position == broken {
+            format!("nop {:+}", jump)
+        } else if jump == 1 && rng.chance(2, 3) {
+            let value = offset(rng);
+            let value = if accumulator + value < 0 {
+                -value
+            } else {
+                value
+            };
+            accumulator += value;
+            format!("acc {:+}", value)
+        } else if jump == 1 && rng.chance(1, 2) {
+            format!("nop {:+}", offset(rng))
+        } else {
+            format!("jmp {:+}", jump)
+        });
+    }
+    program[trap] = Some("jmp -1".to_string());
+    program
+        .into_iter()
+        .enumerate()
+        .map(|(position, instruction)| {
+            let instruction = instruction.unwrap_or_else(|| match rng.range(0..3) {
+                0 => format!("acc {:+}", offset(rng)),
+                1 => format!("nop {:+}", offset(rng)),
+                _ => format!("jmp {:+}", rng.range(0..size) as isize - position as isize),
+            });
+            format!("{}\n", instruction)
+        })
+        .collect()
+}
+
+pub struct Day08;
+
+impl Solution for Day08 {
+    type Input = Vec<Instruction>;
+
+    fn name(&self) -> &'static str {
+        "Handheld Halting"
+    }
+
+    fn day(&self) -> usize {
+        8
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(600))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two};
+    use super::{parse, star_one, star_two};
     use std::io::Cursor;
 
     const INPUT: &[u8] = b"nop +0
@@ -107,9 +209,20 @@ acc +1
 jmp -4
 acc +6";
 
+    #[test]
+    fn test_parse_error() {
+        let error = parse(Cursor::new("nop +0\nacc +1\nfoo +4")).unwrap_err();
+        assert_eq!(error.line(), Some(3));
+        assert_eq!(error.column(), Some(1));
+        assert_eq!(error.text(), Some("foo +4"));
+
+        let error = parse(Cursor::new("jmp +x")).unwrap_err();
+        assert_eq!(error.column(), Some(5));
+    }
+
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(Cursor::new(INPUT)), 5);
+        assert_eq!(star_one(&parse(Cursor::new(INPUT)).unwrap()), 5);
     }
 
     #[test]
@@ -123,6 +236,6 @@ acc -99
 acc +1
 nop -4
 acc +6";
-        assert_eq!(star_two(Cursor::new(input)), 8);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 8);
     }
 }
@@ -1,6 +1,10 @@
 use itertools::Itertools;
 use std::io::BufRead;
 
+use crate::generate::Rng;
+use crate::input::parse_lines;
+use crate::{Answer, ParseError, Solution};
+
 fn xmas(numbers: &[usize], length: usize) -> usize {
     *numbers
         .iter()
@@ -29,28 +33,91 @@ fn xmas2(numbers: &[usize], value: usize) -> Option<Vec<usize>> {
     None
 }
 
-pub fn star_one(input: impl BufRead) -> usize {
-    let numbers: Vec<usize> = input
-        .lines()
-        .map_while(Result::ok)
-        .map(|x| x.parse().unwrap())
-        .collect();
-    xmas(&numbers, 25)
+pub fn parse(input: impl BufRead) -> Result<Vec<usize>, ParseError> {
+    parse_lines(input, |line| Ok(line.parse()?))
+}
+
+pub fn star_one(numbers: &[usize]) -> usize {
+    xmas(numbers, 25)
 }
 
-pub fn star_two(input: impl BufRead) -> usize {
-    let numbers: Vec<usize> = input
-        .lines()
-        .map_while(Result::ok)
-        .map(|x| x.parse().unwrap())
-        .collect();
-    let values = xmas2(&numbers, xmas(&numbers, 25)).unwrap();
+pub fn star_two(numbers: &[usize]) -> usize {
+    let values = xmas2(numbers, xmas(numbers, 25)).unwrap();
     match (values.iter().min(), values.iter().max()) {
         (Some(x), Some(y)) => x + y,
         _ => unreachable!(),
     }
 }
 
+fn is_pair_sum(window: &[usize], number: usize) -> bool {
+    window
+        .iter()
+        .enumerate()
+        .any(|(i, a)| window[i + 1..].iter().any(|b| a + b == number))
+}
+
+/// Generates `size` numbers after a preamble of 25, where exactly one number is not
+/// the sum of two of the 25 before it but is the sum of a run of earlier numbers.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    const PREAMBLE: usize = 25;
+    let size = size.max(PREAMBLE + 20);
+    let weak = rng.range((PREAMBLE + 20).max(size / 2)..size);
+    let mut numbers = rng.sample(1..60, PREAMBLE);
+    while numbers.len() < size {
+        let i = numbers.len();
+        let window = &numbers[i - PREAMBLE..i];
+        let number = if i == weak {
+            loop {
+                let length = rng.range(3..18);
+                let start = rng.range(0..i - length);
+                let sum = numbers[start..start + length].iter().sum();
+                if !is_pair_sum(window, sum) {
+                    break sum;
+                }
+            }
+        } else {
+            // Only adding up the smallest numbers keeps them from growing too fast.
+            let mut smallest = window.to_vec();
+            smallest.sort_unstable();
+            let a = rng.range(0..4);
+            let b = (a + rng.range(1..4)) % 4;
+            smallest[a] + smallest[b]
+        };
+        numbers.push(number);
+    }
+    numbers.iter().map(|x| format!("{}\n", x)).collect()
+}
+
+pub struct Day09;
+
+impl Solution for Day09 {
+    type Input = Vec<usize>;
+
+    fn name(&self) -> &'static str {
+        "Encoding Error"
+    }
+
+    fn day(&self) -> usize {
+        9
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(1000))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{xmas, xmas2};
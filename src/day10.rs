@@ -1,13 +1,18 @@
 use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::io::BufRead;
 
-pub fn star_one(input: impl BufRead) -> usize {
-    let mut data: Vec<usize> = input
-        .lines()
-        .map_while(Result::ok)
-        .map(|line| line.trim().parse().unwrap())
-        .collect();
+use crate::generate::Rng;
+use crate::input::parse_lines;
+use crate::{Answer, ParseError, Solution};
+
+/// Returns the adapter joltages in ascending order.
+pub fn parse(input: impl BufRead) -> Result<Vec<usize>, ParseError> {
+    let mut data = parse_lines(input, |line| Ok(line.trim().parse::<usize>()?))?;
     data.sort_unstable();
+    Ok(data)
+}
+
+pub fn star_one(data: &[usize]) -> usize {
     let target = data.iter().max().unwrap() + 3;
     let mut current = 0;
     let mut diff_1 = 0;
@@ -41,13 +46,7 @@ fn number_to_target(cache: &HashMap<usize, usize>, current: usize, target: usize
         + cache.get(&(current + 3)).unwrap_or(&0)
 }
 
-pub fn star_two(input: impl BufRead) -> usize {
-    let mut data: Vec<usize> = input
-        .lines()
-        .map_while(Result::ok)
-        .map(|line| line.trim().parse().unwrap())
-        .collect();
-    data.sort_unstable();
+pub fn star_two(data: &[usize]) -> usize {
     let target = data.iter().max().unwrap() + 3;
 
     // We need to get process the largest values first.
@@ -79,9 +78,66 @@ pub fn star_two(input: impl BufRead) -> usize {
     number_to_target(&cache, 0, target)
 }
 
+/// The number of ways to get past a run of adapters one jolt apart, by length.
+const ARRANGEMENTS: [usize; 5] = [1, 1, 2, 4, 7];
+
+/// Generates `size` adapters which differ by 1 or 3 jolts.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut adapters = Vec::new();
+    let mut joltage = 0;
+    let mut arrangements: usize = 1;
+    while adapters.len() < size {
+        let mut run = rng.range(0..ARRANGEMENTS.len());
+        // Keep the answer to part two from overflowing.
+        if arrangements.saturating_mul(ARRANGEMENTS[run]) > 1 << 50 {
+            run = run.min(1);
+        }
+        arrangements *= ARRANGEMENTS[run];
+        for _ in 0..run {
+            joltage += 1;
+            adapters.push(joltage);
+        }
+        joltage += 3;
+        adapters.push(joltage);
+    }
+    adapters.truncate(size);
+    rng.shuffle(&mut adapters);
+    adapters.iter().map(|x| format!("{}\n", x)).collect()
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input = Vec<usize>;
+
+    fn name(&self) -> &'static str {
+        "Adapter Array"
+    }
+
+    fn day(&self) -> usize {
+        10
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(100))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two};
+    use super::{parse, star_one, star_two};
     use std::io::Cursor;
 
     #[test]
@@ -97,7 +153,7 @@ mod tests {
 6
 12
 4";
-        assert_eq!(star_one(Cursor::new(input)), 35);
+        assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 35);
     }
 
     #[test]
@@ -133,7 +189,7 @@ mod tests {
 34
 10
 3";
-        assert_eq!(star_one(Cursor::new(input)), 220);
+        assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 220);
     }
 
     #[test]
@@ -149,7 +205,7 @@ mod tests {
 6
 12
 4";
-        assert_eq!(star_two(Cursor::new(input)), 8);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 8);
     }
 
     #[test]
@@ -185,6 +241,6 @@ mod tests {
 34
 10
 3";
-        assert_eq!(star_two(Cursor::new(input)), 19208);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 19208);
     }
 }
@@ -1,8 +1,13 @@
 use std::fmt::Debug;
 use std::{convert::TryFrom, fmt::Display, io::BufRead};
 
+use crate::generate::Rng;
+use crate::grid::{Grid, Position, NEIGHBOURS8};
+use crate::progress;
+use crate::{Answer, ParseError, Solution};
+
 #[derive(Copy, Clone, PartialEq)]
-enum SeatStatus {
+pub enum SeatStatus {
     Floor,
     Empty,
     Occupied,
@@ -43,7 +48,7 @@ impl Debug for SeatStatus {
 }
 
 fn get_new_state(
-    map: &[Vec<SeatStatus>],
+    map: &Grid<SeatStatus>,
     y: usize,
     x: usize,
     max_depth: usize,
@@ -51,136 +56,161 @@ fn get_new_state(
 ) -> SeatStatus {
     let count = count_occupied(map, y, x, max_depth);
 
-    match (map[y][x], count) {
+    match (map[(y, x)], count) {
         (SeatStatus::Empty, 0) => SeatStatus::Occupied,
         (SeatStatus::Occupied, x) if x >= empty_threshold => SeatStatus::Empty,
-        _ => map[y][x],
+        _ => map[(y, x)],
     }
 }
 
-fn step<T>(map: &[Vec<SeatStatus>], get_new_state: T) -> Vec<Vec<SeatStatus>>
+fn step<T>(map: &Grid<SeatStatus>, get_new_state: T) -> Grid<SeatStatus>
 where
-    T: Fn(&[Vec<SeatStatus>], usize, usize) -> SeatStatus,
+    T: Fn(&Grid<SeatStatus>, usize, usize) -> SeatStatus,
 {
-    map.iter()
-        .enumerate()
-        .map(|(y, row)| {
-            row.iter()
-                .enumerate()
-                .map(|(x, _x)| get_new_state(map, y, x))
-                .collect()
-        })
-        .collect()
+    Grid::from_fn(map.rows(), map.columns(), |(y, x)| get_new_state(map, y, x))
 }
 
-fn count_occupied(map: &[Vec<SeatStatus>], y: usize, x: usize, max_depth: usize) -> usize {
-    let dirs: [(isize, isize); 8] = [
-        (-1, -1),
-        (-1, 0),
-        (-1, 1),
-        (0, -1),
-        (0, 1),
-        (1, -1),
-        (1, 0),
-        (1, 1),
-    ];
-    let rows = map.len() as isize;
-    let cols = map[y].len() as isize;
-    dirs.iter()
-        .filter(|(dy, dx)| {
-            let mut depth = 0;
-            let mut cy = y as isize + dy;
-            let mut cx = x as isize + dx;
-            while cy >= 0 && cy < rows && cx >= 0 && cx < cols && depth < max_depth {
-                match map[cy as usize][cx as usize] {
-                    SeatStatus::Occupied => return true,
-                    SeatStatus::Empty => return false,
-                    _ => (),
-                }
-
-                depth += 1;
-                cy += dy;
-                cx += dx;
-            }
-            false
+/// Counts the directions in which the first seat within `max_depth` is occupied.
+fn count_occupied(map: &Grid<SeatStatus>, y: usize, x: usize, max_depth: usize) -> usize {
+    NEIGHBOURS8
+        .iter()
+        .filter(|&&direction| {
+            map.ray((y, x), direction)
+                .take(max_depth)
+                .map(|position| map[position])
+                .find(|&seat| seat != SeatStatus::Floor)
+                == Some(SeatStatus::Occupied)
         })
         .count()
 }
 
-fn get_new_state_one(map: &[Vec<SeatStatus>], y: usize, x: usize) -> SeatStatus {
+fn get_new_state_one(map: &Grid<SeatStatus>, y: usize, x: usize) -> SeatStatus {
     get_new_state(map, y, x, 1, 4)
 }
 
-fn get_new_state_two(map: &[Vec<SeatStatus>], y: usize, x: usize) -> SeatStatus {
-    get_new_state(map, y, x, map.len().max(map[0].len()) + 1, 5)
+fn get_new_state_two(map: &Grid<SeatStatus>, y: usize, x: usize) -> SeatStatus {
+    get_new_state(map, y, x, map.rows().max(map.columns()) + 1, 5)
 }
 
-pub fn star_one(input: impl BufRead) -> usize {
-    let mut map: Vec<Vec<SeatStatus>> = input
-        .lines()
-        .map(|line| {
-            line.unwrap()
-                .chars()
-                .map(SeatStatus::try_from)
-                .filter_map(Result::ok)
-                .collect()
-        })
-        .collect();
+pub fn parse(input: impl BufRead) -> Result<Grid<SeatStatus>, ParseError> {
+    Grid::parse(input)
+}
 
+/// Steps until the seating stops changing, then counts the occupied seats.
+fn settle(
+    input: &Grid<SeatStatus>,
+    get_new_state: fn(&Grid<SeatStatus>, usize, usize) -> SeatStatus,
+) -> usize {
+    let mut map = input.clone();
+
+    let mut steps = 0;
     loop {
-        let map2 = step(&map, get_new_state_one);
+        let map2 = step(&map, get_new_state);
+        steps += 1;
+        progress::report(steps, None);
         if map == map2 {
-            break map
-                .iter()
-                .map(|row| row.iter().filter(|&&x| x == SeatStatus::Occupied).count())
-                .sum();
+            break map.values().filter(|&&x| x == SeatStatus::Occupied).count();
         }
         map = map2;
     }
 }
 
-pub fn star_two(input: impl BufRead) -> usize {
-    let mut map: Vec<Vec<SeatStatus>> = input
-        .lines()
-        .map(|line| {
-            line.unwrap()
-                .chars()
-                .map(SeatStatus::try_from)
-                .filter_map(Result::ok)
-                .collect()
-        })
-        .collect();
+pub fn star_one(input: &Grid<SeatStatus>) -> usize {
+    settle(input, get_new_state_one)
+}
+
+pub fn star_two(input: &Grid<SeatStatus>) -> usize {
+    settle(input, get_new_state_two)
+}
 
+/// Steps until the seating stops changing, like [settle], but returns the seats that
+/// keep changing if it ends up flipping between two seatings instead.
+fn flipping(
+    input: &Grid<SeatStatus>,
+    get_new_state: fn(&Grid<SeatStatus>, usize, usize) -> SeatStatus,
+) -> Vec<Position> {
+    let mut previous = input.clone();
+    let mut map = step(input, get_new_state);
     loop {
-        let map2 = step(&map, get_new_state_two);
-        if map == map2 {
-            break map
-                .iter()
-                .map(|row| row.iter().filter(|&&x| x == SeatStatus::Occupied).count())
-                .sum();
+        let map2 = step(&map, get_new_state);
+        if map2 == map {
+            break vec![];
+        } else if map2 == previous {
+            break map.positions().filter(|&p| map[p] != map2[p]).collect();
         }
+        previous = map;
         map = map2;
     }
 }
 
+/// Generates a seat layout `size` seats square, in which the seating settles down
+/// under both sets of rules.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut layout = Grid::from_fn(size.max(1), size.max(1), |_| {
+        if rng.chance(1, 8) {
+            SeatStatus::Floor
+        } else {
+            SeatStatus::Empty
+        }
+    });
+    // Random layouts often end up flipping between two seatings forever. Replacing
+    // some of the seats that flip with floor stops that in a try or two.
+    loop {
+        let mut seats = flipping(&layout, get_new_state_one);
+        if seats.is_empty() {
+            seats = flipping(&layout, get_new_state_two);
+        }
+        if seats.is_empty() {
+            break format!("{}\n", layout);
+        }
+        for seat in seats {
+            if rng.chance(1, 10) {
+                layout[seat] = SeatStatus::Floor;
+            }
+        }
+    }
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input = Grid<SeatStatus>;
+
+    fn name(&self) -> &'static str {
+        "Seating System"
+    }
+
+    fn day(&self) -> usize {
+        11
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(90))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
-        count_occupied, get_new_state_one, get_new_state_two, star_one, star_two, step, SeatStatus,
-        TryFrom,
+        count_occupied, get_new_state_one, get_new_state_two, parse, star_one, star_two, step,
+        Grid, SeatStatus,
     };
     use std::io::Cursor;
 
-    fn get_map(input: &str) -> Vec<Vec<SeatStatus>> {
-        input
-            .lines()
-            .map(|line| {
-                line.chars()
-                    .map(SeatStatus::try_from)
-                    .filter_map(Result::ok)
-                    .collect()
-            })
-            .collect()
+    fn get_map(input: &str) -> Grid<SeatStatus> {
+        input.parse().unwrap()
     }
 
     #[test]
@@ -260,7 +290,7 @@ L.LLLLL.LL
 LLLLLLLLLL
 L.LLLLLL.L
 L.LLLLL.LL";
-        assert_eq!(star_one(Cursor::new(input)), 37);
+        assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 37);
     }
 
     #[test]
@@ -277,7 +307,7 @@ L.LLLLL.LL";
 ...#.....",
         );
         assert_eq!(
-            count_occupied(&map, 4, 3, map.len().max(map[0].len()) + 1),
+            count_occupied(&map, 4, 3, map.rows().max(map.columns()) + 1),
             8
         )
     }
@@ -356,6 +386,6 @@ L.LLLLL.LL
 LLLLLLLLLL
 L.LLLLLL.L
 L.LLLLL.LL";
-        assert_eq!(star_two(Cursor::new(input)), 26);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 26);
     }
 }
@@ -1,8 +1,12 @@
 use std::io::BufRead;
 use std::{fmt::Debug, str::FromStr};
 
+use crate::generate::Rng;
+use crate::input::parse_lines;
+use crate::{Answer, ParseError, Solution};
+
 #[derive(Copy, Clone, PartialEq)]
-enum Action {
+pub enum Action {
     North(usize),   // Action N means to move north by the given value.
     South(usize),   // Action S means to move south by the given value.
     East(usize),    // Action E means to move east by the given value.
@@ -13,20 +17,29 @@ enum Action {
 }
 
 impl FromStr for Action {
-    type Err = String;
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut chars = s.chars();
-        let instruction = chars.next().unwrap();
-        let value = chars.collect::<String>().parse::<usize>().unwrap();
+        let instruction = chars
+            .next()
+            .ok_or_else(|| ParseError::new("Expected an action like `F10`"))?;
+        let value = chars.as_str().parse::<usize>().map_err(|_| {
+            ParseError::new(format!("Could not parse {} as a value", chars.as_str())).with_column(2)
+        })?;
         match instruction {
             'N' => Ok(Action::North(value)),
             'S' => Ok(Action::South(value)),
             'E' => Ok(Action::East(value)),
             'W' => Ok(Action::West(value)),
-            'L' => Ok(Action::Left(value)),
-            'R' => Ok(Action::Right(value)),
+            'L' if value % 90 == 0 => Ok(Action::Left(value)),
+            'R' if value % 90 == 0 => Ok(Action::Right(value)),
+            'L' | 'R' => Err(ParseError::new(format!(
+                "Can only turn in multiples of 90 degrees, not {}",
+                value
+            ))
+            .with_column(2)),
             'F' => Ok(Action::Forward(value)),
-            _ => Err(format!("Unknown instruction: {}", s)),
+            _ => Err(ParseError::new(format!("Unknown instruction: {}", s)).with_column(1)),
         }
     }
 }
@@ -114,43 +127,84 @@ impl Ship {
     }
 }
 
-pub fn star_one(input: impl BufRead) -> usize {
+pub fn parse(input: impl BufRead) -> Result<Vec<Action>, ParseError> {
+    parse_lines(input, |line| line.parse::<Action>())
+}
+
+pub fn star_one(input: &[Action]) -> usize {
     let mut ship = Ship {
         x: 0,
         y: 0,
         direction: 90,
     };
-    for action in input
-        .lines()
-        .map_while(Result::ok)
-        .map(|line| line.parse::<Action>().unwrap())
-    {
-        ship.step(action);
+    for action in input {
+        ship.step(*action);
     }
     ship.manhatten_distance()
 }
 
-pub fn star_two(input: impl BufRead) -> usize {
+pub fn star_two(input: &[Action]) -> usize {
     let mut ship = Ship {
         x: 0,
         y: 0,
         direction: 90,
     };
     let mut waypoint = (1, 10);
-    for action in input
-        .lines()
-        .map_while(Result::ok)
-        .map(|line| line.parse::<Action>().unwrap())
-    {
-        waypoint = ship.step2(action, waypoint);
+    for action in input {
+        waypoint = ship.step2(*action, waypoint);
     }
     ship.manhatten_distance()
 }
 
+/// Generates `size` navigation instructions.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| match rng.range(0..10) {
+            0..=4 => format!("{}{}\n", rng.choose(&['N', 'S', 'E', 'W']), rng.range(1..6)),
+            5 | 6 => format!(
+                "{}{}\n",
+                rng.choose(&['L', 'R']),
+                rng.choose(&[90, 180, 270])
+            ),
+            _ => format!("F{}\n", rng.range(1..101)),
+        })
+        .collect()
+}
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    type Input = Vec<Action>;
+
+    fn name(&self) -> &'static str {
+        "Rain Risk"
+    }
+
+    fn day(&self) -> usize {
+        12
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(780))
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
-    use super::{star_one, star_two};
+    use super::{parse, star_one, star_two};
     use std::io::Cursor;
 
     #[test]
@@ -160,7 +214,7 @@ N3
 F7
 R90
 F11";
-        assert_eq!(star_one(Cursor::new(input)), 25);
+        assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 25);
     }
 
     #[test]
@@ -170,6 +224,6 @@ N3
 F7
 R90
 F11";
-        assert_eq!(star_two(Cursor::new(input)), 286);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 286);
     }
 }
@@ -1,19 +1,50 @@
 use std::io::BufRead;
 
-pub fn star_one(input: impl BufRead) -> usize {
+use crate::generate::Rng;
+use crate::{Answer, ParseError, Solution};
+
+#[derive(Debug)]
+pub struct Notes {
+    /// The earliest time we could depart.
+    start: usize,
+    /// Bus ids in schedule order, `None` for buses that are out of service.
+    ids: Vec<Option<usize>>,
+}
+
+pub fn parse(input: impl BufRead) -> Result<Notes, ParseError> {
     let mut lines = input.lines();
-    let start = lines.next().unwrap().unwrap().parse::<usize>().unwrap();
-    let ids: Vec<usize> = lines
+    let line = lines
         .next()
-        .unwrap()
-        .unwrap()
+        .ok_or_else(|| ParseError::new("Missing earliest departure time"))??;
+    let start = line
+        .parse::<usize>()
+        .map_err(|e| ParseError::from(e).in_line(1, &line))?;
+    let line = lines
+        .next()
+        .ok_or_else(|| ParseError::new("Missing bus ids"))??;
+    let ids = line
         .split(',')
-        .filter(|x| x != &"x")
-        .map(|x| x.parse::<usize>().unwrap())
-        .collect();
+        .map(|id| match id {
+            "x" => Ok(None),
+            _ => match id.parse::<usize>() {
+                Ok(0) | Err(_) => Err(ParseError::at_token(
+                    &line,
+                    id,
+                    format!("Expected a bus id or `x`, found {:?}", id),
+                )),
+                Ok(id) => Ok(Some(id)),
+            },
+        })
+        .collect::<Result<_, ParseError>>()
+        .map_err(|e| e.in_line(2, &line))?;
+    Ok(Notes { start, ids })
+}
+
+pub fn star_one(input: &Notes) -> usize {
+    let start = input.start;
     let mut closest = usize::MAX;
     let mut closest_id = 0;
-    for id in ids {
+    for &id in input.ids.iter().flatten() {
         let next_time = ((start as f64 / id as f64).ceil() * id as f64) as usize;
         if next_time < closest {
             closest_id = id;
@@ -23,23 +54,20 @@ pub fn star_one(input: impl BufRead) -> usize {
     closest_id * (closest - start)
 }
 
-fn modinv(a: isize, m: isize) -> Option<isize> {
+fn modinv(a: i128, m: i128) -> Option<i128> {
     let a = a % m;
     (0..m).find(|x| (a * x) % m == 1)
 }
 
-pub fn star_two(input: impl BufRead) -> usize {
-    let ids: Vec<(isize, isize)> = input
-        .lines()
-        .nth(1)
-        .unwrap()
-        .unwrap()
-        .split(',')
+pub fn star_two(input: &Notes) -> u128 {
+    // The product of the bus ids quickly outgrows 64 bits, so work in 128 bits.
+    let ids: Vec<(i128, i128)> = input
+        .ids
+        .iter()
         .enumerate()
-        .filter(|(_i, x)| x != &"x")
-        .map(|(i, x)| (i as isize, x.parse::<isize>().unwrap()))
+        .filter_map(|(i, id)| id.map(|id| (i as i128, id as i128)))
         .collect();
-    let n_product: isize = ids.iter().map(|x| x.1).product();
+    let n_product: i128 = ids.iter().map(|x| x.1).product();
     let sum = ids
         .iter()
         .map(|x| {
@@ -54,8 +82,74 @@ pub fn star_two(input: impl BufRead) -> usize {
             let n_product_n = n_product / n;
             modinv(n_product_n, n).map(|s| a * s * n_product_n).unwrap()
         })
-        .sum::<isize>();
-    (sum % n_product) as usize
+        .sum::<i128>();
+    (sum % n_product) as u128
+}
+
+fn is_prime(n: usize) -> bool {
+    n > 1
+        && (2..)
+            .take_while(|d| d * d <= n)
+            .all(|d| !n.is_multiple_of(d))
+}
+
+/// Generates notes for `size` buses, up to 9, with different prime ids. Each bus is
+/// listed at an offset smaller than its id.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let primes: Vec<usize> = (13..1000).filter(|&n| is_prime(n)).collect();
+    let mut ids: Vec<usize> = rng
+        .sample(0..primes.len(), size.clamp(1, 9))
+        .into_iter()
+        .map(|i| primes[i])
+        .collect();
+    ids.sort_unstable();
+    let mut schedule = vec![None; ids.len() * 8];
+    schedule[0] = Some(ids.pop().unwrap());
+    for id in ids {
+        let free: Vec<usize> = (0..id.min(schedule.len()))
+            .filter(|&i| schedule[i].is_none())
+            .collect();
+        schedule[*rng.choose(&free)] = Some(id);
+    }
+    let schedule: Vec<String> = schedule
+        .iter()
+        .map(|id| id.map_or("x".to_string(), |id| id.to_string()))
+        .collect();
+    format!(
+        "{}\n{}\n",
+        rng.range(100_000..1_000_000),
+        schedule.join(",")
+    )
+}
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Input = Notes;
+
+    fn name(&self) -> &'static str {
+        "Shuttle Search"
+    }
+
+    fn day(&self) -> usize {
+        13
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(9))
+    }
 }
 
 #[cfg(test)]
@@ -72,37 +166,37 @@ mod tests {
     fn test_star_one() {
         let input = b"939
 7,13,x,x,59,x,31,19";
-        assert_eq!(star_one(Cursor::new(input)), 295);
+        assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 295);
     }
 
     #[test]
     fn test_star_two() {
         let input = b"939
 7,13,x,x,59,x,31,19";
-        assert_eq!(star_two(Cursor::new(input)), 1068781);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 1068781);
     }
 
     #[test]
     fn test_star_two_a() {
         let input = b"939
 17,x,13,19";
-        assert_eq!(star_two(Cursor::new(input)), 3417);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 3417);
     }
     #[test]
     fn test_star_two_b() {
         let input = b"939
 67,7,59,61";
-        assert_eq!(star_two(Cursor::new(input)), 754018);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 754018);
         let input = b"939
 67,x,7,59,61";
-        assert_eq!(star_two(Cursor::new(input)), 779210);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 779210);
 
         let input = b"939
 67,7,x,59,61";
-        assert_eq!(star_two(Cursor::new(input)), 1261476);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 1261476);
 
         let input = b"939
 1789,37,47,1889";
-        assert_eq!(star_two(Cursor::new(input)), 1202161486);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 1202161486);
     }
 }
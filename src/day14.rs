@@ -2,14 +2,18 @@ use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::io::BufRead;
 
+use crate::generate::Rng;
+use crate::input::parse_lines;
+use crate::{Answer, ParseError, Solution};
+
 use regex::Regex;
 
 lazy_static! {
-    static ref RE: Regex = Regex::new(r"mem\[(?P<index>\d{1,})\] = (?P<value>\d{1,})").unwrap();
+    static ref RE: Regex = Regex::new(r"^mem\[(?P<index>\d{1,})\] = (?P<value>\d{1,})$").unwrap();
 }
 
 #[derive(PartialEq, Copy, Clone)]
-enum MaskPoint {
+pub enum MaskPoint {
     X,
     One,
     Zero,
@@ -38,21 +42,19 @@ impl TryFrom<MaskPoint> for char {
     }
 }
 
-enum Instruction {
+pub enum Instruction {
     Mask(Vec<MaskPoint>),
     Memory(usize, usize),
 }
 
-pub fn star_one(input: impl BufRead) -> usize {
-    let mut instructions = parse_input(input).collect::<Vec<Instruction>>();
-
-    let first = match instructions.remove(0) {
+pub fn star_one(instructions: &[Instruction]) -> usize {
+    let first = match &instructions[0] {
         Instruction::Mask(x) => x,
         _ => unreachable!(),
     };
 
-    let hashmap = instructions
-        .into_iter()
+    let hashmap = instructions[1..]
+        .iter()
         .fold(
             (HashMap::new(), first),
             |(mut hm, mask), instruction| match instruction {
@@ -72,7 +74,7 @@ pub fn star_one(input: impl BufRead) -> usize {
                         .rev()
                         .collect::<String>();
                     let new_value = usize::from_str_radix(&binary_str, 2).unwrap();
-                    hm.insert(index, new_value);
+                    hm.insert(*index, new_value);
                     (hm, mask)
                 }
             },
@@ -113,61 +115,71 @@ fn set_addresses(address: Vec<MaskPoint>, mut set: impl FnMut(usize)) {
     }
 }
 
-fn parse_input(input: impl BufRead) -> impl Iterator<Item = Instruction> {
-    input.lines().map_while(Result::ok).map(|line| {
-        let firstchars: String = line.chars().take(3).collect();
-        match firstchars.as_str() {
-            "mas" => Instruction::Mask(
-                line.chars()
-                    .skip(7)
-                    .map(MaskPoint::try_from)
-                    .filter_map(Result::ok)
-                    .collect(),
-            ),
-            "mem" => {
-                let captures = RE
-                    .captures(&line)
-                    .unwrap_or_else(|| panic!("Could not match {} with regex", line));
-                let index = captures
-                    .name("index")
-                    .unwrap()
-                    .as_str()
-                    .parse::<usize>()
-                    .unwrap();
-                let value = captures
-                    .name("value")
-                    .unwrap()
-                    .as_str()
-                    .parse::<usize>()
-                    .unwrap();
-                Instruction::Memory(index, value)
+fn parse_value(line: &str, value: &str) -> Result<usize, ParseError> {
+    match value.parse::<usize>() {
+        Ok(value) if value < 1 << 36 => Ok(value),
+        _ => Err(ParseError::at_token(
+            line,
+            value,
+            format!("Expected a 36-bit value, found {}", value),
+        )),
+    }
+}
+
+pub fn parse(input: impl BufRead) -> Result<Vec<Instruction>, ParseError> {
+    let instructions = parse_lines(input, |line| {
+        if let Some(mask) = line.strip_prefix("mask = ") {
+            if mask.len() != 36 {
+                return Err(ParseError::at_token(
+                    line,
+                    mask,
+                    format!("Expected a mask of 36 bits, found {}", mask.len()),
+                ));
             }
-            _ => panic!(),
+            let offset = line.len() - mask.len();
+            mask.chars()
+                .enumerate()
+                .map(|(i, c)| {
+                    MaskPoint::try_from(c)
+                        .map_err(|e| ParseError::new(e).with_column(offset + i + 1))
+                })
+                .collect::<Result<_, _>>()
+                .map(Instruction::Mask)
+        } else if let Some(captures) = RE.captures(line) {
+            let index = parse_value(line, &captures["index"])?;
+            let value = parse_value(line, &captures["value"])?;
+            Ok(Instruction::Memory(index, value))
+        } else {
+            Err(ParseError::new("Expected `mask = ...` or `mem[...] = ...`").with_column(1))
         }
-    })
+    })?;
+    match instructions.first() {
+        Some(Instruction::Mask(_)) => Ok(instructions),
+        _ => Err(ParseError::new("The program must start by setting a mask")),
+    }
 }
 
-pub fn star_two(input: impl BufRead) -> usize {
-    let mut instructions = parse_input(input).collect::<Vec<Instruction>>();
-
-    let first = match instructions.remove(0) {
+pub fn star_two(instructions: &[Instruction]) -> usize {
+    let first = match &instructions[0] {
         Instruction::Mask(x) => x
-            .into_iter()
+            .iter()
+            .copied()
             .enumerate()
             .filter(|(_, x)| x != &MaskPoint::Zero)
             .collect::<HashMap<usize, MaskPoint>>(),
         _ => unreachable!(),
     };
 
-    let hashmap = instructions
-        .into_iter()
+    let hashmap = instructions[1..]
+        .iter()
         .fold(
             (HashMap::new(), first),
             |(mut hm, mask), instruction| match instruction {
                 Instruction::Mask(chars) => (
                     hm,
                     chars
-                        .into_iter()
+                        .iter()
+                        .copied()
                         .enumerate()
                         .filter(|(_, x)| x != &MaskPoint::Zero)
                         .collect::<HashMap<usize, MaskPoint>>(),
@@ -179,7 +191,7 @@ pub fn star_two(input: impl BufRead) -> usize {
                         .map(|(i, x)| *mask.get(&i).unwrap_or(&MaskPoint::try_from(x).unwrap()))
                         .collect();
                     set_addresses(adress_str, |new_index| {
-                        hm.insert(new_index, value);
+                        hm.insert(new_index, *value);
                     });
                     (hm, mask)
                 }
@@ -189,6 +201,70 @@ pub fn star_two(input: impl BufRead) -> usize {
     hashmap.values().sum()
 }
 
+/// Generates a program which writes to memory `size` times, changing the mask every
+/// few writes.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut program = String::new();
+    let mut writes = 0;
+    while writes < size.max(1) {
+        // No more than nine floating bits, so part two writes at most 512 addresses
+        // at once.
+        let count = rng.range(0..10);
+        let floating = rng.sample(0..36, count);
+        let mask: String = (0..36)
+            .map(|i| {
+                if floating.contains(&i) {
+                    'X'
+                } else if rng.chance(1, 2) {
+                    '1'
+                } else {
+                    '0'
+                }
+            })
+            .collect();
+        program += &format!("mask = {}\n", mask);
+        for _ in 0..rng.range(1..8) {
+            program += &format!(
+                "mem[{}] = {}\n",
+                rng.range(0..1 << 16),
+                rng.range(0..1 << 30)
+            );
+            writes += 1;
+        }
+    }
+    program
+}
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input = Vec<Instruction>;
+
+    fn name(&self) -> &'static str {
+        "Docking Data"
+    }
+
+    fn day(&self) -> usize {
+        14
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(400))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -200,7 +276,7 @@ mod tests {
 mem[8] = 11
 mem[7] = 101
 mem[8] = 0";
-        assert_eq!(star_one(Cursor::new(input)), 165);
+        assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 165);
     }
 
     #[test]
@@ -209,6 +285,6 @@ mem[8] = 0";
 mem[42] = 100
 mask = 00000000000000000000000000000000X0XX
 mem[26] = 1";
-        assert_eq!(star_two(Cursor::new(input)), 208);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 208);
     }
 }
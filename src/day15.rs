@@ -2,62 +2,128 @@ use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::io::BufRead;
 
-fn run(input: impl BufRead, index: usize) -> usize {
-    let starting: Vec<usize> = input
-        .split(b',')
-        .filter_map(Result::ok)
-        .map(|x| String::from_utf8(x).unwrap().parse::<usize>().unwrap())
-        .collect();
+use crate::cancel::{self, Cancelled, Token};
+use crate::generate::Rng;
+use crate::progress;
+use crate::{Answer, ParseError, Solution};
+
+use itertools::Itertools;
+
+pub fn parse(mut input: impl BufRead) -> Result<Vec<usize>, ParseError> {
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    let line = line.trim_end();
+    let numbers: Vec<usize> = line
+        .split(',')
+        .map(|x| {
+            x.trim().parse::<usize>().map_err(|_| {
+                ParseError::at_token(line, x, format!("Expected a number, found {:?}", x))
+            })
+        })
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.in_line(1, line))?;
+    if numbers.iter().duplicates().next().is_some() {
+        return Err(ParseError::new("The starting numbers must be distinct").in_line(1, line));
+    }
+    Ok(numbers)
+}
+
+fn run(starting: &[usize], index: usize, token: &Token) -> Result<usize, Cancelled> {
     let mut map: HashMap<usize, (usize, Option<usize>)> = HashMap::new();
     // let mut first: HashMap<usize, Vec<usize>> = HashMap::new();
-    // let mut last_spoken = starting[2];
     for (i, x) in starting.iter().enumerate() {
         map.insert(*x, (i, None));
     }
 
-    let start = starting.len();
-
-    (start..)
-        .scan((starting[2], map), |(last_spoken, map), i| {
-            // If we have just spoken it the first time in the previous turn
-            let x = map.get(last_spoken).unwrap();
-            let value = if let Some(later) = x.1 {
-                later - x.0
-            } else {
-                0
-            };
-
-            // Insert this new value into the map;
-            match map.entry(value) {
-                Entry::Occupied(mut e) => {
-                    // We have spoken this before.
-                    let v = e.get_mut();
-                    if let Some(later) = v.1 {
-                        v.0 = later;
-                        v.1.replace(i);
-                    } else {
-                        v.1 = Some(i);
-                    }
-                }
-                Entry::Vacant(o) => {
-                    // First time spoken
-                    o.insert((i, None));
+    let mut last_spoken = *starting.last().unwrap();
+    for i in starting.len()..index {
+        token.check_every(i)?;
+        progress::report(i + 1, Some(index));
+        // If we have just spoken it the first time in the previous turn
+        let x = map.get(&last_spoken).unwrap();
+        let value = if let Some(later) = x.1 {
+            later - x.0
+        } else {
+            0
+        };
+
+        // Insert this new value into the map;
+        match map.entry(value) {
+            Entry::Occupied(mut e) => {
+                // We have spoken this before.
+                let v = e.get_mut();
+                if let Some(later) = v.1 {
+                    v.0 = later;
+                    v.1.replace(i);
+                } else {
+                    v.1 = Some(i);
                 }
-            };
-            *last_spoken = value;
-            // println!("{}", value);
-            Some(value)
-        })
-        .nth(index - start - 1)
-        .unwrap()
+            }
+            Entry::Vacant(o) => {
+                // First time spoken
+                o.insert((i, None));
+            }
+        };
+        last_spoken = value;
+    }
+    Ok(last_spoken)
+}
+
+pub fn star_one(input: &[usize]) -> usize {
+    cancel::uncancelled(|token| run(input, 2020, token))
 }
 
-pub fn star_one(input: impl BufRead) -> usize {
-    run(input, 2020)
+pub fn star_two(input: &[usize]) -> usize {
+    cancel::uncancelled(|token| star_two_until(input, token))
 }
 
-pub fn star_two(input: impl BufRead) -> usize {
-    run(input, 30000000)
+pub fn star_two_until(input: &[usize], token: &Token) -> Result<usize, Cancelled> {
+    run(input, 30000000, token)
+}
+
+/// Generates `size` different starting numbers.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.clamp(1, 100);
+    let numbers: Vec<String> = rng
+        .sample(0..size * 3, size)
+        .iter()
+        .map(|x| x.to_string())
+        .collect();
+    format!("{}\n", numbers.join(","))
+}
+
+pub struct Day15;
+
+impl Solution for Day15 {
+    type Input = Vec<usize>;
+
+    fn name(&self) -> &'static str {
+        "Rambunctious Recitation"
+    }
+
+    fn day(&self) -> usize {
+        15
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn part_two_until(&self, input: &Self::Input, token: &Token) -> Result<Answer, Cancelled> {
+        Ok(star_two_until(input, token)?.into())
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(6))
+    }
 }
 
 #[cfg(test)]
@@ -69,17 +135,17 @@ mod tests {
     fn test_star_one() {
         {
             let input = b"0,3,6";
-            assert_eq!(star_one(Cursor::new(input)), 436);
+            assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 436);
         }
         {
             let input = b"1,3,2";
-            assert_eq!(star_one(Cursor::new(input)), 1);
+            assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 1);
         }
     }
 
     #[test]
     fn test_star_two() {
         let input = b"0,3,6";
-        assert_eq!(star_two(Cursor::new(input)), 175594);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 175594);
     }
 }
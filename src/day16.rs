@@ -5,6 +5,10 @@ use std::{
     collections::{HashMap, HashSet},
 };
 
+use crate::generate::Rng;
+use crate::input::{sections, Section};
+use crate::{Answer, ParseError, Solution};
+
 lazy_static! {
     static ref RE: Regex = Regex::new(
         r"(?P<field>[\w ]+): (?P<min1>\d{1,})-(?P<max1>\d{1,}) or (?P<min2>\d{1,})-(?P<max2>\d{1,})"
@@ -12,99 +16,126 @@ lazy_static! {
     .unwrap();
 }
 
-fn get_valid_numbers(input: &str) -> HashSet<usize> {
-    input
+type Validation = (usize, usize, usize, usize);
+
+#[derive(Debug)]
+pub struct Notes {
+    fields: HashMap<String, Validation>,
+    my_ticket: Vec<usize>,
+    nearby_tickets: Vec<Vec<usize>>,
+}
+
+fn get_field_validation(section: &Section) -> Result<HashMap<String, Validation>, ParseError> {
+    section
         .lines()
-        .flat_map(|line| {
-            let captures = RE
-                .captures(line)
-                .expect("Bad line that does not match regex.");
-            let min1 = captures["min1"].parse::<usize>().unwrap();
-            let max1 = captures["max1"].parse::<usize>().unwrap();
-            let min2 = captures["min2"].parse::<usize>().unwrap();
-            let max2 = captures["max2"].parse::<usize>().unwrap();
-            (min1..=max1).chain(min2..=max2)
+        .map(|(number, line)| {
+            let captures = RE.captures(line).ok_or_else(|| {
+                ParseError::new("Expected a field like `class: 1-3 or 5-7`")
+                    .with_column(1)
+                    .in_line(number, line)
+            })?;
+            let bound = |name: &str| {
+                captures[name]
+                    .parse::<usize>()
+                    .map_err(|e| ParseError::from(e).in_line(number, line))
+            };
+            let validation = (
+                bound("min1")?,
+                bound("max1")?,
+                bound("min2")?,
+                bound("max2")?,
+            );
+            Ok((captures["field"].to_string(), validation))
         })
         .collect()
 }
 
-pub fn star_one(mut input: impl BufRead) -> usize {
-    let mut input_str = String::new();
-    input
-        .read_to_string(&mut input_str)
-        .expect("Could not read all of string");
-    let mut sections = input_str.split("\n\n");
+fn parse_tickets(
+    section: Option<&Section>,
+    header: &str,
+    fields: usize,
+) -> Result<Vec<Vec<usize>>, ParseError> {
+    let section =
+        section.ok_or_else(|| ParseError::new(format!("Missing `{}:` section", header)))?;
+    section
+        .expect_header(header)?
+        .map(|(number, line)| {
+            let ticket = line
+                .split(',')
+                .map(|x| {
+                    x.parse::<usize>().map_err(|_| {
+                        ParseError::at_token(line, x, format!("Expected a number, found {:?}", x))
+                    })
+                })
+                .collect::<Result<Vec<usize>, ParseError>>()
+                .map_err(|e| e.in_line(number, line))?;
+            if ticket.len() != fields {
+                return Err(ParseError::new(format!(
+                    "Expected {} values on the ticket, found {}",
+                    fields,
+                    ticket.len()
+                ))
+                .in_line(number, line));
+            }
+            Ok(ticket)
+        })
+        .collect()
+}
 
-    let valid_numbers: HashSet<usize> = get_valid_numbers(sections.next().unwrap());
+pub fn parse(input: impl BufRead) -> Result<Notes, ParseError> {
+    let mut sections = sections(input);
 
-    let _my_ticket = sections.next().unwrap();
-    let nearby_tickets = sections
-        .next()
-        .unwrap()
-        .lines()
-        .skip(1)
-        .flat_map(|line| line.split(',').map(|x| x.parse::<usize>().unwrap()));
-    nearby_tickets.filter(|x| !valid_numbers.contains(x)).sum()
+    let fields = match sections.next().transpose()? {
+        Some(section) => get_field_validation(&section)?,
+        None => return Err(ParseError::new("Missing the ticket fields")),
+    };
+    let section = sections.next().transpose()?;
+    let mut my_ticket = parse_tickets(section.as_ref(), "your ticket", fields.len())?;
+    if my_ticket.len() != 1 {
+        return Err(ParseError::new("Expected exactly one ticket of your own"));
+    }
+    let section = sections.next().transpose()?;
+    let nearby_tickets = parse_tickets(section.as_ref(), "nearby tickets", fields.len())?;
+    Ok(Notes {
+        fields,
+        my_ticket: my_ticket.remove(0),
+        nearby_tickets,
+    })
 }
 
-fn get_field_validation(input: &str) -> HashMap<String, (usize, usize, usize, usize)> {
-    input
-        .lines()
-        .map(|line| {
-            let captures = RE
-                .captures(line)
-                .expect("Bad line that does not match regex.");
-            let field_name = &captures["field"];
-            let min1 = captures["min1"].parse::<usize>().unwrap();
-            let max1 = captures["max1"].parse::<usize>().unwrap();
-            let min2 = captures["min2"].parse::<usize>().unwrap();
-            let max2 = captures["max2"].parse::<usize>().unwrap();
-            (field_name.to_string(), (min1, max1, min2, max2))
-        })
+fn get_valid_numbers(fields: &HashMap<String, Validation>) -> HashSet<usize> {
+    fields
+        .values()
+        .flat_map(|&(min1, max1, min2, max2)| (min1..=max1).chain(min2..=max2))
         .collect()
 }
 
-fn validate_field(x: usize, (min1, max1, min2, max2): (usize, usize, usize, usize)) -> bool {
-    (x >= min1 && x <= max1) || (x >= min2 && x <= max2)
-}
+pub fn star_one(input: &Notes) -> usize {
+    let valid_numbers: HashSet<usize> = get_valid_numbers(&input.fields);
 
-pub fn star_two(mut input: impl BufRead) -> usize {
-    let mut input_str = String::new();
     input
-        .read_to_string(&mut input_str)
-        .expect("Could not read all of string");
-    let mut sections = input_str.split("\n\n");
+        .nearby_tickets
+        .iter()
+        .flatten()
+        .filter(|x| !valid_numbers.contains(x))
+        .sum()
+}
 
-    let field_type_str = sections.next().unwrap();
+fn validate_field(x: usize, (min1, max1, min2, max2): Validation) -> bool {
+    (x >= min1 && x <= max1) || (x >= min2 && x <= max2)
+}
 
-    let valid_numbers: HashSet<usize> = get_valid_numbers(field_type_str);
-    let field_validation: HashMap<String, (usize, usize, usize, usize)> =
-        get_field_validation(field_type_str);
+pub fn star_two(input: &Notes) -> usize {
+    let valid_numbers: HashSet<usize> = get_valid_numbers(&input.fields);
+    let field_validation = &input.fields;
 
-    let my_ticket = sections
-        .next()
-        .unwrap()
-        .lines()
-        .skip(1)
-        .flat_map(|line| {
-            line.split(',')
-                .map(|x| x.parse::<usize>().unwrap())
-                .collect::<Vec<usize>>()
-        })
-        .collect::<Vec<usize>>();
-    let valid_nearby_tickets: Vec<Vec<usize>> = sections
-        .next()
-        .unwrap()
-        .lines()
-        .skip(1)
-        .map(|line| {
-            line.split(',')
-                .map(|x| x.parse::<usize>().unwrap())
-                .collect::<Vec<usize>>()
-        })
+    let my_ticket = &input.my_ticket;
+    let valid_nearby_tickets: Vec<&Vec<usize>> = input
+        .nearby_tickets
+        .iter()
         .filter(|ticket| ticket.iter().all(|x| valid_numbers.contains(x)))
         .collect();
-    let number_of_fields = valid_nearby_tickets[0].len();
+    let number_of_fields = my_ticket.len();
     let field_data: Vec<Vec<usize>> = (0..number_of_fields)
         .map(|i| {
             valid_nearby_tickets
@@ -149,6 +180,115 @@ pub fn star_two(mut input: impl BufRead) -> usize {
         .product()
 }
 
+const FIELDS: [&str; 20] = [
+    "departure location",
+    "departure station",
+    "departure platform",
+    "departure track",
+    "departure date",
+    "departure time",
+    "arrival location",
+    "arrival station",
+    "arrival platform",
+    "arrival track",
+    "class",
+    "duration",
+    "price",
+    "route",
+    "row",
+    "seat",
+    "train",
+    "type",
+    "wagon",
+    "zone",
+];
+
+/// Generates notes with `size` nearby tickets, about a quarter of which are invalid.
+///
+/// Every field accepts the numbers from 50 to 599. The field ranked `r` also accepts
+/// 901 up to `901 + r`, and each column holds its field's marker `901 + r` on at
+/// least one valid ticket, so the field ranked `r` fits exactly `r + 1` columns.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let fields = FIELDS.len();
+    let ranks = rng.sample(0..fields, fields);
+    let columns = rng.sample(0..fields, fields);
+    let rules: String = FIELDS
+        .iter()
+        .zip(&ranks)
+        .map(|(name, rank)| {
+            format!(
+                "{}: {}-{} or {}-{}\n",
+                name,
+                rng.range(25..50),
+                rng.range(600..800),
+                rng.range(850..900),
+                901 + rank
+            )
+        })
+        .collect();
+    let ticket =
+        |rng: &mut Rng| -> Vec<usize> { (0..fields).map(|_| rng.range(50..600)).collect() };
+    let my_ticket = ticket(rng);
+    let mut nearby: Vec<Vec<usize>> = (0..size).map(|_| ticket(rng)).collect();
+    // The last quarter are invalid, which leaves at least one valid ticket.
+    let valid = size - size / 4;
+    for (column, rank) in columns.iter().enumerate() {
+        nearby[rng.range(0..valid)][column] = 901 + rank;
+    }
+    for ticket in &mut nearby[valid..] {
+        ticket[rng.range(0..fields)] = if rng.chance(1, 2) {
+            rng.range(0..25)
+        } else {
+            rng.range(950..1000)
+        };
+    }
+    rng.shuffle(&mut nearby);
+    let format = |ticket: &[usize]| -> String {
+        let values: Vec<String> = ticket.iter().map(|x| x.to_string()).collect();
+        format!("{}\n", values.join(","))
+    };
+    format!(
+        "{}\nyour ticket:\n{}\nnearby tickets:\n{}",
+        rules,
+        format(&my_ticket),
+        nearby
+            .iter()
+            .map(|ticket| format(ticket))
+            .collect::<String>()
+    )
+}
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    type Input = Notes;
+
+    fn name(&self) -> &'static str {
+        "Ticket Translation"
+    }
+
+    fn day(&self) -> usize {
+        16
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(240))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -168,7 +308,7 @@ nearby tickets:
 40,4,50
 55,2,20
 38,6,12";
-        assert_eq!(star_one(Cursor::new(input)), 71);
+        assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 71);
     }
 
     #[test]
@@ -186,6 +326,6 @@ nearby tickets:
 40,4,50
 55,2,20
 38,6,12";
-        assert_eq!(star_two(Cursor::new(input)), 175594);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 175594);
     }
 }
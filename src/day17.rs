@@ -1,8 +1,16 @@
-use std::fmt::Debug;
-use std::{convert::TryFrom, io::BufRead};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::{convert::TryFrom, io::BufRead, iter};
 
-#[derive(Copy, Clone, PartialEq)]
-enum Cube {
+use itertools::Itertools;
+
+use crate::differential::Differential;
+use crate::generate::Rng;
+use crate::grid::Grid;
+use crate::{Answer, ParseError, Solution};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Cube {
     Active,
     Inactive,
 }
@@ -13,12 +21,12 @@ impl TryFrom<char> for Cube {
         match c {
             '#' => Ok(Cube::Active),
             '.' => Ok(Cube::Inactive),
-            _ => Err(format!("Could not match {} to SeatStatus", c)),
+            _ => Err(format!("Could not match {} to Cube", c)),
         }
     }
 }
 
-impl Debug for Cube {
+impl Display for Cube {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let c = match self {
             Cube::Active => '#',
@@ -28,179 +36,284 @@ impl Debug for Cube {
     }
 }
 
-type State = Vec<Vec<Vec<Cube>>>;
+/// The planes of the pocket dimension, one grid for each z.
+type State = Vec<Grid<Cube>>;
 
-fn count_active(state: &[Vec<Vec<Cube>>], i: usize, j: usize, k: usize) -> usize {
-    let dirs = (-1..=1)
-        .flat_map(|i| {
-            (-1..=1)
-                .flat_map(|j| {
-                    (-1..=1)
-                        .map(|k| (i, j, k))
-                        .collect::<Vec<(isize, isize, isize)>>()
-                })
-                .collect::<Vec<(isize, isize, isize)>>()
-        })
-        .filter(|x| !(x.0 == 0 && x.1 == 0 && x.2 == 0));
-    let mut count = 0;
-    for dir in dirs {
-        let c_i = i as isize + dir.0;
-        let c_j = j as isize + dir.1;
-        let c_k = k as isize + dir.2;
-        if c_i >= 0
-            && c_j >= 0
-            && c_k >= 0
-            && c_i < state.len() as isize
-            && c_j < state[c_i as usize].len() as isize
-            && c_k < state[c_i as usize][c_j as usize].len() as isize
-            && state[c_i as usize][c_j as usize][c_k as usize] == Cube::Active
-        {
-            count += 1;
-        }
+fn next(cube: Cube, active_neighbours: usize) -> Cube {
+    match (cube, active_neighbours) {
+        (Cube::Active, 2..=3) => Cube::Active,
+        (Cube::Inactive, 3) => Cube::Active,
+        _ => Cube::Inactive,
     }
-    count
 }
 
-fn step(state: Vec<Vec<Vec<Cube>>>) -> Vec<Vec<Vec<Cube>>> {
-    let depth = state.len() + 2;
-    let number_of_rows = state[0].len() + 2;
-    let number_of_columns = state[0][0].len() + 2;
-    let mut new_state = vec![vec![vec![Cube::Inactive; number_of_columns]; number_of_rows]; depth];
-    let mut old_state = vec![vec![vec![Cube::Inactive; number_of_columns]; number_of_rows]; depth];
-    for i in 0..(depth - 2) {
-        for j in 0..(number_of_rows - 2) {
-            for k in 0..(number_of_columns - 2) {
-                old_state[i + 1][j + 1][k + 1] = state[i][j][k]
-            }
-        }
-    }
-    for i in 0..depth {
-        for j in 0..number_of_rows {
-            for k in 0..number_of_columns {
-                let current = old_state[i][j][k];
-                new_state[i][j][k] = match (current, count_active(&old_state, i, j, k)) {
-                    (Cube::Active, 2..=3) => Cube::Active,
-                    (Cube::Inactive, 3) => Cube::Active,
-                    _ => Cube::Inactive,
-                }
-            }
-        }
-    }
-    new_state
+/// The planes either side of `i` that exist in `state`, including `i` itself.
+fn nearby<T>(state: &[T], i: usize) -> impl Iterator<Item = (usize, &T)> {
+    let start = i.saturating_sub(1);
+    state[start..(i + 2).min(state.len())]
+        .iter()
+        .enumerate()
+        .map(move |(offset, plane)| (start + offset, plane))
 }
 
-fn count_active2(state: &[Vec<Vec<Vec<Cube>>>], i: usize, j: usize, k: usize, w: usize) -> usize {
-    // Calculate all the relative positions for the neighbours.
-    let dirs = (-1..=1)
-        .flat_map(|i| {
-            (-1..=1)
-                .flat_map(|j| {
-                    (-1..=1)
-                        .flat_map(|k| {
-                            (-1..=1)
-                                .map(|w| (i, j, k, w))
-                                .collect::<Vec<(isize, isize, isize, isize)>>()
-                        })
-                        .collect::<Vec<(isize, isize, isize, isize)>>()
-                })
-                .collect::<Vec<(isize, isize, isize, isize)>>()
+fn count_active(state: &[Grid<Cube>], i: usize, j: usize, k: usize) -> usize {
+    nearby(state, i)
+        .map(|(z, plane)| {
+            let own = z != i && plane[(j, k)] == Cube::Active;
+            own as usize
+                + plane
+                    .neighbours8((j, k))
+                    .filter(|&p| plane[p] == Cube::Active)
+                    .count()
         })
-        .filter(|x| !(x.0 == 0 && x.1 == 0 && x.2 == 0 && x.3 == 0));
-    let mut count = 0;
-    for dir in dirs {
-        let c_i = i as isize + dir.0;
-        let c_j = j as isize + dir.1;
-        let c_k = k as isize + dir.2;
-        let c_w = w as isize + dir.3;
-        if c_i >= 0
-            && c_j >= 0
-            && c_k >= 0
-            && c_w >= 0
-            && c_i < state.len() as isize
-            && c_j < state[c_i as usize].len() as isize
-            && c_k < state[c_i as usize][c_j as usize].len() as isize
-            && c_w < state[c_i as usize][c_j as usize][c_k as usize].len() as isize
-            && state[c_i as usize][c_j as usize][c_k as usize][c_w as usize] == Cube::Active
-        {
-            count += 1;
-        }
-    }
-    count
+        .sum()
 }
 
-fn step2(state: Vec<Vec<Vec<Vec<Cube>>>>) -> Vec<Vec<Vec<Vec<Cube>>>> {
-    let depth = state.len() + 2;
-    let number_of_rows = state[0].len() + 2;
-    let number_of_columns = state[0][0].len() + 2;
-    let max_w = state[0][0][0].len() + 2;
-    let mut new_state =
-        vec![vec![vec![vec![Cube::Inactive; max_w]; number_of_columns]; number_of_rows]; depth];
-    let mut old_state =
-        vec![vec![vec![vec![Cube::Inactive; max_w]; number_of_columns]; number_of_rows]; depth];
-    for i in 0..(depth - 2) {
-        for j in 0..(number_of_rows - 2) {
-            for k in 0..(number_of_columns - 2) {
-                for w in 0..(max_w - 2) {
-                    old_state[i + 1][j + 1][k + 1][w + 1] = state[i][j][k][w]
-                }
-            }
-        }
-    }
-    for i in 0..depth {
-        for j in 0..number_of_rows {
-            for k in 0..number_of_columns {
-                for w in 0..max_w {
-                    let current = old_state[i][j][k][w];
-                    new_state[i][j][k][w] = match (current, count_active2(&old_state, i, j, k, w)) {
-                        (Cube::Active, 2..=3) => Cube::Active,
-                        (Cube::Inactive, 3) => Cube::Active,
-                        _ => Cube::Inactive,
-                    }
-                }
-            }
-        }
-    }
-    new_state
+/// Surrounds the state with inactive cubes so it can grow by one in each direction.
+fn pad(state: &[Grid<Cube>]) -> State {
+    let (rows, columns) = (state[0].rows() + 2, state[0].columns() + 2);
+    let empty = Grid::new(rows, columns, Cube::Inactive);
+    let planes = state.iter().map(|plane| plane.pad(1, Cube::Inactive));
+    iter::once(empty.clone())
+        .chain(planes)
+        .chain(iter::once(empty))
+        .collect()
+}
+
+fn step(state: State) -> State {
+    let old_state = pad(&state);
+    old_state
+        .iter()
+        .enumerate()
+        .map(|(i, plane)| {
+            Grid::from_fn(plane.rows(), plane.columns(), |(j, k)| {
+                next(plane[(j, k)], count_active(&old_state, i, j, k))
+            })
+        })
+        .collect()
+}
+
+fn count_active2(state: &[State], i: usize, j: usize, k: usize, w: usize) -> usize {
+    nearby(state, i)
+        .map(|(h, cube)| {
+            let own = h != i && cube[j][(k, w)] == Cube::Active;
+            own as usize + count_active(cube, j, k, w)
+        })
+        .sum()
 }
 
-fn parse_state(input: impl BufRead) -> State {
-    let numbers: Vec<Vec<Cube>> = input
-        .lines()
-        .map_while(Result::ok)
-        .map(|line| line.chars().map(|c| Cube::try_from(c).unwrap()).collect())
+fn step2(state: Vec<State>) -> Vec<State> {
+    let padded: Vec<State> = state.iter().map(|cube| pad(cube)).collect();
+    let plane = &padded[0][0];
+    let empty = vec![Grid::new(plane.rows(), plane.columns(), Cube::Inactive); padded[0].len()];
+    let old_state: Vec<State> = iter::once(empty.clone())
+        .chain(padded)
+        .chain(iter::once(empty))
         .collect();
-    vec![numbers]
+    old_state
+        .iter()
+        .enumerate()
+        .map(|(i, cube)| {
+            cube.iter()
+                .enumerate()
+                .map(|(j, plane)| {
+                    Grid::from_fn(plane.rows(), plane.columns(), |(k, w)| {
+                        next(plane[(k, w)], count_active2(&old_state, i, j, k, w))
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Parses the initial 2D slice of the pocket dimension.
+pub fn parse(input: impl BufRead) -> Result<Grid<Cube>, ParseError> {
+    Grid::parse(input)
 }
 
-pub fn star_one(input: impl BufRead) -> usize {
-    let mut state = parse_state(input);
-    println!("{:?}", state);
+pub fn star_one(input: &Grid<Cube>) -> usize {
+    let mut state: State = vec![input.clone()];
+    debug!("{}", input);
     for _i in 0..6 {
         state = step(state);
     }
     state
-        .into_iter()
-        .flat_map(|plane| plane.into_iter().flatten())
-        .filter(|x| x == &Cube::Active)
+        .iter()
+        .flat_map(Grid::values)
+        .filter(|x| x == &&Cube::Active)
         .count()
 }
 
-pub fn star_two(input: impl BufRead) -> usize {
-    let mut state = vec![parse_state(input)];
-    println!("{:?}", state);
+pub fn star_two(input: &Grid<Cube>) -> usize {
+    let mut state = vec![vec![input.clone()]];
+    debug!("{}", input);
     for _i in 0..6 {
         state = step2(state);
     }
     state
-        .into_iter()
-        .flat_map(|cube| {
-            cube.into_iter()
-                .flat_map(|plane| plane.into_iter().flatten())
-        })
-        .filter(|x| x == &Cube::Active)
+        .iter()
+        .flatten()
+        .flat_map(Grid::values)
+        .filter(|x| x == &&Cube::Active)
         .count()
 }
 
+/// Generates a starting slice `size` cubes square.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let slice = Grid::from_fn(size.max(1), size.max(1), |_| {
+        if rng.chance(1, 2) {
+            Cube::Active
+        } else {
+            Cube::Inactive
+        }
+    });
+    format!("{}\n", slice)
+}
+
+/// The coordinates of the active cubes in `state` after `cycles` cycles, which have
+/// padded it by that many cubes on every side.
+fn active(state: &[Grid<Cube>], cycles: usize) -> Vec<Vec<isize>> {
+    let offset = cycles as isize;
+    state
+        .iter()
+        .enumerate()
+        .flat_map(|(z, plane)| {
+            plane
+                .positions()
+                .filter(|&p| plane[p] == Cube::Active)
+                .map(move |(y, x)| {
+                    vec![
+                        z as isize - offset,
+                        y as isize - offset,
+                        x as isize - offset,
+                    ]
+                })
+        })
+        .collect()
+}
+
+fn active2(state: &[State], cycles: usize) -> Vec<Vec<isize>> {
+    state
+        .iter()
+        .enumerate()
+        .flat_map(|(w, cube)| {
+            active(cube, cycles)
+                .into_iter()
+                .map(move |mut coordinates| {
+                    coordinates.insert(0, w as isize - cycles as isize);
+                    coordinates
+                })
+        })
+        .collect()
+}
+
+/// The active cubes after each of six cycles in `dimensions` dimensions, worked out
+/// with a set of coordinates as a reference for [step] and [step2].
+fn reference_cycles(input: &Grid<Cube>, dimensions: usize) -> Vec<String> {
+    let mut active: HashSet<Vec<isize>> = input
+        .positions()
+        .filter(|&p| input[p] == Cube::Active)
+        .map(|(y, x)| {
+            let mut coordinates = vec![0; dimensions - 2];
+            coordinates.extend([y as isize, x as isize]);
+            coordinates
+        })
+        .collect();
+    let directions: Vec<Vec<isize>> = (0..dimensions)
+        .map(|_| -1..=1)
+        .multi_cartesian_product()
+        .filter(|direction| direction.iter().any(|&d| d != 0))
+        .collect();
+    (0..6)
+        .map(|_| {
+            let mut neighbours: HashMap<Vec<isize>, usize> = HashMap::new();
+            for cube in &active {
+                for direction in &directions {
+                    let neighbour = cube.iter().zip(direction).map(|(a, b)| a + b).collect();
+                    *neighbours.entry(neighbour).or_default() += 1;
+                }
+            }
+            active = neighbours
+                .into_iter()
+                .filter(|(cube, count)| *count == 3 || *count == 2 && active.contains(cube))
+                .map(|(cube, _)| cube)
+                .collect();
+            format!("{:?}", active.iter().sorted().collect::<Vec<_>>())
+        })
+        .collect()
+}
+
+pub(crate) fn differentials() -> Vec<Differential> {
+    vec![
+        Differential {
+            day: 17,
+            name: "3D cycles",
+            size: 5,
+            reference: |input| Ok(reference_cycles(&parse(input.as_bytes())?, 3)),
+            fast: |input| {
+                let mut state = vec![parse(input.as_bytes())?];
+                Ok((1..=6)
+                    .map(|cycle| {
+                        state = step(std::mem::take(&mut state));
+                        format!(
+                            "{:?}",
+                            active(&state, cycle).iter().sorted().collect::<Vec<_>>()
+                        )
+                    })
+                    .collect())
+            },
+        },
+        Differential {
+            day: 17,
+            name: "4D cycles",
+            size: 5,
+            reference: |input| Ok(reference_cycles(&parse(input.as_bytes())?, 4)),
+            fast: |input| {
+                let mut state = vec![vec![parse(input.as_bytes())?]];
+                Ok((1..=6)
+                    .map(|cycle| {
+                        state = step2(std::mem::take(&mut state));
+                        format!(
+                            "{:?}",
+                            active2(&state, cycle).iter().sorted().collect::<Vec<_>>()
+                        )
+                    })
+                    .collect())
+            },
+        },
+    ]
+}
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    type Input = Grid<Cube>;
+
+    fn name(&self) -> &'static str {
+        "Conway Cubes"
+    }
+
+    fn day(&self) -> usize {
+        17
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(8))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -208,27 +321,13 @@ mod tests {
 
     fn print_state(state: &State) {
         for z in state {
-            for row in z {
-                for x in row {
-                    print!("{:?}", x);
-                }
-                println!();
-            }
-            println!();
+            eprintln!("{}\n", z);
         }
     }
 
-    fn print_state2(state: &Vec<Vec<Vec<Vec<Cube>>>>) {
+    fn print_state2(state: &[State]) {
         for w in state {
-            for z in w {
-                for row in z {
-                    for x in row {
-                        print!("{:?}", x);
-                    }
-                    println!();
-                }
-                println!();
-            }
+            print_state(w);
         }
     }
 
@@ -250,8 +349,7 @@ mod tests {
             .filter(|x| !(x.0 == 0 && x.1 == 0 && x.2 == 0))
             .collect();
         assert_eq!(dirs.len(), 26);
-        let state = parse_state(Cursor::new(input));
-        // dbg!(&state);
+        let state = vec![parse(Cursor::new(input)).unwrap()];
 
         assert_eq!(count_active(&state, 0, 0, 1), 1);
     }
@@ -262,7 +360,7 @@ mod tests {
 ..#
 ###";
 
-        let state = parse_state(Cursor::new(input));
+        let state = vec![parse(Cursor::new(input)).unwrap()];
         let new_state = step(state);
 
         print_state(&new_state);
@@ -273,7 +371,7 @@ mod tests {
         let input = b".#.
 ..#
 ###";
-        assert_eq!(star_one(Cursor::new(input)), 112);
+        assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 112);
     }
 
     #[test]
@@ -282,7 +380,7 @@ mod tests {
 ..#
 ###";
 
-        let state = vec![parse_state(Cursor::new(input))];
+        let state = vec![vec![parse(Cursor::new(input)).unwrap()]];
 
         let new_state = step2(state);
 
@@ -314,6 +412,6 @@ mod tests {
         let input = b".#.
 ..#
 ###";
-        assert_eq!(star_two(Cursor::new(input)), 848);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 848);
     }
 }
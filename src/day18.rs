@@ -9,6 +9,10 @@
 use std::collections::VecDeque;
 use std::io::BufRead;
 
+use crate::generate::Rng;
+use crate::input::parse_lines;
+use crate::{Answer, ParseError, Solution};
+
 // enum Expression {
 //     Addition(Box<Expression>, Box<Expression>),
 //     Multiply(Box<Expression>, Box<Expression>),
@@ -40,8 +44,8 @@ use std::io::BufRead;
 //     )(input)
 // }
 
-#[derive(Debug, PartialEq)]
-enum Op {
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Op {
     Add,
     Mult,
     Value(usize),
@@ -61,8 +65,8 @@ fn eval_addition_multiply(mut input: VecDeque<Op>) -> VecDeque<Op> {
             let a = match result.pop_back() {
                 Some(Op::Value(v)) => v,
                 v => {
-                    println!("Res: {:?}", input);
-                    println!("Add: {:?}", result);
+                    debug!("Res: {:?}", input);
+                    debug!("Add: {:?}", result);
                     panic!("{:?}", v)
                 }
             };
@@ -132,8 +136,8 @@ fn eval_addition(mut input: VecDeque<Op>) -> VecDeque<Op> {
             let a = match result.pop_back() {
                 Some(Op::Value(v)) => v,
                 v => {
-                    println!("Res: {:?}", input);
-                    println!("Add: {:?}", result);
+                    debug!("Res: {:?}", input);
+                    debug!("Add: {:?}", result);
                     panic!("{:?}", v)
                 }
             };
@@ -171,74 +175,122 @@ fn eval_multiply(mut input: VecDeque<Op>) -> VecDeque<Op> {
     result
 }
 
-pub fn star_one(mut input: impl BufRead) -> usize {
-    let mut input_str = String::new();
-    input
-        .read_to_string(&mut input_str)
-        .expect("Could not read all of string");
-    // let (input, expression) = parse(&input_str).unwrap();
-    // println!("{}", input);
-    // expression.eval()
-    input_str
-        .lines()
-        .map(|line| {
-            let chars: Vec<Op> = line
-                .chars()
-                .filter(|c| c != &' ')
-                .map(|c| match c {
-                    '+' => Op::Add,
-                    '*' => Op::Mult,
-                    c @ '0'..='9' => Op::Value(c.to_digit(10).unwrap() as usize),
-                    '(' => Op::LeftParen,
-                    ')' => Op::RightParen,
-                    _ => panic!("basklf"),
-                })
-                .collect();
-            let res = eval_star_one(chars.into());
-            if res.len() != 1 {
-                panic!("Not equal to 1");
-            }
-            match res[0] {
-                Op::Value(x) => x,
-                _ => panic!("blah"),
-            }
-        })
-        .sum()
+/// Tokenizes a line of homework, checking that it is a well formed expression.
+fn parse_line(line: &str) -> Result<Vec<Op>, ParseError> {
+    let mut ops = Vec::new();
+    let mut expect_operand = true;
+    let mut depth = 0;
+    for (i, c) in line.chars().enumerate().filter(|(_, c)| c != &' ') {
+        let error = |message: &str| Err(ParseError::new(message).with_column(i + 1));
+        let op = match c {
+            '+' => Op::Add,
+            '*' => Op::Mult,
+            '0'..='9' => Op::Value(c.to_digit(10).unwrap() as usize),
+            '(' => Op::LeftParen,
+            ')' => Op::RightParen,
+            c => return error(&format!("Unknown character {}", c)),
+        };
+        match (op, expect_operand) {
+            (Op::Value(_), true) => expect_operand = false,
+            (Op::LeftParen, true) => depth += 1,
+            (Op::Add | Op::Mult, false) => expect_operand = true,
+            (Op::RightParen, false) if depth > 0 => depth -= 1,
+            (Op::RightParen, false) => return error("Unmatched closing parenthesis"),
+            (_, true) => return error("Expected a number or an opening parenthesis"),
+            (_, false) => return error("Expected an operator or a closing parenthesis"),
+        }
+        ops.push(op);
+    }
+    if expect_operand {
+        Err(ParseError::new("Expression is incomplete").with_column(line.chars().count() + 1))
+    } else if depth > 0 {
+        Err(ParseError::new("Unclosed parenthesis").with_column(line.chars().count() + 1))
+    } else {
+        Ok(ops)
+    }
 }
 
-pub fn star_two(mut input: impl BufRead) -> usize {
-    let mut input_str = String::new();
-    input
-        .read_to_string(&mut input_str)
-        .expect("Could not read all of string");
-    // let (input, expression) = parse(&input_str).unwrap();
-    // println!("{}", input);
-    // expression.eval()
-    input_str
-        .lines()
-        .map(|line| {
-            let chars: Vec<Op> = line
-                .chars()
-                .filter(|c| c != &' ')
-                .map(|c| match c {
-                    '+' => Op::Add,
-                    '*' => Op::Mult,
-                    c @ '0'..='9' => Op::Value(c.to_digit(10).unwrap() as usize),
-                    '(' => Op::LeftParen,
-                    ')' => Op::RightParen,
-                    _ => panic!("basklf"),
-                })
-                .collect();
-            let res = eval_star_two(chars.into());
-            if res.len() != 1 {
-                panic!("Not equal to 1");
-            }
-            match res[0] {
-                Op::Value(x) => x,
-                _ => panic!("blah"),
+/// Tokenizes each line of homework.
+pub fn parse(input: impl BufRead) -> Result<Vec<Vec<Op>>, ParseError> {
+    parse_lines(input, parse_line)
+}
+
+fn evaluate(line: &[Op], eval: impl Fn(VecDeque<Op>) -> VecDeque<Op>) -> usize {
+    let res = eval(line.iter().copied().collect());
+    if res.len() != 1 {
+        panic!("Not equal to 1");
+    }
+    match res[0] {
+        Op::Value(x) => x,
+        _ => panic!("blah"),
+    }
+}
+
+pub fn star_one(input: &[Vec<Op>]) -> usize {
+    input.iter().map(|line| evaluate(line, eval_star_one)).sum()
+}
+
+pub fn star_two(input: &[Vec<Op>]) -> usize {
+    input.iter().map(|line| evaluate(line, eval_star_two)).sum()
+}
+
+fn generate_expression(rng: &mut Rng, depth: usize) -> String {
+    let mut expression = String::new();
+    for i in 0..rng.range(2..5) {
+        if i > 0 {
+            expression += if rng.chance(1, 2) { " + " } else { " * " };
+        }
+        if depth < 2 && rng.chance(1, 4) {
+            expression += &format!("({})", generate_expression(rng, depth + 1));
+        } else {
+            expression += &rng.range(1..10).to_string();
+        }
+    }
+    expression
+}
+
+/// Generates `size` expressions.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| loop {
+            let expression = generate_expression(rng, 0);
+            // However the expression is evaluated the result is at most 9 to the
+            // power of the number of digits, which needs to fit in a usize.
+            if expression.chars().filter(char::is_ascii_digit).count() <= 14 {
+                break format!("{}\n", expression);
             }
         })
-        .sum()
+        .collect()
+}
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    type Input = Vec<Vec<Op>>;
+
+    fn name(&self) -> &'static str {
+        "Operation Order"
+    }
+
+    fn day(&self) -> usize {
+        18
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(370))
+    }
 }
 
 #[cfg(test)]
@@ -246,50 +298,60 @@ mod tests {
     use super::*;
     use std::io::Cursor;
 
+    #[test]
+    fn test_parse_error() {
+        let error = parse(Cursor::new("1 + 2\n(1 + 2 * 3\n")).unwrap_err();
+        assert_eq!(error.line(), Some(2));
+        assert_eq!(error.message(), "Unclosed parenthesis");
+
+        let error = parse(Cursor::new("1 + * 2")).unwrap_err();
+        assert_eq!(error.column(), Some(5));
+    }
+
     #[test]
     fn test_star_one() {
         {
             let input = b"1 + 2 * 3 + 4 * 5 + 6";
-            assert_eq!(star_one(Cursor::new(input)), 71);
+            assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 71);
         }
         {
             let input = b"2 * 3 + (4 * 5)";
-            assert_eq!(star_one(Cursor::new(input)), 26);
+            assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 26);
         }
         {
             let input = b"5 + (8 * 3 + 9 + 3 * 4 * 3)";
-            assert_eq!(star_one(Cursor::new(input)), 437);
+            assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 437);
         }
         {
             let input = b"5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))";
-            assert_eq!(star_one(Cursor::new(input)), 12240);
+            assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 12240);
         }
         {
             let input = b"((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2";
-            assert_eq!(star_one(Cursor::new(input)), 13632);
+            assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 13632);
         }
     }
     #[test]
     fn test_star_two() {
         {
             let input = b"1 + 2 * 3 + 4 * 5 + 6";
-            assert_eq!(star_two(Cursor::new(input)), 231);
+            assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 231);
         }
         {
             let input = b"2 * 3 + (4 * 5)";
-            assert_eq!(star_two(Cursor::new(input)), 46);
+            assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 46);
         }
         {
             let input = b"5 + (8 * 3 + 9 + 3 * 4 * 3)";
-            assert_eq!(star_two(Cursor::new(input)), 1445);
+            assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 1445);
         }
         {
             let input = b"5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))";
-            assert_eq!(star_two(Cursor::new(input)), 669060);
+            assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 669060);
         }
         {
             let input = b"((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2";
-            assert_eq!(star_two(Cursor::new(input)), 23340);
+            assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 23340);
         }
     }
 }
@@ -1,6 +1,10 @@
 use std::{collections::HashMap, io::BufRead};
 
-#[derive(Debug, PartialEq)]
+use crate::generate::Rng;
+use crate::input::sections;
+use crate::{Answer, ParseError, Solution};
+
+#[derive(Debug, Clone, PartialEq)]
 enum Rule {
     Value(char),
     Ref(usize),
@@ -8,27 +12,47 @@ enum Rule {
     Multiple(Vec<Rule>),
 }
 
-fn parse_single(input: &str) -> Rule {
-    if input.contains('\"') {
-        let v: Vec<char> = input.chars().collect();
-        if v.len() != 3 {
-            panic!("Input length is not a char: {}", input)
+fn parse_single(line: &str, token: &str) -> Result<Rule, ParseError> {
+    if let Some(value) = token.strip_prefix('"').and_then(|t| t.strip_suffix('"')) {
+        let v: Vec<char> = value.chars().collect();
+        if v.len() != 1 {
+            return Err(ParseError::at_token(
+                line,
+                token,
+                format!("Expected a single quoted character, found {}", token),
+            ));
         }
-        Rule::Value(v[1])
-    } else if let Ok(value) = input.parse::<usize>() {
-        Rule::Ref(value)
+        Ok(Rule::Value(v[0]))
+    } else if let Ok(value) = token.parse::<usize>() {
+        Ok(Rule::Ref(value))
     } else {
-        panic!("Unable to parse");
+        Err(ParseError::at_token(
+            line,
+            token,
+            format!("Expected a rule number or a character, found {}", token),
+        ))
     }
 }
 
-fn parse_rule(input: &[&str]) -> Rule {
+fn parse_rule(line: &str, input: &[&str]) -> Result<Rule, ParseError> {
     if input.contains(&"|") {
-        Rule::Alt(input.split(|x| x == &"|").map(parse_rule).collect())
+        Ok(Rule::Alt(
+            input
+                .split(|x| x == &"|")
+                .map(|alt| parse_rule(line, alt))
+                .collect::<Result<_, _>>()?,
+        ))
+    } else if input.is_empty() {
+        Err(ParseError::new("Expected a rule").with_column(line.chars().count() + 1))
     } else if input.len() == 1 {
-        parse_single(input[0])
+        parse_single(line, input[0])
     } else {
-        Rule::Multiple(input.iter().map(|r| parse_single(r)).collect())
+        Ok(Rule::Multiple(
+            input
+                .iter()
+                .map(|r| parse_single(line, r))
+                .collect::<Result<_, _>>()?,
+        ))
     }
 }
 
@@ -45,7 +69,7 @@ fn match_rule<'a>(
     rule: &Rule,
     input: &'a [char],
 ) -> Option<Vec<&'a [char]>> {
-    // println!("{:?}: {:?}", rule, input);
+    trace!("{:?}: {:?}", rule, input);
     match rule {
         Rule::Multiple(v) => {
             let mut new_remainders = vec![input];
@@ -87,64 +111,232 @@ fn match_rule<'a>(
     }
 }
 
-fn parse_rule_line(line: &str) -> (usize, Rule) {
-    let index = line.split(':').next().unwrap().parse::<usize>().unwrap();
-    let rule_string: Vec<&str> = line.split(':').nth(1).unwrap().split_whitespace().collect();
-    let rule = parse_rule(&rule_string[..]);
-    (index, rule)
+fn parse_rule_line(line: &str) -> Result<(usize, Rule), ParseError> {
+    let (index, rule_string) = line
+        .split_once(':')
+        .ok_or_else(|| ParseError::new("Expected a rule like `0: 4 1 5`").with_column(1))?;
+    let index = index
+        .parse::<usize>()
+        .map_err(|e| ParseError::from(e).with_column(1))?;
+    let rule_string: Vec<&str> = rule_string.split_whitespace().collect();
+    let rule = parse_rule(line, &rule_string[..])?;
+    Ok((index, rule))
 }
 
-fn parse_input(mut input: impl BufRead, overrides: Option<&str>) -> (String, HashMap<usize, Rule>) {
-    let mut input_str = String::new();
-    input
-        .read_to_string(&mut input_str)
-        .expect("Could not read all of string");
-    let mut sections = input_str.split("\n\n");
-    let mut rules: HashMap<usize, Rule> = sections
+/// Finds a rule number referred to by `rule` which is not in `rules`.
+fn missing_ref(rules: &HashMap<usize, Rule>, rule: &Rule) -> Option<usize> {
+    match rule {
+        Rule::Value(_) => None,
+        Rule::Ref(i) => Some(*i).filter(|i| !rules.contains_key(i)),
+        Rule::Alt(v) | Rule::Multiple(v) => v.iter().find_map(|r| missing_ref(rules, r)),
+    }
+}
+
+#[derive(Debug)]
+pub struct Messages {
+    rules: HashMap<usize, Rule>,
+    messages: Vec<String>,
+}
+
+pub fn parse(input: impl BufRead) -> Result<Messages, ParseError> {
+    let mut sections = sections(input);
+    let section = sections
+        .next()
+        .transpose()?
+        .ok_or_else(|| ParseError::new("Missing rules section"))?;
+    let mut rules = HashMap::new();
+    let mut indices = Vec::new();
+    for (number, line) in section.lines() {
+        let (index, rule) = parse_rule_line(line).map_err(|e| e.in_line(number, line))?;
+        rules.insert(index, rule);
+        indices.push((number, line, index));
+    }
+    for (number, line, index) in indices {
+        if let Some(missing) = missing_ref(&rules, &rules[&index]) {
+            return Err(ParseError::new(format!(
+                "Rule {} refers to rule {} which does not exist",
+                index, missing
+            ))
+            .in_line(number, line));
+        }
+    }
+    if !rules.contains_key(&0) {
+        return Err(ParseError::new("Rule 0 not found"));
+    }
+    let messages = sections
         .next()
-        .unwrap()
+        .transpose()?
+        .ok_or_else(|| ParseError::new("Missing messages section"))?
         .lines()
-        .map(parse_rule_line)
+        .map(|(_, line)| line.trim().to_string())
         .collect();
-    if let Some(overrides) = overrides {
-        for line in overrides.lines() {
-            let (index, rule) = parse_rule_line(line);
-            rules.insert(index, rule);
-        }
+    Ok(Messages { rules, messages })
+}
+
+fn apply_overrides(rules: &mut HashMap<usize, Rule>, overrides: &str) {
+    for line in overrides.lines() {
+        let (index, rule) = parse_rule_line(line).expect("Overrides should be valid rules");
+        rules.insert(index, rule);
     }
-    let values = sections.next().unwrap();
-    (values.to_string(), rules)
 }
 
-pub fn star_one(input: impl BufRead) -> usize {
-    let (values, rules) = parse_input(input, None);
+fn count_matching(rules: &HashMap<usize, Rule>, messages: &[String]) -> usize {
     let rule0 = rules.get(&0).expect("Rule 0 not found");
-    values
-        .lines()
+    messages
+        .iter()
         .filter(|line| {
             let chars: Vec<char> = line.chars().collect();
-            let res = match_rule(&rules, rule0, &chars[..]);
+            let res = match_rule(rules, rule0, &chars[..]);
+            // Check if any of the inputs left to parse is empty.
             res.map(|r| r.iter().any(|r| r.is_empty())).unwrap_or(false)
         })
         .count()
 }
 
-pub fn star_two(input: impl BufRead) -> usize {
+pub fn star_one(input: &Messages) -> usize {
+    count_matching(&input.rules, &input.messages)
+}
+
+pub fn star_two(input: &Messages) -> usize {
     let overrides = "8: 42 | 42 8
 11: 42 31 | 42 11 31";
-    let (values, rules) = parse_input(input, Some(overrides));
+    let mut rules = input.rules.clone();
+    apply_overrides(&mut rules, overrides);
 
-    let rule0 = rules.get(&0).expect("Rule 0 not found");
+    count_matching(&rules, &input.messages)
+}
 
-    values
-        .lines()
-        .filter(|line| {
-            let chars: Vec<char> = line.trim().chars().collect();
-            let res = match_rule(&rules, rule0, &chars[..]);
-            // Check if any of the inputs left to parse is empty.
-            res.map(|r| r.iter().any(|r| r.is_empty())).unwrap_or(false)
+/// A rule in a generated grammar, referring to other rules by their position.
+enum Grammar {
+    Value(char),
+    Alt(Vec<Vec<usize>>),
+}
+
+fn expand(grammar: &[Grammar], rng: &mut Rng, rule: usize, message: &mut String) {
+    match &grammar[rule] {
+        Grammar::Value(c) => message.push(*c),
+        Grammar::Alt(alternatives) => {
+            for &rule in rng.choose(alternatives) {
+                expand(grammar, rng, rule, message);
+            }
+        }
+    }
+}
+
+/// Generates rules in the same shape as the puzzle's, where rule 0 is `8 11` and
+/// rules 42 and 31 match strings of eight characters, followed by `size` messages.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    // Build the grammar up in levels, each matching strings twice as long as the
+    // level below.
+    let mut grammar = vec![Grammar::Value('a'), Grammar::Value('b')];
+    let mut level = vec![0, 1];
+    for depth in 1..=3 {
+        let count = if depth == 3 { 2 } else { 3 };
+        let mut next = Vec::new();
+        for _ in 0..count {
+            let alternatives = (0..rng.range(1..3))
+                .map(|_| vec![*rng.choose(&level), *rng.choose(&level)])
+                .collect();
+            grammar.push(Grammar::Alt(alternatives));
+            next.push(grammar.len() - 1);
+        }
+        level = next;
+    }
+    let (rule42, rule31) = (level[0], level[1]);
+    grammar.push(Grammar::Alt(vec![vec![rule42]]));
+    let rule8 = grammar.len() - 1;
+    grammar.push(Grammar::Alt(vec![vec![rule42, rule31]]));
+    let rule11 = grammar.len() - 1;
+    grammar.push(Grammar::Alt(vec![vec![rule8, rule11]]));
+    let rule0 = grammar.len() - 1;
+
+    // Give the rest of the rules numbers which are not already taken.
+    let fixed = [
+        (rule0, 0),
+        (rule8, 8),
+        (rule11, 11),
+        (rule42, 42),
+        (rule31, 31),
+    ];
+    let mut free = (1..grammar.len() + 10).filter(|n| ![0, 8, 11, 31, 42].contains(n));
+    let mut numbers: Vec<usize> = (0..grammar.len()).map(|_| free.next().unwrap()).collect();
+    rng.shuffle(&mut numbers);
+    for (rule, number) in fixed {
+        numbers[rule] = number;
+    }
+    let mut rules: Vec<String> = grammar
+        .iter()
+        .enumerate()
+        .map(|(i, rule)| match rule {
+            Grammar::Value(c) => format!("{}: \"{}\"\n", numbers[i], c),
+            Grammar::Alt(alternatives) => {
+                let alternatives: Vec<String> = alternatives
+                    .iter()
+                    .map(|rules| {
+                        let rules: Vec<String> =
+                            rules.iter().map(|&r| numbers[r].to_string()).collect();
+                        rules.join(" ")
+                    })
+                    .collect();
+                format!("{}: {}\n", numbers[i], alternatives.join(" | "))
+            }
         })
-        .count()
+        .collect();
+    rng.shuffle(&mut rules);
+
+    let messages: String = (0..size)
+        .map(|_| {
+            // Messages matching the looping rules of part two, some of which also
+            // match the rules of part one.
+            let thirty_ones = rng.range(1..4);
+            let forty_twos = rng.range(thirty_ones + 1..thirty_ones + 4);
+            let mut message = String::new();
+            for _ in 0..forty_twos {
+                expand(&grammar, rng, rule42, &mut message);
+            }
+            for _ in 0..thirty_ones {
+                expand(&grammar, rng, rule31, &mut message);
+            }
+            if rng.chance(1, 3) {
+                // Most likely no longer a match.
+                let i = rng.range(0..message.len());
+                let c = if &message[i..i + 1] == "a" { "b" } else { "a" };
+                message.replace_range(i..i + 1, c);
+            }
+            message + "\n"
+        })
+        .collect();
+    format!("{}\n{}", rules.concat(), messages)
+}
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    type Input = Messages;
+
+    fn name(&self) -> &'static str {
+        "Monster Messages"
+    }
+
+    fn day(&self) -> usize {
+        19
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(400))
+    }
 }
 
 #[cfg(test)]
@@ -214,7 +406,7 @@ bababa
 abbbab
 aaabbb
 aaaabbb";
-        assert_eq!(star_one(Cursor::new(input)), 2);
+        assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 2);
     }
 
     #[test]
@@ -224,7 +416,7 @@ aaaabbb";
 2: \"b\"
 
 test";
-        let (_, rules) = parse_input(Cursor::new(input), None);
+        let rules = parse(Cursor::new(input)).unwrap().rules;
         let test: Vec<char> = "a".chars().collect();
         assert_eq!(
             match_rule(&rules, rules.get(&0).unwrap(), &test[..]),
@@ -236,7 +428,8 @@ test";
     fn test_match_rule() {
         let overrides = "8: 42 | 42 8
 11: 42 31 | 42 11 31";
-        let (_, rules) = parse_input(Cursor::new(INPUT), Some(overrides));
+        let mut rules = parse(Cursor::new(INPUT)).unwrap().rules;
+        apply_overrides(&mut rules, overrides);
         let test: Vec<char> = "babbbbaabbbbbabbbbbbaabaaabaaa".chars().collect();
         let output = match_rule(&rules, rules.get(&0).unwrap(), &test[..]);
         assert_eq!(output.map(|r| r.iter().any(|r| r.is_empty())), Some(true));
@@ -245,7 +438,7 @@ test";
     #[test]
     fn test_parse_rule() {
         {
-            let (index, rule) = parse_rule_line("8: 42 | 42 8");
+            let (index, rule) = parse_rule_line("8: 42 | 42 8").unwrap();
             let expected = Rule::Alt(vec![
                 Rule::Ref(42),
                 Rule::Multiple(vec![Rule::Ref(42), Rule::Ref(8)]),
@@ -255,7 +448,7 @@ test";
         }
 
         {
-            let (index, rule) = parse_rule_line("11: 42 31 | 42 11 31");
+            let (index, rule) = parse_rule_line("11: 42 31 | 42 11 31").unwrap();
             let expected = Rule::Alt(vec![
                 Rule::Multiple(vec![Rule::Ref(42), Rule::Ref(31)]),
                 Rule::Multiple(vec![Rule::Ref(42), Rule::Ref(11), Rule::Ref(31)]),
@@ -265,8 +458,22 @@ test";
         }
     }
 
+    #[test]
+    fn test_parse_error() {
+        let error = parse(Cursor::new("0: 1 2\n1: \"a\"\n\nab")).unwrap_err();
+        assert_eq!(error.line(), Some(1));
+        assert_eq!(
+            error.message(),
+            "Rule 0 refers to rule 2 which does not exist"
+        );
+
+        let error = parse(Cursor::new("0: 1\n1: a\n\na")).unwrap_err();
+        assert_eq!(error.line(), Some(2));
+        assert_eq!(error.column(), Some(4));
+    }
+
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(Cursor::new(INPUT)), 12);
+        assert_eq!(star_two(&parse(Cursor::new(INPUT)).unwrap()), 12);
     }
 }
@@ -8,12 +8,18 @@ use std::{
     str::FromStr,
 };
 
+use crate::cancel::{self, Cancelled, Token};
+use crate::generate::Rng;
+use crate::grid::{self, Grid};
+use crate::input::{sections, Section};
+use crate::{Answer, ParseError, Solution};
+
 lazy_static! {
     static ref RE: Regex = Regex::new(r"^Tile (?P<id>\d+):").unwrap();
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-enum Pixel {
+pub enum Pixel {
     Hash,
     Dot,
 }
@@ -24,7 +30,7 @@ impl TryFrom<char> for Pixel {
         match c {
             '.' => Ok(Pixel::Dot),
             '#' => Ok(Pixel::Hash),
-            _ => Err(format!("Could not match {} to SeatStatus", c)),
+            _ => Err(format!("Could not match {} to Pixel", c)),
         }
     }
 }
@@ -40,13 +46,13 @@ impl Display for Pixel {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Tile {
+pub struct Tile {
     id: usize,
     left: Edge,
     right: Edge,
     top: Edge,
     bottom: Edge,
-    matrix: Vec<Vec<Pixel>>,
+    matrix: Matrix,
 }
 
 impl Tile {
@@ -55,7 +61,7 @@ impl Tile {
         top.reverse();
         let mut bottom = self.right.clone();
         bottom.reverse();
-        let matrix = matrix_rotate(&self.matrix);
+        let matrix = self.matrix.rotate();
         Tile {
             id: self.id,
             top,
@@ -74,7 +80,7 @@ impl Tile {
                 let mut left = self.left.clone();
                 left.reverse();
 
-                let matrix = matrix_flip(&self.matrix, axis);
+                let matrix = self.matrix.flip_vertical();
                 Tile {
                     id: self.id,
                     top: self.bottom.clone(),
@@ -89,7 +95,7 @@ impl Tile {
                 top.reverse();
                 let mut bottom = self.bottom.clone();
                 bottom.reverse();
-                let matrix = matrix_flip(&self.matrix, axis);
+                let matrix = self.matrix.flip_horizontal();
                 Tile {
                     id: self.id,
                     top,
@@ -104,23 +110,52 @@ impl Tile {
     }
 }
 
-impl FromStr for Tile {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut lines = s.lines();
-        let captures = RE
-            .captures(lines.next().unwrap())
-            .expect("Bad line that does not match regex.");
-        let id = captures["id"].parse::<usize>().unwrap();
-        let matrix: Vec<Vec<Pixel>> = lines
-            .map(|line| line.chars().map(|c| Pixel::try_from(c).unwrap()).collect())
-            .collect();
-        // number_of_tiles += 1;
-        let top = matrix[0].clone();
-        let bottom = matrix.last().unwrap().clone();
-        let left = matrix.iter().map(|r| r[0]).collect();
-        let right = matrix.iter().map(|r| *r.last().unwrap()).collect();
+impl Tile {
+    fn parse(section: &Section) -> Result<Self, ParseError> {
+        let mut lines = section.lines();
+        // Sections always have at least one line.
+        let (number, header) = lines.next().unwrap();
+        let size = section.lines().count() - 1;
+        let captures = RE.captures(header).ok_or_else(|| {
+            ParseError::new("Expected a header like `Tile 1234:`")
+                .with_column(1)
+                .in_line(number, header)
+        })?;
+        let id = captures["id"]
+            .parse::<usize>()
+            .map_err(|e| ParseError::at_token(header, &captures["id"], e.to_string()))
+            .map_err(|e| e.in_line(number, header))?;
+        let rows: Vec<Vec<Pixel>> = lines
+            .map(|(number, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(|(i, c)| {
+                        Pixel::try_from(c).map_err(|e| {
+                            ParseError::new(e).with_column(i + 1).in_line(number, line)
+                        })
+                    })
+                    .collect::<Result<Vec<Pixel>, ParseError>>()
+                    .and_then(|row| {
+                        if row.len() == size {
+                            Ok(row)
+                        } else {
+                            Err(ParseError::new(format!("Tile {} is not square", id))
+                                .in_line(number, line))
+                        }
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        if rows.is_empty() {
+            return Err(
+                ParseError::new(format!("Tile {} has no pixels", id)).in_line(number, header)
+            );
+        }
+        // Every row was checked against the number of rows above.
+        let matrix = Grid::from_rows(rows).unwrap();
+        let top = matrix.row(0).to_vec();
+        let bottom = matrix.row(matrix.rows() - 1).to_vec();
+        let left = matrix.column(0);
+        let right = matrix.column(matrix.columns() - 1);
         Ok(Tile {
             id,
             left,
@@ -132,41 +167,30 @@ impl FromStr for Tile {
     }
 }
 
-fn matrix_flip(matrix: &[Vec<Pixel>], axis: usize) -> Vec<Vec<Pixel>> {
-    match axis {
-        0 => matrix.iter().rev().cloned().collect(),
-        1 => matrix
-            .iter()
-            .map(|row| row.iter().rev().cloned().collect())
-            .collect(),
-        x => panic!("Could not flip about axis {}", x),
-    }
-}
+impl FromStr for Tile {
+    type Err = ParseError;
 
-fn matrix_rotate(matrix: &[Vec<Pixel>]) -> Vec<Vec<Pixel>> {
-    let mut new_matrix = matrix.to_owned();
-    let matrix_height = matrix.len();
-    for i in 0..matrix.len() {
-        for (j, row) in new_matrix.iter_mut().enumerate().take(matrix[i].len()) {
-            row[i] = matrix[matrix_height - 1 - i][j];
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match sections(s.as_bytes()).next() {
+            Some(section) => Tile::parse(&section?),
+            None => Err(ParseError::new("Expected a tile")),
         }
     }
-    new_matrix
 }
 
 fn matrix_rotations(matrix: Matrix) -> Vec<Matrix> {
     let mut rotations = Vec::with_capacity(4);
     rotations.push(matrix);
     for _ in 0..3 {
-        rotations.push(matrix_rotate(rotations.last().unwrap()))
+        rotations.push(rotations.last().unwrap().rotate())
     }
     rotations
 }
 
 fn matrix_flips(matrix: Matrix) -> Vec<Matrix> {
     let mut flips = Vec::with_capacity(4);
-    let flip_h = matrix_flip(&matrix, 0);
-    let flip_v = matrix_flip(&matrix, 1);
+    let flip_h = matrix.flip_vertical();
+    let flip_v = matrix.flip_horizontal();
     // let flip_hv = flip_h.flip(1);
     flips.push(matrix);
     flips.push(flip_h);
@@ -175,14 +199,14 @@ fn matrix_flips(matrix: Matrix) -> Vec<Matrix> {
     flips
 }
 
-fn matrix_transformations(matrix: Vec<Vec<Pixel>>) -> impl Iterator<Item = Matrix> {
+fn matrix_transformations(matrix: Matrix) -> impl Iterator<Item = Matrix> {
     matrix_flips(matrix).into_iter().flat_map(matrix_rotations)
 }
 
 type Position = (isize, isize);
 type Map<'a> = HashMap<Position, Tile>;
 type Edge = Vec<Pixel>;
-type Matrix = Vec<Vec<Pixel>>;
+type Matrix = Grid<Pixel>;
 
 fn tile_rotations(tile: Tile) -> Vec<Tile> {
     let mut rotations = Vec::with_capacity(4);
@@ -231,14 +255,14 @@ fn solve<'a>(
     done: &mut HashSet<usize>,
     width: usize,
     depth: usize,
-) -> bool {
+    token: &Token,
+) -> Result<bool, Cancelled> {
+    token.check()?;
     if depth == width * width {
-        println!("Found!");
-        true
+        debug!("Found!");
+        Ok(true)
     } else {
         let position = ((depth / width) as isize, (depth % width) as isize);
-        // println!("{}: Checking {}, {:?}", extra, width, depth);
-        // let tmp = map.clone();
         let ids: HashSet<_> = get_neighbour_ids(map, &position)
             .iter()
             .map(|id| &tile_to_tile[id])
@@ -254,41 +278,48 @@ fn solve<'a>(
                 }
             })
             .unwrap();
-        ids.into_iter()
+        let candidates = ids
+            .into_iter()
             .map(|id| tiles.get(&id).unwrap())
-            .flat_map(|tile| transformations(tile.clone()))
-            .any(|tile| {
-                if !done.contains(&tile.id) && is_valid_in_position(map, &position, &tile) {
-                    // Potentially dont copy map instead add and remove.
-                    let id = tile.id;
-                    done.insert(id);
-                    map.insert(position, tile);
-                    if solve(map, tile_to_tile, tiles, done, width, depth + 1) {
-                        return true;
-                    }
-                    map.remove(&position);
-                    done.remove(&id);
+            .flat_map(|tile| transformations(tile.clone()));
+        for tile in candidates {
+            if !done.contains(&tile.id) && is_valid_in_position(map, &position, &tile) {
+                // Potentially dont copy map instead add and remove.
+                let id = tile.id;
+                done.insert(id);
+                map.insert(position, tile);
+                if solve(map, tile_to_tile, tiles, done, width, depth + 1, token)? {
+                    return Ok(true);
                 }
-                false
-            })
+                map.remove(&position);
+                done.remove(&id);
+            }
+        }
+        Ok(false)
     }
 }
 
-fn parse_tiles(input_str: &str) -> impl Iterator<Item = Tile> + '_ {
-    input_str
-        .split("\n\n")
-        .map(|tile_str| tile_str.parse::<Tile>().unwrap())
+pub fn parse(input: impl BufRead) -> Result<Vec<Tile>, ParseError> {
+    let tiles: Vec<Tile> = sections(input)
+        .map(|section| Tile::parse(&section?))
+        .collect::<Result<_, _>>()?;
+    if let Some(tile) = tiles
+        .iter()
+        .find(|t| t.matrix.rows() != tiles[0].matrix.rows())
+    {
+        return Err(ParseError::new(format!(
+            "Tile {} is not the same size as tile {}",
+            tile.id, tiles[0].id
+        )));
+    }
+    Ok(tiles)
 }
 
-pub fn star_one(mut input: impl BufRead) -> usize {
-    let mut input_str = String::new();
-    input
-        .read_to_string(&mut input_str)
-        .expect("Could not read all of string");
-    let mut tiles: Vec<Tile> = parse_tiles(&input_str).flat_map(transformations).collect();
-    println!("{}", tiles.len());
+pub fn star_one(input: &[Tile]) -> usize {
+    let mut tiles: Vec<Tile> = input.iter().cloned().flat_map(transformations).collect();
+    debug!("{} transformed tiles", tiles.len());
     tiles.dedup();
-    println!("{}", tiles.len());
+    debug!("{} after removing duplicates", tiles.len());
     let mut edge_to_tile_id = HashMap::new();
     for t in &tiles {
         edge_to_tile_id
@@ -320,7 +351,7 @@ pub fn star_one(mut input: impl BufRead) -> usize {
     }
 
     for tile in &tile_tile {
-        println!("{:?}", tile);
+        trace!("{:?}", tile);
     }
 
     tile_tile
@@ -330,10 +361,10 @@ pub fn star_one(mut input: impl BufRead) -> usize {
         .product()
 }
 
-fn convert_to_matrix(map: &Map, width: usize) -> Vec<Vec<Pixel>> {
+fn convert_to_matrix(map: &Map, width: usize) -> Matrix {
     let top_left_tile = &map.get(&(0, 0)).unwrap();
-    let tile_height = top_left_tile.matrix.len();
-    let tile_width = top_left_tile.matrix[0].len();
+    let tile_height = top_left_tile.matrix.rows();
+    let tile_width = top_left_tile.matrix.columns();
     let mut vec = Vec::new();
     for i in 0..(width * tile_height) {
         if i % tile_height != 0 && i % tile_height != tile_height - 1 {
@@ -346,21 +377,23 @@ fn convert_to_matrix(map: &Map, width: usize) -> Vec<Vec<Pixel>> {
             vec.push(row);
         }
     }
-    vec
+    Grid::from_rows(vec).unwrap()
 }
 
-fn search(matrix: &[Vec<Pixel>]) -> usize {
-    let sea_monster = [
-        "                  # ".chars().collect::<Vec<char>>(),
-        "#    ##    ##    ###".chars().collect::<Vec<char>>(),
-        " #  #  #  #  #  #   ".chars().collect::<Vec<char>>(),
-    ];
+const SEA_MONSTER: [&str; 3] = [
+    "                  # ",
+    "#    ##    ##    ###",
+    " #  #  #  #  #  #   ",
+];
+
+fn search(matrix: &Matrix) -> usize {
     let mut count = 0;
-    for i in 0..(matrix.len() - sea_monster.len()) {
-        for j in 0..(matrix[i].len() - sea_monster[0].len()) {
-            if (0..sea_monster.len()).all(|y| {
-                (0..sea_monster[y].len())
-                    .all(|x| sea_monster[y][x] != '#' || matrix[i + y][j + x] == Pixel::Hash)
+    for i in 0..(matrix.rows() - SEA_MONSTER.len()) {
+        for j in 0..(matrix.columns() - SEA_MONSTER[0].len()) {
+            if SEA_MONSTER.iter().enumerate().all(|(y, row)| {
+                row.bytes()
+                    .enumerate()
+                    .all(|(x, c)| c != b'#' || matrix[(i + y, j + x)] == Pixel::Hash)
             }) {
                 count += 1;
             }
@@ -369,34 +402,16 @@ fn search(matrix: &[Vec<Pixel>]) -> usize {
     count
 }
 
-fn print_tile(tile: &Tile) {
-    for row in &tile.matrix {
-        for x in row {
-            print!("{}", x);
-        }
-        println!();
-    }
-}
-
-fn print_image(image: &[Vec<Pixel>]) {
-    for row in image {
-        for p in row {
-            match p {
-                Pixel::Hash => print!("#"),
-                Pixel::Dot => print!("."),
-            }
-        }
-        println!()
-    }
-}
-
-fn print_map(map: &Map, width: usize) {
-    for i in 0..width {
-        for j in 0..width {
-            print!("{} ", map.get(&(i as isize, j as isize)).unwrap().id);
-        }
-        println!();
-    }
+fn format_map(map: &Map, width: usize) -> String {
+    (0..width)
+        .map(|i| {
+            (0..width)
+                .map(|j| map.get(&(i as isize, j as isize)).unwrap().id.to_string())
+                .collect::<Vec<String>>()
+                .join(" ")
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
 }
 
 fn get_position(map: &Map, tile_width: usize, pos: &Position) -> Pixel {
@@ -404,35 +419,39 @@ fn get_position(map: &Map, tile_width: usize, pos: &Position) -> Pixel {
     let i = pos.0 / tile_width;
     let j = pos.1 / tile_width;
     let tile = map.get(&(i, j)).unwrap();
-    tile.matrix[(pos.0 - i * tile_width) as usize][(pos.1 - j * tile_width) as usize]
+    tile.matrix[(
+        (pos.0 - i * tile_width) as usize,
+        (pos.1 - j * tile_width) as usize,
+    )]
 }
 
-fn print_map_content(map: &Map, width: usize, tile_width: usize) {
+/// The whole map with a gap between each tile.
+fn format_map_content(map: &Map, width: usize, tile_width: usize) -> String {
+    let mut content = String::new();
     for i in 0..(width * tile_width) {
         if i % tile_width == 0 {
-            println!()
+            content.push('\n');
         }
-        println!(" ");
         for j in 0..(width * tile_width) {
             if j % tile_width == 0 {
-                print!(" ")
+                content.push(' ');
             }
-            print!(
-                "{}",
-                get_position(map, tile_width, &(i as isize, j as isize))
-            );
+            content += &get_position(map, tile_width, &(i as isize, j as isize)).to_string();
         }
+        content.push('\n');
     }
-    println!()
+    content
 }
 
-pub fn star_two(mut input: impl BufRead) -> usize {
-    let mut input_str = String::new();
-    input
-        .read_to_string(&mut input_str)
-        .expect("Could not read all of string");
+pub fn star_two(input: &[Tile]) -> usize {
+    cancel::uncancelled(|token| star_two_until(input, token))
+}
+
+pub fn star_two_until(input: &[Tile], token: &Token) -> Result<usize, Cancelled> {
     let mut number_of_tiles = 0;
-    let tiles: HashMap<usize, Tile> = parse_tiles(&input_str)
+    let tiles: HashMap<usize, Tile> = input
+        .iter()
+        .cloned()
         .map(|tile| {
             number_of_tiles += 1;
             (tile.id, tile)
@@ -477,15 +496,13 @@ pub fn star_two(mut input: impl BufRead) -> usize {
         .find(|(_id, value)| value.len() == 2)
         .unwrap();
 
-    // println!("{}", tiles.len());
-
     let width = (number_of_tiles as f64).sqrt() as usize;
-    println!("{}: {}", width, number_of_tiles);
+    debug!("{} tiles in a {}x{} square", number_of_tiles, width, width);
 
     let first_neighbours: Vec<&usize> = first_neighbours.iter().collect();
 
-    println!(
-        "{:?}",
+    debug!(
+        "Starting tiles: {:?}",
         transformations(tiles.get(first_corner).unwrap().clone())
             .filter(|tile| {
                 edge_to_tile_id[&tile.right]
@@ -509,54 +526,42 @@ pub fn star_two(mut input: impl BufRead) -> usize {
                     .any(|id| id == first_neighbours[1])
         })
         .find_map(|tile| {
-            println!("Starting tile: {:?}", tile.id);
-            print_tile(&tile);
-            println!("{:?}", tile.left);
-            println!("{:?}", tile.right);
-            println!("{:?}", edge_to_tile_id[&tile.right]);
-            let mut hs = HashSet::new();
-            hs.insert(tile.id);
-            println!(
-                "{:?}",
+            debug!("Starting tile: {:?}", tile.id);
+            trace!("{}", tile.matrix);
+            trace!("Left: {:?}", tile.left);
+            trace!("Right: {:?}", tile.right);
+            trace!(
+                "Right neighbours: {:?}",
                 edge_to_tile_id[&tile.right]
-                    .difference(&hs)
-                    .copied()
-                    .collect::<HashSet<usize>>()
+                    .iter()
+                    .filter(|&&id| id != tile.id)
+                    .collect::<Vec<&usize>>()
             );
             let mut map = HashMap::new();
             map.insert((0, 0), tile.clone());
             let mut done = HashSet::new();
             done.insert(tile.id);
-            if solve(&mut map, &tile_to_tile, &tiles, &mut done, width, 1) {
-                Some(map)
-            } else {
-                None
-            }
+            solve(&mut map, &tile_to_tile, &tiles, &mut done, width, 1, token)
+                .map(|found| found.then_some(map))
+                .transpose()
         })
-        .unwrap();
+        .unwrap()?;
     let first_corner = map.get(&(0, 0)).unwrap();
-    let tile_width = first_corner.matrix.len();
+    let tile_width = first_corner.matrix.rows();
 
-    print_tile(first_corner);
-
-    print_map(&map, width);
-
-    print_map_content(&map, width, tile_width);
+    trace!("{}", first_corner.matrix);
+    debug!("{}", format_map(&map, width));
+    trace!("{}", format_map_content(&map, width, tile_width));
     let image = convert_to_matrix(&map, width);
-    println!();
-    print_image(&image);
-    println!();
+    trace!("{}", image);
 
-    let hashes: usize = image
-        .iter()
-        .map(|row| row.iter().filter(|c| c == &&Pixel::Hash).count())
-        .sum();
+    let hashes = image.values().filter(|c| c == &&Pixel::Hash).count();
 
     let sea_monster_count = matrix_transformations(image)
         .find_map(|matrix| {
             let count = search(&matrix);
             if count > 0 {
-                print_image(&matrix);
+                trace!("{}", matrix);
                 Some(count)
             } else {
                 None
@@ -564,7 +569,133 @@ pub fn star_two(mut input: impl BufRead) -> usize {
         })
         .unwrap();
 
-    hashes - sea_monster_count * 15
+    Ok(hashes - sea_monster_count * 15)
+}
+
+fn random_pixel(rng: &mut Rng, hashes: usize) -> Pixel {
+    if rng.chance(hashes, 10) {
+        Pixel::Hash
+    } else {
+        Pixel::Dot
+    }
+}
+
+/// Generates `size` by `size` tiles, from 3 up to 12, which fit together into an
+/// image with a few sea monsters in it.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let width = size.clamp(3, 12);
+    let side = width * 8;
+    let mut image = Grid::from_fn(side, side, |_| random_pixel(rng, 2));
+    // Keep the sea monsters apart, so none of their squares are counted twice.
+    let mut taken = Grid::new(side, side, false);
+    let mut monsters = 1 + width * width / 16;
+    while monsters > 0 {
+        let i = rng.range(0..side - SEA_MONSTER.len());
+        let j = rng.range(0..side - SEA_MONSTER[0].len());
+        let area: Vec<grid::Position> = (i..i + SEA_MONSTER.len())
+            .flat_map(|row| (j..j + SEA_MONSTER[0].len()).map(move |column| (row, column)))
+            .collect();
+        if area.iter().any(|&position| taken[position]) {
+            continue;
+        }
+        for (row, column) in area {
+            taken[(row, column)] = true;
+            if SEA_MONSTER[row - i].as_bytes()[column - j] == b'#' {
+                image[(row, column)] = Pixel::Hash;
+            }
+        }
+        monsters -= 1;
+    }
+
+    // Neighbouring tiles share their borders, which are placed between the pieces
+    // of the image.
+    let mut picture = Grid::from_fn(width * 9 + 1, width * 9 + 1, |(row, column)| {
+        if row % 9 == 0 || column % 9 == 0 {
+            random_pixel(rng, 5)
+        } else {
+            image[(row - row / 9 - 1, column - column / 9 - 1)]
+        }
+    });
+    // Every border needs to be different, forwards or backwards, so that there is
+    // only one way to put the tiles together.
+    let mut borders = HashSet::new();
+    for a in 0..=width {
+        for b in 0..width {
+            for ((row, column), (down, across)) in
+                [((9 * a, 9 * b), (0, 1)), ((9 * b, 9 * a), (1, 0))]
+            {
+                let border: Vec<grid::Position> = (0..10)
+                    .map(|k| (row + k * down, column + k * across))
+                    .collect();
+                loop {
+                    let forwards: Edge = border.iter().map(|&position| picture[position]).collect();
+                    let backwards: Edge = forwards.iter().rev().copied().collect();
+                    if forwards != backwards && !borders.contains(&forwards) {
+                        borders.insert(forwards);
+                        borders.insert(backwards);
+                        break;
+                    }
+                    // Leave the corners alone, they are shared with other borders.
+                    for &position in &border[1..9] {
+                        picture[position] = random_pixel(rng, 5);
+                    }
+                }
+            }
+        }
+    }
+
+    let ids = rng.sample(1000..10000, width * width);
+    let mut tiles: Vec<String> = ids
+        .iter()
+        .enumerate()
+        .map(|(n, id)| {
+            let (i, j) = (n / width * 9, n % width * 9);
+            let mut tile = Grid::from_fn(10, 10, |(row, column)| picture[(i + row, j + column)]);
+            for _ in 0..rng.range(0..4) {
+                tile = tile.rotate();
+            }
+            if rng.chance(1, 2) {
+                tile = tile.flip_vertical();
+            }
+            format!("Tile {}:\n{}\n", id, tile)
+        })
+        .collect();
+    rng.shuffle(&mut tiles);
+    tiles.join("\n")
+}
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    type Input = Vec<Tile>;
+
+    fn name(&self) -> &'static str {
+        "Jurassic Jigsaw"
+    }
+
+    fn day(&self) -> usize {
+        20
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn part_two_until(&self, input: &Self::Input, token: &Token) -> Result<Answer, Cancelled> {
+        Ok(star_two_until(input, token)?.into())
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(12))
+    }
 }
 
 #[cfg(test)]
@@ -575,64 +706,89 @@ mod tests {
     #[test]
     fn test_star_one() {
         let input = include_bytes!("day20_test.txt");
-        assert_eq!(star_one(Cursor::new(input)), 20899048083289);
+        assert_eq!(
+            star_one(&parse(Cursor::new(input)).unwrap()),
+            20899048083289
+        );
     }
 
     #[test]
     fn test_matrix_flip() {
         {
-            let input = vec![vec![Pixel::Hash, Pixel::Dot], vec![Pixel::Dot, Pixel::Hash]];
-            let expected = vec![vec![Pixel::Dot, Pixel::Hash], vec![Pixel::Hash, Pixel::Dot]];
-            assert_eq!(matrix_flip(&input, 0), expected);
-            assert_eq!(matrix_flip(&input, 1), expected);
+            let input = Grid::from_rows(vec![
+                vec![Pixel::Hash, Pixel::Dot],
+                vec![Pixel::Dot, Pixel::Hash],
+            ])
+            .unwrap();
+            let expected = Grid::from_rows(vec![
+                vec![Pixel::Dot, Pixel::Hash],
+                vec![Pixel::Hash, Pixel::Dot],
+            ])
+            .unwrap();
+            assert_eq!(input.flip_vertical(), expected);
+            assert_eq!(input.flip_horizontal(), expected);
         }
         {
-            let input = vec![
+            let input = Grid::from_rows(vec![
                 vec![Pixel::Hash, Pixel::Dot, Pixel::Hash],
                 vec![Pixel::Dot, Pixel::Hash, Pixel::Hash],
                 vec![Pixel::Hash, Pixel::Dot, Pixel::Dot],
-            ];
-            let expected1 = vec![
+            ])
+            .unwrap();
+            let expected1 = Grid::from_rows(vec![
                 vec![Pixel::Hash, Pixel::Dot, Pixel::Dot],
                 vec![Pixel::Dot, Pixel::Hash, Pixel::Hash],
                 vec![Pixel::Hash, Pixel::Dot, Pixel::Hash],
-            ];
-            let expected2 = vec![
+            ])
+            .unwrap();
+            let expected2 = Grid::from_rows(vec![
                 vec![Pixel::Hash, Pixel::Dot, Pixel::Hash],
                 vec![Pixel::Hash, Pixel::Hash, Pixel::Dot],
                 vec![Pixel::Dot, Pixel::Dot, Pixel::Hash],
-            ];
-            assert_eq!(matrix_flip(&input, 0), expected1);
-            assert_eq!(matrix_flip(&input, 1), expected2);
+            ])
+            .unwrap();
+            assert_eq!(input.flip_vertical(), expected1);
+            assert_eq!(input.flip_horizontal(), expected2);
         }
     }
 
     #[test]
     fn test_matrix_rotate() {
         {
-            let input = vec![vec![Pixel::Hash, Pixel::Dot], vec![Pixel::Dot, Pixel::Hash]];
-            let expected = vec![vec![Pixel::Dot, Pixel::Hash], vec![Pixel::Hash, Pixel::Dot]];
-            assert_eq!(matrix_rotate(&input), expected);
+            let input = Grid::from_rows(vec![
+                vec![Pixel::Hash, Pixel::Dot],
+                vec![Pixel::Dot, Pixel::Hash],
+            ])
+            .unwrap();
+            let expected = Grid::from_rows(vec![
+                vec![Pixel::Dot, Pixel::Hash],
+                vec![Pixel::Hash, Pixel::Dot],
+            ])
+            .unwrap();
+            assert_eq!(input.rotate(), expected);
         }
         {
-            let input = vec![
+            let input = Grid::from_rows(vec![
                 vec![Pixel::Hash, Pixel::Dot, Pixel::Hash],
                 vec![Pixel::Dot, Pixel::Hash, Pixel::Hash],
                 vec![Pixel::Hash, Pixel::Dot, Pixel::Dot],
-            ];
-            let expected1 = vec![
+            ])
+            .unwrap();
+            let expected1 = Grid::from_rows(vec![
                 vec![Pixel::Hash, Pixel::Dot, Pixel::Hash],
                 vec![Pixel::Dot, Pixel::Hash, Pixel::Dot],
                 vec![Pixel::Dot, Pixel::Hash, Pixel::Hash],
-            ];
-            let expected2 = vec![
+            ])
+            .unwrap();
+            let expected2 = Grid::from_rows(vec![
                 vec![Pixel::Dot, Pixel::Dot, Pixel::Hash],
                 vec![Pixel::Hash, Pixel::Hash, Pixel::Dot],
                 vec![Pixel::Hash, Pixel::Dot, Pixel::Hash],
-            ];
-            let output = matrix_rotate(&input);
+            ])
+            .unwrap();
+            let output = input.rotate();
             assert_eq!(output, expected1);
-            assert_eq!(matrix_rotate(&output), expected2);
+            assert_eq!(output.rotate(), expected2);
         }
     }
 
@@ -656,6 +812,6 @@ mod tests {
     #[test]
     fn test_star_two() {
         let input = include_bytes!("day20_test.txt");
-        assert_eq!(star_two(Cursor::new(input)), 273);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 273);
     }
 }
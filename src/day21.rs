@@ -5,32 +5,39 @@ use std::{
     io::BufRead,
 };
 
+use crate::generate::Rng;
+use crate::input::parse_lines;
+use crate::{Answer, ParseError, Solution};
+
 lazy_static! {
     static ref RE: Regex =
-        Regex::new(r"(?P<ingredients>[\w ]+) \(contains (?P<allergens>[\w ,]+)").unwrap();
+        Regex::new(r"^(?P<ingredients>[\w ]+) \(contains (?P<allergens>[\w ,]+)\)$").unwrap();
 }
 
-pub fn star_one(input: impl BufRead) -> usize {
-    let mut a = HashSet::new();
-    let mut i = HashSet::new();
-    let data: Vec<(Vec<String>, Vec<String>)> = input
-        .lines()
-        .map_while(Result::ok)
-        .map(|line| {
-            let captures = RE.captures(&line).unwrap();
-            let allergens = captures["allergens"]
-                .split(", ")
-                .map(|s| s.to_string())
-                .collect::<Vec<_>>();
-            let ingredients = captures["ingredients"]
-                .split_whitespace()
-                .map(|s| s.to_string())
-                .collect();
-            (ingredients, allergens)
-        })
-        .collect();
+/// The ingredients of a food and the allergens it is known to contain.
+type Food = (Vec<String>, Vec<String>);
+
+pub fn parse(input: impl BufRead) -> Result<Vec<Food>, ParseError> {
+    parse_lines(input, |line| {
+        let captures = RE.captures(line).ok_or_else(|| {
+            ParseError::new("Expected a food like `abc def (contains dairy, fish)`").with_column(1)
+        })?;
+        let allergens = captures["allergens"]
+            .split(", ")
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>();
+        let ingredients = captures["ingredients"]
+            .split_whitespace()
+            .map(|s| s.to_string())
+            .collect();
+        Ok((ingredients, allergens))
+    })
+}
+
+/// Works out which ingredient contains each allergen, keyed by ingredient.
+fn get_labels(data: &[Food]) -> HashMap<&String, String> {
     let mut hm: HashMap<String, HashSet<&String>> = HashMap::new();
-    for (ingredients, allergens) in &data {
+    for (ingredients, allergens) in data {
         for allergen in allergens {
             let hs: HashSet<_> = ingredients.iter().collect();
             match hm.entry(allergen.to_string()) {
@@ -43,13 +50,7 @@ pub fn star_one(input: impl BufRead) -> usize {
                     o.insert(hs);
                 }
             }
-
-            for ingredient in ingredients {
-                i.insert(ingredient.to_string());
-            }
-            a.insert(allergen.to_string());
         }
-        // println!("{:?}", hm);
     }
     let mut possible_labels: Vec<(String, HashSet<&String>)> = hm.into_iter().collect();
     possible_labels.sort_by_key(|x| Reverse(x.1.len()));
@@ -69,9 +70,14 @@ pub fn star_one(input: impl BufRead) -> usize {
         }
         possible_labels.sort_by_key(|x| Reverse(x.1.len()));
     }
-    println!("{:?}", labels);
+    debug!("{:?}", labels);
+    labels
+}
+
+pub fn star_one(data: &[Food]) -> usize {
+    let labels = get_labels(data);
     let mut count = 0;
-    for (ingredients, _allergens) in &data {
+    for (ingredients, _allergens) in data {
         for ingredient in ingredients {
             if !labels.contains_key(ingredient) {
                 count += 1
@@ -82,87 +88,103 @@ pub fn star_one(input: impl BufRead) -> usize {
     count
 }
 
-pub fn star_two(input: impl BufRead) -> usize {
-    let mut a = HashSet::new();
-    let mut i = HashSet::new();
-    let data: Vec<(Vec<String>, Vec<String>)> = input
-        .lines()
-        .map_while(Result::ok)
-        .map(|line| {
-            let captures = RE.captures(&line).unwrap();
-            let allergens = captures["allergens"]
-                .split(", ")
-                .map(|s| s.to_string())
-                .collect::<Vec<_>>();
-            let ingredients = captures["ingredients"]
-                .split_whitespace()
-                .map(|s| s.to_string())
-                .collect();
-            (ingredients, allergens)
-        })
+pub fn star_two(data: &[Food]) -> String {
+    let labels = get_labels(data);
+
+    let mut label_list: Vec<(&&String, &String)> = labels.iter().collect();
+
+    label_list.sort_by_key(|x| x.1);
+
+    label_list
+        .into_iter()
+        .map(|x| *x.0)
+        .cloned()
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+const ALLERGENS: [&str; 9] = [
+    "dairy",
+    "eggs",
+    "fish",
+    "nuts",
+    "peanuts",
+    "sesame",
+    "shellfish",
+    "soy",
+    "wheat",
+];
+
+/// Generates foods made from `size` ingredients, eight of which contain an allergen.
+///
+/// Foods are added until each allergen is only listed with its own ingredient in
+/// common, so the allergens can be worked out one at a time.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut ingredients = HashSet::new();
+    while ingredients.len() < size.max(16) {
+        ingredients.insert(rng.word(3..8));
+    }
+    let mut ingredients: Vec<String> = ingredients.into_iter().collect();
+    // Sets have no fixed order.
+    ingredients.sort_unstable();
+    rng.shuffle(&mut ingredients);
+    let allergens: Vec<&str> = rng
+        .sample(0..ALLERGENS.len(), 8)
+        .into_iter()
+        .map(|i| ALLERGENS[i])
         .collect();
-    let mut hm: HashMap<String, HashSet<&String>> = HashMap::new();
-    for (ingredients, allergens) in &data {
-        for allergen in allergens {
-            let hs: HashSet<_> = ingredients.iter().collect();
-            match hm.entry(allergen.to_string()) {
-                Entry::Occupied(mut e) => {
-                    let v = e.get();
-                    let v = v.intersection(&hs).copied().collect();
-                    e.insert(v);
-                }
-                Entry::Vacant(o) => {
-                    o.insert(hs);
-                }
-            }
 
-            for ingredient in ingredients {
-                i.insert(ingredient.to_string());
+    let mut candidates: Vec<HashSet<usize>> = vec![(0..ingredients.len()).collect(); 8];
+    let mut foods = String::new();
+    while candidates.iter().any(|candidates| candidates.len() > 1) {
+        let count = rng.range(4..ingredients.len() / 2);
+        let mut food = rng.sample(0..ingredients.len(), count);
+        let count = rng.range(1..4);
+        let mut listed = rng.sample(0..8, count);
+        listed.sort_unstable();
+        for &allergen in &listed {
+            // The first ingredients are the ones containing the allergens.
+            if !food.contains(&allergen) {
+                food.push(allergen);
             }
-            a.insert(allergen.to_string());
+            candidates[allergen].retain(|i| food.contains(i));
         }
-        // println!("{:?}", hm);
+        rng.shuffle(&mut food);
+        let food: Vec<&str> = food.iter().map(|&i| ingredients[i].as_str()).collect();
+        let listed: Vec<&str> = listed.iter().map(|&i| allergens[i]).collect();
+        foods += &format!("{} (contains {})\n", food.join(" "), listed.join(", "));
     }
-    let mut possible_labels: Vec<(String, HashSet<&String>)> = hm.into_iter().collect();
-    possible_labels.sort_by_key(|x| Reverse(x.1.len()));
+    foods
+}
 
-    let mut labels = HashMap::new();
-    while let Some((allergen, possible_ing)) = possible_labels.pop() {
-        if possible_ing.len() != 1 {
-            panic!("Possible ingredients are not 1!");
-        }
-        let v: Vec<&String> = possible_ing.into_iter().collect();
-        labels.insert(v[0], allergen);
-        for x in &mut possible_labels {
-            x.1.remove(v[0]);
-        }
-        possible_labels.sort_by_key(|x| Reverse(x.1.len()));
+pub struct Day21;
+
+impl Solution for Day21 {
+    type Input = Vec<Food>;
+
+    fn name(&self) -> &'static str {
+        "Allergen Assessment"
     }
-    println!("{:?}", labels);
-    let mut count = 0;
-    for (ingredients, _allergens) in &data {
-        for ingredient in ingredients {
-            if !labels.contains_key(ingredient) {
-                count += 1;
-            }
-        }
+
+    fn day(&self) -> usize {
+        21
     }
 
-    let mut label_list: Vec<(&&String, &String)> = labels.iter().collect();
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
 
-    label_list.sort_by_key(|x| x.1);
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
 
-    println!(
-        "{}",
-        label_list
-            .into_iter()
-            .map(|x| *x.0)
-            .cloned()
-            .collect::<Vec<String>>()
-            .join(",")
-    );
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
 
-    count
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(100))
+    }
 }
 
 #[cfg(test)]
@@ -176,7 +198,7 @@ mod tests {
 trh fvjkl sbzzf mxmxvkd (contains dairy)
 sqjhc fvjkl (contains soy)
 sqjhc mxmxvkd sbzzf (contains fish)";
-        assert_eq!(star_one(Cursor::new(input)), 5);
+        assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 5);
     }
 
     #[test]
@@ -185,6 +207,9 @@ sqjhc mxmxvkd sbzzf (contains fish)";
 trh fvjkl sbzzf mxmxvkd (contains dairy)
 sqjhc fvjkl (contains soy)
 sqjhc mxmxvkd sbzzf (contains fish)";
-        assert_eq!(star_two(Cursor::new(input)), 5);
+        assert_eq!(
+            star_two(&parse(Cursor::new(input)).unwrap()),
+            "mxmxvkd,sqjhc,fvjkl"
+        );
     }
 }
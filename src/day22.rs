@@ -3,45 +3,73 @@ use std::{
     io::BufRead,
 };
 
-pub fn star_one(mut input: impl BufRead) -> usize {
-    let mut input_str = String::new();
-    input
-        .read_to_string(&mut input_str)
-        .expect("Could not read all of string");
-    let mut players: Vec<VecDeque<usize>> = input_str
-        .split("\n\n")
-        .map(|section| {
-            section
-                .lines()
-                .skip(1)
-                .map(|x| {
-                    x.parse::<usize>()
-                        .unwrap_or_else(|_| panic!("Could not parse card: {}", x))
+use crate::cancel::{self, Cancelled, Token};
+use crate::generate::Rng;
+use crate::input::sections;
+use crate::{Answer, ParseError, Solution};
+
+/// Parses each player's deck, top card first.
+pub fn parse(input: impl BufRead) -> Result<Vec<VecDeque<usize>>, ParseError> {
+    let mut seen = HashSet::new();
+    let decks = sections(input)
+        .enumerate()
+        .map(|(player, section)| {
+            section?
+                .expect_header(&format!("Player {}", player + 1))?
+                .map(|(number, line)| match line.parse::<usize>() {
+                    Ok(card) if seen.insert(card) => Ok(card),
+                    Ok(card) => Err(ParseError::new(format!("Card {} is dealt twice", card))
+                        .in_line(number, line)),
+                    Err(_) => Err(ParseError::new(format!("Could not parse card: {}", line))
+                        .with_column(1)
+                        .in_line(number, line)),
                 })
                 .collect()
         })
-        .collect();
+        .collect::<Result<Vec<VecDeque<usize>>, ParseError>>()?;
+    if decks.len() != 2 {
+        return Err(ParseError::new(format!(
+            "Expected decks for 2 players, found {}",
+            decks.len()
+        )));
+    }
+    Ok(decks)
+}
+
+/// Plays a round of Combat, where the higher card wins.
+fn play_round(player1: &mut VecDeque<usize>, player2: &mut VecDeque<usize>) {
+    let p1 = player1.pop_front().unwrap();
+    let p2 = player2.pop_front().unwrap();
+    match (p1, p2) {
+        (a, b) if a > b => {
+            player1.push_back(a);
+            player1.push_back(b);
+        }
+        (a, b) if a < b => {
+            player2.push_back(b);
+            player2.push_back(a);
+        }
+        _ => panic!("Cards are equal"),
+    }
+}
+
+pub fn star_one(input: &[VecDeque<usize>]) -> usize {
+    cancel::uncancelled(|token| star_one_until(input, token))
+}
+
+pub fn star_one_until(input: &[VecDeque<usize>], token: &Token) -> Result<usize, Cancelled> {
+    let mut players = input.to_vec();
     let mut player2 = players.pop().unwrap();
     let mut player1 = players.pop().unwrap();
+    let mut round = 0;
     while !player2.is_empty() && !player1.is_empty() {
-        let p1 = player1.pop_front().unwrap();
-        let p2 = player2.pop_front().unwrap();
-        match (p1, p2) {
-            (a, b) if a > b => {
-                player1.push_back(a);
-                player1.push_back(b);
-            }
-            (a, b) if a < b => {
-                player2.push_back(b);
-                player2.push_back(a);
-            }
-            _ => panic!("Cards are equal"),
-        }
+        token.check_every(round)?;
+        round += 1;
+        play_round(&mut player1, &mut player2);
     }
 
-    if !player1.is_empty() {
-        println!("Player1");
-        println!("{:?}", player1);
+    Ok(if !player1.is_empty() {
+        debug!("Player 1 wins: {:?}", player1);
         player1
             .into_iter()
             .rev()
@@ -49,14 +77,14 @@ pub fn star_one(mut input: impl BufRead) -> usize {
             .map(|(i, x)| (i + 1) * x)
             .sum()
     } else {
-        println!("{:?}", player2);
+        debug!("Player 2 wins: {:?}", player2);
         player2
             .into_iter()
             .rev()
             .enumerate()
             .map(|(i, x)| (i + 1) * x)
             .sum()
-    }
+    })
 }
 
 fn get_hash(players: &[VecDeque<usize>]) -> u64 {
@@ -70,14 +98,18 @@ fn get_hash(players: &[VecDeque<usize>]) -> u64 {
     hasher.finish()
 }
 
-fn play_star_two(mut players: Vec<VecDeque<usize>>) -> (usize, Vec<VecDeque<usize>>) {
+fn play_star_two(
+    mut players: Vec<VecDeque<usize>>,
+    token: &Token,
+) -> Result<(usize, Vec<VecDeque<usize>>), Cancelled> {
     let mut played = HashSet::new();
     loop {
+        token.check_every(played.len())?;
         let game_hash = get_hash(&players);
         if played.contains(&game_hash) || players[1].is_empty() {
-            break (1, players);
+            break Ok((1, players));
         } else if players[0].is_empty() {
-            break (2, players);
+            break Ok((2, players));
         }
 
         played.insert(game_hash);
@@ -94,7 +126,7 @@ fn play_star_two(mut players: Vec<VecDeque<usize>>) -> (usize, Vec<VecDeque<usiz
                 .map(|(i, player)| player.iter().take(p[i]).copied().collect())
                 .collect();
 
-            play_star_two(players).0
+            play_star_two(players, token)?.0
         } else if p[0] > p[1] {
             1
         } else if p[1] > p[0] {
@@ -113,33 +145,82 @@ fn play_star_two(mut players: Vec<VecDeque<usize>>) -> (usize, Vec<VecDeque<usiz
     }
 }
 
-pub fn star_two(mut input: impl BufRead) -> usize {
-    let mut input_str = String::new();
-    input
-        .read_to_string(&mut input_str)
-        .expect("Could not read all of string");
-    let players: Vec<VecDeque<usize>> = input_str
-        .split("\n\n")
-        .map(|section| {
-            section
-                .lines()
-                .skip(1)
-                .map(|x| {
-                    x.parse::<usize>()
-                        .unwrap_or_else(|_| panic!("Could not parse card: {}", x))
-                })
-                .collect()
-        })
-        .collect();
+pub fn star_two(input: &[VecDeque<usize>]) -> usize {
+    cancel::uncancelled(|token| star_two_until(input, token))
+}
 
-    let (winner, mut players) = play_star_two(players);
-    players
+pub fn star_two_until(input: &[VecDeque<usize>], token: &Token) -> Result<usize, Cancelled> {
+    let (winner, mut players) = play_star_two(input.to_vec(), token)?;
+    Ok(players
         .remove(winner - 1)
         .into_iter()
         .rev()
         .enumerate()
         .map(|(i, x)| (i + 1) * x)
-        .sum()
+        .sum())
+}
+
+/// Generates two decks of `size` cards each, which do not play a game of Combat
+/// forever.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let mut cards: Vec<usize> = (1..=size * 2).collect();
+    let (player1, player2) = loop {
+        rng.shuffle(&mut cards);
+        let mut players = [
+            VecDeque::from(cards[..size].to_vec()),
+            VecDeque::from(cards[size..].to_vec()),
+        ];
+        let mut played = HashSet::new();
+        while !players[0].is_empty() && !players[1].is_empty() && played.insert(players.clone()) {
+            let [player1, player2] = &mut players;
+            play_round(player1, player2);
+        }
+        if players[0].is_empty() || players[1].is_empty() {
+            break cards.split_at(size);
+        }
+    };
+    let deal =
+        |deck: &[usize]| -> String { deck.iter().map(|card| format!("{}\n", card)).collect() };
+    format!("Player 1:\n{}\nPlayer 2:\n{}", deal(player1), deal(player2))
+}
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    type Input = Vec<VecDeque<usize>>;
+
+    fn name(&self) -> &'static str {
+        "Crab Combat"
+    }
+
+    fn day(&self) -> usize {
+        22
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn part_one_until(&self, input: &Self::Input, token: &Token) -> Result<Answer, Cancelled> {
+        Ok(star_one_until(input, token)?.into())
+    }
+
+    fn part_two_until(&self, input: &Self::Input, token: &Token) -> Result<Answer, Cancelled> {
+        Ok(star_two_until(input, token)?.into())
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(25))
+    }
 }
 
 #[cfg(test)]
@@ -162,7 +243,7 @@ Player 2:
 4
 7
 10";
-        assert_eq!(star_one(Cursor::new(input)), 306);
+        assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 306);
     }
 
     #[test]
@@ -175,7 +256,7 @@ Player 2:
 2
 29
 14";
-        assert_eq!(star_two(Cursor::new(input)), 105);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 105);
     }
 
     #[test]
@@ -193,6 +274,19 @@ Player 2:
 4
 7
 10";
-        assert_eq!(star_two(Cursor::new(input)), 291);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 291);
+    }
+
+    #[test]
+    fn test_parse_crlf() {
+        let input = "Player 1:\r\n9\r\n2 \r\n\r\n\r\nPlayer 2:\r\n5\r\n8\r\n\r\n";
+        let decks = parse(Cursor::new(input)).unwrap();
+        assert_eq!(
+            decks,
+            vec![VecDeque::from(vec![9, 2]), VecDeque::from(vec![5, 8])]
+        );
+
+        let error = parse(Cursor::new("Player 1:\n9\n\nPlayer 3:\n5")).unwrap_err();
+        assert_eq!(error.line(), Some(4));
     }
 }
@@ -1,6 +1,13 @@
 use std::{collections::HashMap, io::BufRead};
 
 use slab::Slab;
+
+use crate::cancel::{self, Cancelled, Token};
+use crate::differential::Differential;
+use crate::generate::Rng;
+use crate::progress;
+use crate::{Answer, ParseError, Solution};
+
 // https://github.com/smol-rs/vec-arena/blob/master/examples/linked-list.rs
 /// The null index, akin to null pointers.
 ///
@@ -136,7 +143,6 @@ impl<T: Copy> List<T> {
     }
 }
 
-#[allow(dead_code)]
 fn step(v: &mut Vec<usize>, current: usize, print: bool) -> usize {
     let current_value = v[current];
     let mut d1 = v[current] - 1;
@@ -152,7 +158,7 @@ fn step(v: &mut Vec<usize>, current: usize, print: bool) -> usize {
     let min = *v.iter().min().unwrap();
 
     if print {
-        println!("pick up: {} {} {}", c1, c2, c3);
+        trace!("pick up: {} {} {}", c1, c2, c3);
     }
     // Find destination cup
     let d = loop {
@@ -165,16 +171,16 @@ fn step(v: &mut Vec<usize>, current: usize, print: bool) -> usize {
         }
     };
     if print {
-        println!("{:?} - {}", v, d);
+        trace!("{:?} - {}", v, d);
     }
     v.rotate_right(len - 3 - d - 1);
     if print {
-        println!("{:?}", v);
+        trace!("{:?}", v);
     }
     // x x x 4 5 6 7 8
     //
     if print {
-        println!("destination: {} ({})", v[d], d);
+        trace!("destination: {} ({})", v[d], d);
     }
     // Insert new cups
     v.push(c1);
@@ -207,58 +213,61 @@ fn step_linked(v: &mut List<usize>, lookup: &HashMap<usize, usize>, max: usize)
     };
 
     v.insert(*lookup.get(&d).unwrap(), c3);
-    // dbg!(&v.to_vec());
     v.insert(*lookup.get(&d).unwrap(), c2);
     v.insert(*lookup.get(&d).unwrap(), c1);
-    // dbg!(&other);
 
     v.push_back(current_value);
 
     1
 }
 
-pub fn star_one(input: impl BufRead) -> usize {
-    let v: Vec<usize> = input
-        .bytes()
-        .map(|x| (x.unwrap() - b'0') as usize)
-        .collect();
+/// Parses the cup labels in clockwise order.
+pub fn parse(mut input: impl BufRead) -> Result<Vec<usize>, ParseError> {
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    let line = line.trim_end();
+    let cups: Vec<usize> = line
+        .chars()
+        .enumerate()
+        .map(|(i, c)| match c.to_digit(10) {
+            Some(x) => Ok(x as usize),
+            None => {
+                Err(ParseError::new(format!("Cup label is not a digit: {}", c)).with_column(i + 1))
+            }
+        })
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.in_line(1, line))?;
+    if cups.len() < 5 {
+        return Err(ParseError::new("Expected at least 5 cups").in_line(1, line));
+    }
+    let mut sorted = cups.clone();
+    sorted.sort_unstable();
+    if !sorted.iter().copied().eq(1..=cups.len()) {
+        return Err(ParseError::new(format!(
+            "Expected each cup from 1 to {} exactly once",
+            cups.len()
+        ))
+        .in_line(1, line));
+    }
+    Ok(cups)
+}
+
+pub fn star_one(input: &[usize]) -> usize {
+    let v = input.to_vec();
 
     let max = *v.iter().max().unwrap();
     let (mut list, lookup) = create_inputs(v);
     for round in 0..100 {
-        println!("-- move {} --", round + 1);
-        // println!(
-        //     "cups: {}",
-        //     v.iter()
-        //         .enumerate()
-        //         .map(|(i, x)| if i == current_index {
-        //             format!("({}) ", x)
-        //         } else {
-        //             format!("{} ", x)
-        //         })
-        //         .collect::<String>()
-        // );
-        println!("{:?}", list.to_vec());
+        trace!("-- move {} --", round + 1);
+        trace!("{:?}", list.to_vec());
         step_linked(&mut list, &lookup, max);
     }
-    println!("-- final --");
-    println!("{:?}", list.to_vec());
-    // println!(
-    //     "cups:  {}",
-    //     v.iter()
-    //         .enumerate()
-    //         .map(|(i, x)| if i == current_index {
-    //             format!("({}) ", x)
-    //         } else {
-    //             format!("{} ", x)
-    //         })
-    //         .collect::<String>()
-    // );
+    debug!("-- final --");
+    debug!("{:?}", list.to_vec());
     let mut res = vec![];
     let mut current = list.arena.get(*lookup.get(&1).unwrap()).unwrap();
-    dbg!(current);
+    trace!("{:?}", current);
     while let Some(node) = list.arena.get(current.next) {
-        // dbg!(node);
         res.push(node.value);
         current = node;
         if current.next == NULL {
@@ -269,9 +278,9 @@ pub fn star_one(input: impl BufRead) -> usize {
         }
     }
 
-    println!("first");
-    dbg!(&res);
-    dbg!(&current);
+    trace!("first");
+    trace!("{:?}", &res);
+    trace!("{:?}", &current);
     let mut current = list.arena.get(list.head).unwrap();
     if current.value != 1 {
         res.push(current.value);
@@ -284,7 +293,7 @@ pub fn star_one(input: impl BufRead) -> usize {
         }
     }
 
-    println!("first");
+    trace!("first");
 
     res.iter()
         .map(|x| x.to_string())
@@ -302,11 +311,12 @@ fn create_inputs(cups: Vec<usize>) -> (List<usize>, HashMap<usize, usize>) {
     (list, lookup)
 }
 
-pub fn star_two(input: impl BufRead) -> usize {
-    let mut cups: Vec<usize> = input
-        .bytes()
-        .map(|x| (x.unwrap() - b'0') as usize)
-        .collect();
+pub fn star_two(input: &[usize]) -> usize {
+    cancel::uncancelled(|token| star_two_until(input, token))
+}
+
+pub fn star_two_until(input: &[usize], token: &Token) -> Result<usize, Cancelled> {
+    let mut cups = input.to_vec();
     let max = *cups.iter().max().unwrap() + 1;
     let upper = 1_000_000;
     cups.extend(max..=upper);
@@ -316,27 +326,12 @@ pub fn star_two(input: impl BufRead) -> usize {
     let (mut list, lookup) = create_inputs(cups);
 
     let number_of_rounds = 10_000_000;
-
-    let print_every = 10_000;
     for round in 0..number_of_rounds {
+        token.check_every(round)?;
         step_linked(&mut list, &lookup, 1_000_000);
-        if round % print_every == 0 {
-            println!("{}", round as f64 * 100f64 / number_of_rounds as f64);
-        }
+        progress::report(round + 1, Some(number_of_rounds));
     }
-    // println!("-- final --");
-    // println!(
-    //     "cups:  {}",
-    //     cups.iter()
-    //         .enumerate()
-    //         .map(|(i, x)| if i == current_index {
-    //             format!("({}) ", x)
-    //         } else {
-    //             format!("{} ", x)
-    //         })
-    //         .collect::<String>()
-    // );
-    println!("Done, now to find 1");
+    info!("Done, now to find 1");
     while let Some(x) = list.pop_front() {
         if x == 1 {
             break;
@@ -344,7 +339,91 @@ pub fn star_two(input: impl BufRead) -> usize {
     }
     let cup1 = list.pop_front().unwrap();
     let cup2 = list.pop_front().unwrap();
-    cup1 * cup2
+    Ok(cup1 * cup2)
+}
+
+/// Generates a circle of `size` cups, from 5 up to 9.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let cups: String = rng
+        .sample(1..size.clamp(5, 9) + 1, size.clamp(5, 9))
+        .iter()
+        .map(|cup| cup.to_string())
+        .collect();
+    format!("{}\n", cups)
+}
+
+/// How many cups the differential test plays with, filling in the labels above the
+/// input's like part two does.
+const DIFFERENTIAL_CUPS: usize = 20;
+
+pub(crate) fn differentials() -> Vec<Differential> {
+    fn cups(input: &str) -> Result<Vec<usize>, ParseError> {
+        let mut cups = parse(input.as_bytes())?;
+        cups.extend(cups.len() + 1..=DIFFERENTIAL_CUPS);
+        Ok(cups)
+    }
+
+    // Both record the circle starting from the current cup after each move.
+    vec![Differential {
+        day: 23,
+        name: "moves",
+        size: 9,
+        reference: |input| {
+            let mut cups = cups(input)?;
+            let mut current = 0;
+            Ok((0..100)
+                .map(|_| {
+                    current = (step(&mut cups, current, false) + 1) % cups.len();
+                    let mut circle = cups.clone();
+                    circle.rotate_left(current);
+                    format!("{:?}", circle)
+                })
+                .collect())
+        },
+        fast: |input| {
+            let (mut list, lookup) = create_inputs(cups(input)?);
+            Ok((0..100)
+                .map(|_| {
+                    step_linked(&mut list, &lookup, DIFFERENTIAL_CUPS);
+                    format!("{:?}", list.to_vec())
+                })
+                .collect())
+        },
+    }]
+}
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    type Input = Vec<usize>;
+
+    fn name(&self) -> &'static str {
+        "Crab Cups"
+    }
+
+    fn day(&self) -> usize {
+        23
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn part_two_until(&self, input: &Self::Input, token: &Token) -> Result<Answer, Cancelled> {
+        Ok(star_two_until(input, token)?.into())
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(9))
+    }
 }
 
 #[cfg(test)]
@@ -405,7 +484,7 @@ mod tests {
     #[test]
     fn test_star_one() {
         let input = b"389125467";
-        assert_eq!(star_one(Cursor::new(input)), 67384529);
+        assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 67384529);
     }
 
     #[test]
@@ -438,6 +517,6 @@ mod tests {
     #[test]
     fn test_star_two() {
         let input = b"389125467";
-        assert_eq!(star_two(Cursor::new(input)), 149245887792);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 149245887792);
     }
 }
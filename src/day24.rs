@@ -1,6 +1,13 @@
 use std::{collections::HashMap, io::BufRead};
 
-enum Direction {
+use crate::generate::Rng;
+use crate::grid::{self, Grid};
+use crate::input::parse_lines;
+use crate::progress;
+use crate::{Answer, ParseError, Solution};
+
+#[derive(Debug)]
+pub enum Direction {
     // e, se, sw, w, nw, and ne
     East,
     SouthEast,
@@ -10,13 +17,13 @@ enum Direction {
     NorthEast,
 }
 
-fn parse_line(line: String) -> Vec<Direction> {
-    let mut chars: Vec<char> = line.chars().rev().collect();
+fn parse_line(line: &str) -> Result<Vec<Direction>, ParseError> {
+    let mut chars = line.chars().enumerate();
     let mut directions = Vec::new();
 
-    while let Some(current) = chars.pop() {
+    while let Some((i, current)) = chars.next() {
         let current2 = if current == 's' || current == 'n' {
-            chars.pop()
+            chars.next().map(|(_, c)| c)
         } else {
             None
         };
@@ -27,29 +34,25 @@ fn parse_line(line: String) -> Vec<Direction> {
             ('s', Some('w')) => Direction::SouthWest,
             ('n', Some('e')) => Direction::NorthEast,
             ('n', Some('w')) => Direction::NorthWest,
-            _ => panic!(),
+            _ => {
+                return Err(
+                    ParseError::new("Expected one of e, se, sw, w, nw or ne").with_column(i + 1)
+                )
+            }
         };
         directions.push(dir);
     }
-    directions
+    Ok(directions)
 }
 
 type Position = (isize, isize);
 
-fn adjacent_positions(current: &Position) -> Vec<Position> {
-    vec![
-        (current.0, current.1 + 1),
-        (current.0 - 1, current.1),
-        (current.0 - 1, current.1 - 1),
-        (current.0, current.1 - 1),
-        (current.0 + 1, current.1),
-        (current.0 + 1, current.1 + 1),
-    ]
-}
+/// The steps to each of the six tiles touching a tile.
+const ADJACENT: [grid::Direction; 6] = [(0, 1), (-1, 0), (-1, -1), (0, -1), (1, 0), (1, 1)];
 
-fn follow(mut directions: Vec<Direction>) -> Position {
+fn follow(directions: &[Direction]) -> Position {
     let mut current = (0, 0);
-    while let Some(dir) = directions.pop() {
+    for dir in directions {
         current = match dir {
             Direction::East => (current.0, current.1 + 1),
             Direction::SouthEast => (current.0 - 1, current.1),
@@ -62,7 +65,7 @@ fn follow(mut directions: Vec<Direction>) -> Position {
     current
 }
 
-#[derive(PartialEq, Clone)]
+#[derive(PartialEq, Clone, Copy)]
 enum Colour {
     White,
     Black,
@@ -77,70 +80,119 @@ impl Colour {
     }
 }
 
-fn step(hm: HashMap<Position, Colour>) -> HashMap<Position, Colour> {
-    let mut tile_count = HashMap::new();
-    for (position, colour) in &hm {
-        if colour == &Colour::Black {
-            if !tile_count.contains_key(position) {
-                tile_count.insert(*position, 0);
-            }
-            for pos in adjacent_positions(position) {
-                *tile_count.entry(pos).or_insert(0) += 1;
-            }
-        }
-    }
-    let mut new_tiles = HashMap::new();
-    for (position, count) in tile_count {
-        match (hm.get(&position).unwrap_or(&Colour::White), count) {
-            (Colour::Black, 1..=2) => {
-                new_tiles.insert(position, Colour::Black);
-            }
-            (Colour::White, 2) => {
-                new_tiles.insert(position, Colour::Black);
-            }
-            (Colour::Black, _) => {}
-            (Colour::White, _) => {}
-        };
-    }
-    new_tiles
-}
-
-pub fn star_one(input: impl BufRead) -> usize {
-    let tiles: Vec<Vec<Direction>> = input
-        .lines()
-        .map_while(Result::ok)
-        .map(parse_line)
-        .collect();
+fn flip_tiles(tiles: &[Vec<Direction>]) -> HashMap<Position, Colour> {
     let mut hm = HashMap::new();
     for directions in tiles {
         let position = follow(directions);
         hm.entry(position).or_insert(Colour::White).flip();
     }
+    hm
+}
+
+/// Lays out the smallest grid that holds every black tile.
+fn to_grid(hm: &HashMap<Position, Colour>) -> Grid<Colour> {
+    let black: Vec<&Position> = hm
+        .iter()
+        .filter(|(_, colour)| colour == &&Colour::Black)
+        .map(|(position, _)| position)
+        .collect();
+    if black.is_empty() {
+        return Grid::new(0, 0, Colour::White);
+    }
+    let min_row = black.iter().map(|p| p.0).min().unwrap();
+    let max_row = black.iter().map(|p| p.0).max().unwrap();
+    let min_column = black.iter().map(|p| p.1).min().unwrap();
+    let max_column = black.iter().map(|p| p.1).max().unwrap();
+    Grid::from_fn(
+        (max_row - min_row + 1) as usize,
+        (max_column - min_column + 1) as usize,
+        |(row, column)| {
+            let position = (row as isize + min_row, column as isize + min_column);
+            hm.get(&position).copied().unwrap_or(Colour::White)
+        },
+    )
+}
 
-    hm.values()
+fn step(floor: &Grid<Colour>) -> Grid<Colour> {
+    // Black tiles can spread by one tile in each direction.
+    let floor = floor.pad(1, Colour::White);
+    Grid::from_fn(floor.rows(), floor.columns(), |position| {
+        let count = floor
+            .neighbours(position, &ADJACENT)
+            .filter(|&p| floor[p] == Colour::Black)
+            .count();
+        match (floor[position], count) {
+            (Colour::Black, 1..=2) | (Colour::White, 2) => Colour::Black,
+            _ => Colour::White,
+        }
+    })
+}
+
+pub fn parse(input: impl BufRead) -> Result<Vec<Vec<Direction>>, ParseError> {
+    parse_lines(input, parse_line)
+}
+
+pub fn star_one(tiles: &[Vec<Direction>]) -> usize {
+    flip_tiles(tiles)
+        .values()
         .filter(|&colour| colour == &Colour::Black)
         .count()
 }
 
-pub fn star_two(input: impl BufRead) -> usize {
-    let tiles: Vec<Vec<Direction>> = input
-        .lines()
-        .map_while(Result::ok)
-        .map(parse_line)
-        .collect();
-    let mut hm = HashMap::new();
-    for directions in tiles {
-        let position = follow(directions);
-        hm.entry(position).or_insert(Colour::White).flip();
+pub fn star_two(tiles: &[Vec<Direction>]) -> usize {
+    let mut floor = to_grid(&flip_tiles(tiles));
+    for day in 1..=100 {
+        floor = step(&floor);
+        progress::report(day, Some(100));
     }
-    for _i in 0..100 {
-        hm = step(hm);
-    }
-    hm.values()
+    floor
+        .values()
         .filter(|&colour| colour == &Colour::Black)
         .count()
 }
 
+/// Generates directions to `size` tiles.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let steps: String = (0..rng.range(10..25))
+                .map(|_| *rng.choose(&["e", "se", "sw", "w", "nw", "ne"]))
+                .collect();
+            steps + "\n"
+        })
+        .collect()
+}
+
+pub struct Day24;
+
+impl Solution for Day24 {
+    type Input = Vec<Vec<Direction>>;
+
+    fn name(&self) -> &'static str {
+        "Lobby Layout"
+    }
+
+    fn day(&self) -> usize {
+        24
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(500))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -148,8 +200,15 @@ mod tests {
 
     #[test]
     fn test_follow() {
-        let input = parse_line("nwwswee".to_string());
-        assert_eq!(follow(input), (0, 0));
+        let input = parse_line("nwwswee").unwrap();
+        assert_eq!(follow(&input), (0, 0));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = parse(Cursor::new("esew\nnwwsnx")).unwrap_err();
+        assert_eq!(error.line(), Some(2));
+        assert_eq!(error.column(), Some(4));
     }
 
     #[test]
@@ -174,7 +233,7 @@ nenewswnwewswnenesenwnesewesw
 eneswnwswnwsenenwnwnwwseeswneewsenese
 neswnwewnwnwseenwseesewsenwsweewe
 wseweeenwnesenwwwswnew";
-        assert_eq!(star_one(Cursor::new(input)), 10);
+        assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 10);
     }
 
     #[test]
@@ -199,6 +258,6 @@ nenewswnwewswnenesenwnesewesw
 eneswnwswnwsenenwnwnwwseeswneewsenese
 neswnwewnwnwseenwseesewsenwsweewe
 wseweeenwnesenwwwswnew";
-        assert_eq!(star_two(Cursor::new(input)), 2208);
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 2208);
     }
 }
@@ -1,15 +1,21 @@
 use std::io::BufRead;
 
-fn get_loop_size(pkey: usize, subject_number: usize) -> usize {
+use crate::cancel::{self, Cancelled, Token};
+use crate::generate::Rng;
+use crate::input::parse_lines;
+use crate::{Answer, ParseError, Solution};
+
+fn get_loop_size(pkey: usize, subject_number: usize, token: &Token) -> Result<usize, Cancelled> {
     let mut value = 1;
     let mut i = 0;
     while value != pkey {
+        token.check_every(i)?;
         value *= subject_number;
         value %= 20201227;
 
         i += 1;
     }
-    i
+    Ok(i)
 }
 
 fn create_encryption_key(subject_number: usize, loops: usize) -> usize {
@@ -21,18 +27,74 @@ fn create_encryption_key(subject_number: usize, loops: usize) -> usize {
     value
 }
 
-pub fn star_one(input: impl BufRead) -> usize {
-    let pkeys: Vec<usize> = input
-        .lines()
-        .map_while(Result::ok)
-        .map(|x| x.parse::<usize>().unwrap())
-        .collect();
-    let loops: Vec<usize> = pkeys.iter().map(|pkey| get_loop_size(*pkey, 7)).collect();
-    create_encryption_key(pkeys[1], loops[0])
+/// Parses the card and door public keys.
+pub fn parse(input: impl BufRead) -> Result<Vec<usize>, ParseError> {
+    let pkeys = parse_lines(input, |line| Ok(line.parse::<usize>()?))?;
+    if pkeys.len() != 2 {
+        return Err(ParseError::new(format!(
+            "Expected the card and door public keys but found {} keys",
+            pkeys.len()
+        )));
+    }
+    Ok(pkeys)
+}
+
+pub fn star_one(pkeys: &[usize]) -> usize {
+    cancel::uncancelled(|token| star_one_until(pkeys, token))
+}
+
+pub fn star_one_until(pkeys: &[usize], token: &Token) -> Result<usize, Cancelled> {
+    let loops: Vec<usize> = pkeys
+        .iter()
+        .map(|pkey| get_loop_size(*pkey, 7, token))
+        .collect::<Result<_, _>>()?;
+    Ok(create_encryption_key(pkeys[1], loops[0]))
+}
+
+/// Day 25 only has one puzzle, the second star is awarded for completing every other day.
+pub fn star_two(_input: &[usize]) -> &'static str {
+    "Merry Christmas!"
+}
+
+/// Generates public keys for a card and door whose loop sizes are below `size`.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let card = create_encryption_key(7, rng.range(1..size.max(2)));
+    let door = create_encryption_key(7, rng.range(1..size.max(2)));
+    format!("{}\n{}\n", card, door)
 }
 
-pub fn star_two(_input: impl BufRead) -> usize {
-    1
+pub struct Day25;
+
+impl Solution for Day25 {
+    type Input = Vec<usize>;
+
+    fn name(&self) -> &'static str {
+        "Combo Breaker"
+    }
+
+    fn day(&self) -> usize {
+        25
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Answer {
+        star_one(input).into()
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Answer {
+        star_two(input).into()
+    }
+
+    fn part_one_until(&self, input: &Self::Input, token: &Token) -> Result<Answer, Cancelled> {
+        Ok(star_one_until(input, token)?.into())
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        generate(rng, size.unwrap_or(10_000_000))
+    }
 }
 
 #[cfg(test)]
@@ -42,20 +104,31 @@ mod tests {
 
     #[test]
     fn test_loop_size() {
-        assert_eq!(get_loop_size(5764801, 7), 8);
-        assert_eq!(get_loop_size(17807724, 7), 11);
+        assert_eq!(get_loop_size(5764801, 7, &Token::new()), Ok(8));
+        assert_eq!(get_loop_size(17807724, 7, &Token::new()), Ok(11));
+    }
+
+    #[test]
+    fn test_parse_requires_two_keys() {
+        let error = parse(Cursor::new(b"5764801")).unwrap_err();
+        assert_eq!(error.line(), None);
+        let error = parse(Cursor::new(
+            b"5764801
+1780x724",
+        ))
+        .unwrap_err();
+        assert_eq!(error.line(), Some(2));
     }
 
     #[test]
     fn test_star_one() {
         let input = b"5764801
 17807724";
-        assert_eq!(star_one(Cursor::new(input)), 14897079);
+        assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 14897079);
     }
 
     #[test]
     fn test_star_two() {
-        let input = b"";
-        assert_eq!(star_two(Cursor::new(input)), 1);
+        assert_eq!(star_two(&[]), "Merry Christmas!");
     }
 }
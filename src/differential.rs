@@ -0,0 +1,158 @@
+//! Differential tests, which check the fast way a day is solved against a slower
+//! reference implementation on [generated](crate::generate) inputs.
+//!
+//! Both implementations record their state after every step, and the first step at
+//! which they disagree is reported along with the seed of the input, so that the
+//! case can be recreated with `main generate <day> --seed <seed> --size <size>`.
+//!
+//! The tests run [`DEFAULT_CASES`] seeds for each comparison. Set `DIFFERENTIAL_CASES`
+//! to run more or fewer, and `DIFFERENTIAL_SEED` to start from a seed other than 0.
+
+use std::{
+    env,
+    fmt::{self, Display},
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::{day17, day23, generate, pool, registry, ParseError};
+
+/// How many seeds the tests try when `DIFFERENTIAL_CASES` is not set.
+pub const DEFAULT_CASES: u64 = 10;
+
+/// The states after each step of one implementation, in a form that can be compared.
+pub type Steps = fn(&str) -> Result<Vec<String>, ParseError>;
+
+/// Two implementations of the same part of a day.
+pub struct Differential {
+    pub day: usize,
+    /// What is being compared, for reports.
+    pub name: &'static str,
+    /// The size of input to generate, small enough for the reference to be quick.
+    pub size: usize,
+    pub reference: Steps,
+    pub fast: Steps,
+}
+
+/// Every comparison there is.
+pub fn all() -> Vec<Differential> {
+    day17::differentials()
+        .into_iter()
+        .chain(day23::differentials())
+        .collect()
+}
+
+/// The first step at which two implementations disagreed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub day: usize,
+    pub name: &'static str,
+    pub seed: u64,
+    pub size: usize,
+    /// How many steps matched before this one.
+    pub step: usize,
+    pub reference: String,
+    pub fast: String,
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "day {:02} {} diverged at step {} for `main generate {} --seed {} --size {}`",
+            self.day, self.name, self.step, self.day, self.seed, self.size
+        )?;
+        writeln!(f, "reference: {}", self.reference)?;
+        write!(f, "fast:      {}", self.fast)
+    }
+}
+
+/// Runs `steps`, turning any error or panic into the last step.
+fn run(steps: Steps, input: &str) -> Vec<String> {
+    match panic::catch_unwind(AssertUnwindSafe(|| steps(input))) {
+        Ok(Ok(steps)) => steps,
+        Ok(Err(e)) => vec![format!("could not parse input: {}", e)],
+        Err(payload) => vec![format!(
+            "panicked: {}",
+            pool::panic_message(payload.as_ref())
+        )],
+    }
+}
+
+/// Compares both implementations on the input generated from `seed`.
+pub fn check(differential: &Differential, seed: u64) -> Result<(), Divergence> {
+    let puzzle = registry::get(differential.day)
+        .expect("Differentials are only defined for days that exist");
+    let input = generate::generate(puzzle, seed, Some(differential.size));
+    let reference = run(differential.reference, &input);
+    let fast = run(differential.fast, &input);
+    let missing = || "<no more steps>".to_string();
+    match (0..reference.len().max(fast.len())).find(|&i| reference.get(i) != fast.get(i)) {
+        None => Ok(()),
+        Some(step) => Err(Divergence {
+            day: differential.day,
+            name: differential.name,
+            seed,
+            size: differential.size,
+            step,
+            reference: reference.get(step).cloned().unwrap_or_else(missing),
+            fast: fast.get(step).cloned().unwrap_or_else(missing),
+        }),
+    }
+}
+
+/// The seeds to test, taken from `DIFFERENTIAL_SEED` and `DIFFERENTIAL_CASES`.
+pub fn seeds() -> Result<std::ops::Range<u64>, String> {
+    let var = |name: &str, default: u64| match env::var(name) {
+        Ok(value) => value
+            .parse::<u64>()
+            .map_err(|_| format!("Expected a number in {}, found {:?}", name, value)),
+        Err(_) => Ok(default),
+    };
+    let start = var("DIFFERENTIAL_SEED", 0)?;
+    Ok(start..start + var("DIFFERENTIAL_CASES", DEFAULT_CASES)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_differentials() {
+        let seeds = seeds().unwrap();
+        for differential in all() {
+            for seed in seeds.clone() {
+                if let Err(divergence) = check(&differential, seed) {
+                    panic!("{}", divergence);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_divergence() {
+        let differential = Differential {
+            day: 23,
+            name: "broken",
+            size: 9,
+            reference: |_| Ok(vec!["1".to_string(), "2".to_string()]),
+            fast: |_| Ok(vec!["1".to_string(), "3".to_string()]),
+        };
+        let divergence = check(&differential, 4).unwrap_err();
+        assert_eq!(divergence.step, 1);
+        assert_eq!(
+            (divergence.reference.as_str(), divergence.fast.as_str()),
+            ("2", "3")
+        );
+        assert!(divergence
+            .to_string()
+            .contains("`main generate 23 --seed 4 --size 9`"));
+
+        let differential = Differential {
+            fast: |_| panic!("Oops"),
+            ..differential
+        };
+        let divergence = check(&differential, 4).unwrap_err();
+        assert_eq!(divergence.step, 0);
+        assert_eq!(divergence.fast, "panicked: Oops");
+    }
+}
@@ -0,0 +1,183 @@
+use std::{fmt::Display, io, num::ParseIntError};
+
+/// Returned when a puzzle input cannot be parsed.
+///
+/// Parsers fill in as much of the location as they know; the line is usually added by
+/// [`crate::input::parse_lines`] and the day by the [registry](crate::registry) wrapper.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    day: Option<usize>,
+    /// 1-based line number within the input.
+    line: Option<usize>,
+    /// 1-based column, counted in characters.
+    column: Option<usize>,
+    /// The line that could not be parsed.
+    text: Option<String>,
+    message: String,
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>) -> Self {
+        ParseError {
+            day: None,
+            line: None,
+            column: None,
+            text: None,
+            message: message.into(),
+        }
+    }
+
+    /// An error about `token`, which should be a slice of `line`.
+    pub fn at_token(line: &str, token: &str, message: impl Into<String>) -> Self {
+        let offset = (token.as_ptr() as usize)
+            .checked_sub(line.as_ptr() as usize)
+            .filter(|offset| offset + token.len() <= line.len())
+            .or_else(|| line.find(token))
+            .unwrap_or(0);
+        ParseError::new(message).with_column(line[..offset].chars().count() + 1)
+    }
+
+    pub fn with_day(mut self, day: usize) -> Self {
+        self.day = Some(day);
+        self
+    }
+
+    pub fn with_column(mut self, column: usize) -> Self {
+        self.column = Some(column);
+        self
+    }
+
+    /// Records the line the error occurred on, unless a more specific one is known.
+    pub fn in_line(mut self, line: usize, text: &str) -> Self {
+        if self.line.is_none() {
+            self.line = Some(line);
+            self.text = Some(text.to_string());
+        }
+        self
+    }
+
+    pub fn day(&self) -> Option<usize> {
+        self.day
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    pub fn column(&self) -> Option<usize> {
+        self.column
+    }
+
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Formats the error as a multi-line diagnostic pointing at the offending text in
+    /// `source`.
+    pub fn report(&self, source: &str) -> String {
+        let mut report = match self.day {
+            Some(day) => format!("error: day {:02}: {}\n", day, self.message),
+            None => format!("error: {}\n", self.message),
+        };
+        match (self.line, &self.text) {
+            (Some(line), Some(text)) => {
+                let column = self.column.unwrap_or(1);
+                let gutter = " ".repeat(line.to_string().len());
+                report += &format!("{}--> {}:{}:{}\n", gutter, source, line, column);
+                report += &format!("{} |\n", gutter);
+                report += &format!("{} | {}\n", line, text);
+                report += &format!("{} | {}^\n", gutter, " ".repeat(column.saturating_sub(1)));
+            }
+            _ => report += &format!(" --> {}\n", source),
+        }
+        report
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(day) = self.day {
+            write!(f, "day {:02}, ", day)?;
+        }
+        if let Some(line) = self.line {
+            write!(f, "line {}, ", line)?;
+        }
+        if let Some(column) = self.column {
+            write!(f, "column {}, ", column)?;
+        }
+        f.write_str(&self.message)?;
+        if let Some(text) = &self.text {
+            write!(f, ": {:?}", text)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<io::Error> for ParseError {
+    fn from(e: io::Error) -> Self {
+        ParseError::new(format!("Could not read input: {}", e))
+    }
+}
+
+impl From<ParseIntError> for ParseError {
+    fn from(e: ParseIntError) -> Self {
+        ParseError::new(format!("Could not parse number: {}", e))
+    }
+}
+
+impl From<String> for ParseError {
+    fn from(message: String) -> Self {
+        ParseError::new(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_at_token() {
+        let line = "jmp +x";
+        let error = ParseError::at_token(line, &line[4..], "Bad offset").in_line(3, line);
+        assert_eq!(error.line(), Some(3));
+        assert_eq!(error.column(), Some(5));
+        assert_eq!(error.text(), Some("jmp +x"));
+        assert_eq!(
+            error.with_day(8).to_string(),
+            "day 08, line 3, column 5, Bad offset: \"jmp +x\""
+        );
+    }
+
+    #[test]
+    fn test_in_line_keeps_first_location() {
+        let error = ParseError::new("Bad").in_line(2, "a").in_line(7, "b");
+        assert_eq!(error.line(), Some(2));
+        assert_eq!(error.text(), Some("a"));
+    }
+
+    #[test]
+    fn test_report() {
+        let error = ParseError::new("Unknown operation")
+            .with_column(1)
+            .in_line(12, "foo +1")
+            .with_day(8);
+        assert_eq!(
+            error.report("data/day08.txt"),
+            "error: day 08: Unknown operation
+  --> data/day08.txt:12:1
+   |
+12 | foo +1
+   | ^
+"
+        );
+        // Column 0 is out of range, but still points at the start of the line.
+        let error = ParseError::new("Bad").with_column(0).in_line(1, "x");
+        assert!(error.report("input").ends_with("1 | x\n  | ^\n"));
+    }
+}
@@ -0,0 +1,194 @@
+//! Downloading puzzle inputs, used by `main fetch`.
+//!
+//! Inputs are kept in a cache directory as `<cache>/<year>/dayNN.txt` and are only
+//! ever downloaded once, so a day that has been fetched before can be fetched again
+//! without a network or a session token.
+//!
+//! The session token is the `session` cookie from a logged in browser. It is read
+//! from `AOC_SESSION`, or failing that from [`session_path`]. The site can be swapped
+//! for another, such as a local stub, with `--base-url` or `AOC_BASE_URL`.
+//!
+//! The real site is only served over https, which needs `curl` to be installed; see
+//! [`http`].
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::http;
+
+/// Where inputs are downloaded from unless told otherwise.
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+/// Where downloaded inputs are kept unless told otherwise.
+pub const DEFAULT_CACHE: &str = ".cache/inputs";
+
+/// The file the session token is read from when `AOC_SESSION` is not set.
+pub fn session_path() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| Path::new(&home).join(".config/advent-of-code/session"))
+}
+
+/// Reads the session token from `AOC_SESSION` or the file at [`session_path`].
+pub fn session() -> Option<String> {
+    let token = env::var("AOC_SESSION")
+        .ok()
+        .or_else(|| fs::read_to_string(session_path()?).ok())?;
+    let token = token.trim();
+    (!token.is_empty()).then(|| token.to_string())
+}
+
+/// Where an input came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fetched {
+    Cached(PathBuf),
+    Downloaded(PathBuf),
+}
+
+impl Fetched {
+    pub fn path(&self) -> &Path {
+        match self {
+            Fetched::Cached(path) | Fetched::Downloaded(path) => path,
+        }
+    }
+}
+
+/// Downloads inputs into a cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fetcher {
+    pub base_url: String,
+    pub cache: PathBuf,
+    /// Only needed for inputs which are not cached yet.
+    pub session: Option<String>,
+}
+
+impl Fetcher {
+    /// A fetcher using the given settings, or else the ones from the environment, or
+    /// else the defaults.
+    pub fn new(base_url: Option<String>, cache: Option<PathBuf>) -> Self {
+        Fetcher {
+            base_url: base_url
+                .or_else(|| env::var("AOC_BASE_URL").ok())
+                .unwrap_or_else(|| DEFAULT_BASE_URL.to_string()),
+            cache: cache.unwrap_or_else(|| PathBuf::from(DEFAULT_CACHE)),
+            session: session(),
+        }
+    }
+
+    /// Where the input for `day` of `year` is cached.
+    pub fn cache_path(&self, year: usize, day: usize) -> PathBuf {
+        self.cache
+            .join(year.to_string())
+            .join(format!("day{:02}.txt", day))
+    }
+
+    /// The input for `day` of `year`, downloading it if it is not cached yet.
+    pub fn fetch(&self, year: usize, day: usize) -> Result<Fetched, String> {
+        let path = self.cache_path(year, day);
+        if path.exists() {
+            return Ok(Fetched::Cached(path));
+        }
+        let session = self.session.as_deref().ok_or_else(|| {
+            format!(
+                "Day {} of {} is not cached and there is no session token, set AOC_SESSION{}",
+                day,
+                year,
+                session_path()
+                    .map(|path| format!(" or write it to {}", path.display()))
+                    .unwrap_or_default()
+            )
+        })?;
+        let url = format!(
+            "{}/{}/day/{}/input",
+            self.base_url.trim_end_matches('/'),
+            year,
+            day
+        );
+        let response = http::get(&url, session)?;
+        match response.status {
+            200 => {}
+            404 => return Err(format!("Day {} of {} is not available yet", day, year)),
+            400 | 401 | 403 | 500 => {
+                return Err(format!(
+                    "The session token was not accepted for {} (status {})",
+                    url, response.status
+                ))
+            }
+            status => {
+                return Err(format!(
+                    "Fetching {} gave status {}: {}",
+                    url,
+                    status,
+                    response.body.trim()
+                ))
+            }
+        }
+
+        // Write to the side first, so a failed write is never mistaken for an input.
+        let parent = path.parent().unwrap();
+        let partial = path.with_extension("part");
+        fs::create_dir_all(parent)
+            .and_then(|_| fs::write(&partial, &response.body))
+            .and_then(|_| fs::rename(&partial, &path))
+            .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+        Ok(Fetched::Downloaded(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fetcher(base_url: &str, name: &str) -> Fetcher {
+        let cache = env::temp_dir().join(format!("fetch-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&cache);
+        Fetcher {
+            base_url: base_url.to_string(),
+            cache,
+            session: Some("abc".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_fetch() {
+        let (url, server) = http::stub(vec![(200, "1\n2\n3\n")]);
+        let fetcher = fetcher(&url, "cache");
+        let path = fetcher.cache_path(2020, 1);
+        assert!(path.ends_with("2020/day01.txt"));
+        assert_eq!(
+            fetcher.fetch(2020, 1),
+            Ok(Fetched::Downloaded(path.clone()))
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1\n2\n3\n");
+        // The stub only answers once, so this has to come from the cache.
+        let offline = Fetcher {
+            session: None,
+            ..fetcher.clone()
+        };
+        assert_eq!(offline.fetch(2020, 1), Ok(Fetched::Cached(path)));
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("GET /2020/day/1/input HTTP/1.1\r\n"));
+        assert!(requests[0].contains("Cookie: session=abc\r\n"));
+        assert!(offline
+            .fetch(2020, 2)
+            .unwrap_err()
+            .contains("no session token"));
+        fs::remove_dir_all(&fetcher.cache).unwrap();
+    }
+
+    #[test]
+    fn test_fetch_errors() {
+        let (url, server) = http::stub(vec![(404, "Not found"), (400, "Please log in")]);
+        let fetcher = fetcher(&url, "errors");
+        assert_eq!(
+            fetcher.fetch(2020, 25),
+            Err("Day 25 of 2020 is not available yet".to_string())
+        );
+        assert!(fetcher
+            .fetch(2020, 25)
+            .unwrap_err()
+            .contains("not accepted"));
+        assert!(!fetcher.cache_path(2020, 25).exists());
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,135 @@
+//! Random puzzle inputs, used by `main generate`.
+//!
+//! Each day knows how to [generate](crate::Solution::generate) inputs of its own. The
+//! inputs only depend on the seed and size they were generated from, so a case can be
+//! shared by passing those around instead of the input itself.
+
+use std::ops::Range;
+
+use crate::Puzzle;
+
+/// A small random number generator (SplitMix64).
+///
+/// Generated inputs must be the same on every platform and every release, which is
+/// easier to promise without relying on an outside crate.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `range`, which must not be empty.
+    pub fn range(&mut self, range: Range<usize>) -> usize {
+        assert!(!range.is_empty(), "Cannot pick from an empty range");
+        let width = (range.end - range.start) as u128;
+        range.start + ((self.next_u64() as u128 * width) >> 64) as usize
+    }
+
+    /// True `numerator` times out of `denominator`.
+    pub fn chance(&mut self, numerator: usize, denominator: usize) -> bool {
+        self.range(0..denominator) < numerator
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range(0..items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.range(0..i + 1));
+        }
+    }
+
+    /// `count` different numbers from `range`, in a random order.
+    pub fn sample(&mut self, range: Range<usize>, count: usize) -> Vec<usize> {
+        assert!(
+            count <= range.len(),
+            "Cannot pick {} from {:?}",
+            count,
+            range
+        );
+        let mut items: Vec<usize> = range.collect();
+        self.shuffle(&mut items);
+        items.truncate(count);
+        items
+    }
+
+    /// A lower case word with a length in `length`.
+    pub fn word(&mut self, length: Range<usize>) -> String {
+        (0..self.range(length))
+            .map(|_| (b'a' + self.range(0..26) as u8) as char)
+            .collect()
+    }
+}
+
+/// Generates an input for `puzzle` from `seed`.
+///
+/// What `size` means depends on the day, usually the number of lines or the length
+/// of a side. Each day picks a size like that of a real input when it is not given.
+pub fn generate(puzzle: &dyn Puzzle, seed: u64, size: Option<usize>) -> String {
+    puzzle.generate(&mut Rng::new(seed), size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+
+    #[test]
+    fn test_rng() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        let numbers: Vec<u64> = (0..10).map(|_| a.next_u64()).collect();
+        assert_eq!(numbers, (0..10).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert_ne!(Rng::new(8).next_u64(), numbers[0]);
+        assert_eq!(Rng::new(0).next_u64(), 0xe220a8397b1dcdaf);
+
+        let mut rng = Rng::new(1);
+        assert!((0..1000)
+            .map(|_| rng.range(3..7))
+            .all(|x| (3..7).contains(&x)));
+        let mut sample = rng.sample(0..20, 20);
+        sample.sort_unstable();
+        assert_eq!(sample, (0..20).collect::<Vec<_>>());
+    }
+
+    /// Every generated input should parse and have an answer, for a few seeds.
+    #[test]
+    fn test_generate() {
+        // Part two of these runs for millions of steps whatever the input.
+        let slow = [15, 23];
+        for puzzle in registry::all() {
+            let day = puzzle.day();
+            for seed in 0..3 {
+                let size = match day {
+                    // Keep the brute force searches quick in debug builds.
+                    1 => Some(20),
+                    11 => Some(30),
+                    25 => Some(10_000),
+                    _ => None,
+                };
+                let input = generate(puzzle, seed, size);
+                assert_eq!(input, generate(puzzle, seed, size));
+                let parsed = puzzle.parse(&mut input.as_bytes()).unwrap_or_else(|e| {
+                    panic!("{}", e.report(&format!("day {} seed {}", day, seed)))
+                });
+                parsed.part_one();
+                if !slow.contains(&day) {
+                    parsed.part_two();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,350 @@
+//! A rectangular grid for the puzzles that take place on a map.
+
+use std::{
+    convert::TryFrom,
+    fmt::Display,
+    io::BufRead,
+    ops::{Index, IndexMut},
+    str::FromStr,
+};
+
+use crate::{input::parse_lines, ParseError};
+
+/// A cell in the grid as `(row, column)`, counting from the top left.
+pub type Position = (usize, usize);
+
+/// A step between cells as `(rows, columns)`.
+pub type Direction = (isize, isize);
+
+/// Up, left, right and down.
+pub const NEIGHBOURS4: [Direction; 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+/// Every cell touching another, including diagonally.
+pub const NEIGHBOURS8: [Direction; 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A grid stored row by row in a single `Vec`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    rows: usize,
+    columns: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid by calling `f` for each position.
+    pub fn from_fn(rows: usize, columns: usize, mut f: impl FnMut(Position) -> T) -> Self {
+        let cells = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (row, column)))
+            .map(&mut f)
+            .collect();
+        Grid {
+            rows,
+            columns,
+            cells,
+        }
+    }
+
+    /// Builds a grid from its rows, or returns `None` if they are not all the same
+    /// length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let columns = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != columns) {
+            return None;
+        }
+        Some(Grid {
+            rows: rows.len(),
+            columns,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn get(&self, (row, column): Position) -> Option<&T> {
+        if row < self.rows && column < self.columns {
+            self.cells.get(row * self.columns + column)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, (row, column): Position) -> Option<&mut T> {
+        if row < self.rows && column < self.columns {
+            self.cells.get_mut(row * self.columns + column)
+        } else {
+            None
+        }
+    }
+
+    /// The position one step from `position` in `direction`, if it is in the grid.
+    pub fn offset(&self, (row, column): Position, (dr, dc): Direction) -> Option<Position> {
+        let row = row.checked_add_signed(dr).filter(|&row| row < self.rows)?;
+        let column = column
+            .checked_add_signed(dc)
+            .filter(|&column| column < self.columns)?;
+        Some((row, column))
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.columns..(row + 1) * self.columns]
+    }
+
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics on a zero size, which an empty grid would ask for.
+        self.cells.chunks(self.columns.max(1))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let columns = self.columns;
+        (0..self.rows).flat_map(move |row| (0..columns).map(move |column| (row, column)))
+    }
+
+    /// Each cell along with its position.
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// The positions one step from `position` in each of `directions` that are in
+    /// the grid.
+    pub fn neighbours<'a>(
+        &'a self,
+        position: Position,
+        directions: &'a [Direction],
+    ) -> impl Iterator<Item = Position> + 'a {
+        directions
+            .iter()
+            .filter_map(move |&direction| self.offset(position, direction))
+    }
+
+    pub fn neighbours4(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        self.neighbours(position, &NEIGHBOURS4)
+    }
+
+    pub fn neighbours8(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        self.neighbours(position, &NEIGHBOURS8)
+    }
+
+    /// Every position from `position` in `direction` until the edge of the grid, not
+    /// including `position` itself.
+    pub fn ray(
+        &self,
+        position: Position,
+        direction: Direction,
+    ) -> impl Iterator<Item = Position> + '_ {
+        std::iter::successors(self.offset(position, direction), move |&position| {
+            self.offset(position, direction)
+        })
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            rows: self.rows,
+            columns: self.columns,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(rows: usize, columns: usize, value: T) -> Self {
+        Grid {
+            rows,
+            columns,
+            cells: vec![value; rows * columns],
+        }
+    }
+
+    pub fn column(&self, column: usize) -> Vec<T> {
+        (0..self.rows)
+            .map(|row| self[(row, column)].clone())
+            .collect()
+    }
+
+    /// The grid surrounded by `border` cells of `value` on every side.
+    pub fn pad(&self, border: usize, value: T) -> Self {
+        Grid::from_fn(
+            self.rows + 2 * border,
+            self.columns + 2 * border,
+            |(row, column)| match (row.checked_sub(border), column.checked_sub(border)) {
+                (Some(row), Some(column)) => self.get((row, column)).unwrap_or(&value).clone(),
+                _ => value.clone(),
+            },
+        )
+    }
+
+    /// The grid turned a quarter turn clockwise.
+    pub fn rotate(&self) -> Self {
+        Grid::from_fn(self.columns, self.rows, |(row, column)| {
+            self[(self.rows - 1 - column, row)].clone()
+        })
+    }
+
+    /// The grid turned upside down, so the first row is last.
+    pub fn flip_vertical(&self) -> Self {
+        Grid::from_fn(self.rows, self.columns, |(row, column)| {
+            self[(self.rows - 1 - row, column)].clone()
+        })
+    }
+
+    /// The grid mirrored left to right, so the first column is last.
+    pub fn flip_horizontal(&self) -> Self {
+        Grid::from_fn(self.rows, self.columns, |(row, column)| {
+            self[(row, self.columns - 1 - column)].clone()
+        })
+    }
+}
+
+impl<T> Grid<T>
+where
+    T: TryFrom<char>,
+    T::Error: Display,
+{
+    /// Parses one row per line, converting each character to a cell.
+    pub fn parse(input: impl BufRead) -> Result<Self, ParseError> {
+        let mut columns = None;
+        let rows = parse_lines(input, |line| {
+            let row: Vec<T> = line
+                .chars()
+                .enumerate()
+                .map(|(i, c)| {
+                    T::try_from(c).map_err(|e| ParseError::new(e.to_string()).with_column(i + 1))
+                })
+                .collect::<Result<_, _>>()?;
+            match columns {
+                Some(columns) if columns != row.len() => Err(ParseError::new(format!(
+                    "Expected {} cells in the row, found {}",
+                    columns,
+                    row.len()
+                ))),
+                _ => {
+                    columns = Some(row.len());
+                    Ok(row)
+                }
+            }
+        })?;
+        match Grid::from_rows(rows) {
+            Some(grid) if grid.rows > 0 && grid.columns > 0 => Ok(grid),
+            _ => Err(ParseError::new("The grid is empty")),
+        }
+    }
+}
+
+impl<T> FromStr for Grid<T>
+where
+    T: TryFrom<char>,
+    T::Error: Display,
+{
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Grid::parse(s.as_bytes())
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (row, column): Position) -> &Self::Output {
+        assert!(
+            row < self.rows && column < self.columns,
+            "Position {:?} is outside of a {}x{} grid",
+            (row, column),
+            self.rows,
+            self.columns
+        );
+        &self.cells[row * self.columns + column]
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, (row, column): Position) -> &mut Self::Output {
+        assert!(
+            row < self.rows && column < self.columns,
+            "Position {:?} is outside of a {}x{} grid",
+            (row, column),
+            self.rows,
+            self.columns
+        );
+        &mut self.cells[row * self.columns + column]
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, row) in self.iter_rows().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(s: &str) -> Grid<char> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let g = grid("ab\ncd\nef");
+        assert_eq!((g.rows(), g.columns()), (3, 2));
+        assert_eq!(g[(2, 0)], 'e');
+        assert_eq!(g.get((0, 2)), None);
+        assert_eq!(g.to_string(), "ab\ncd\nef");
+
+        let error = "ab\nc".parse::<Grid<char>>().unwrap_err();
+        assert_eq!(error.line(), Some(2));
+        assert!("".parse::<Grid<char>>().is_err());
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let g = grid("abc\ndef\nghi");
+        let cells = |positions: Vec<Position>| positions.iter().map(|&p| g[p]).collect::<String>();
+        assert_eq!(cells(g.neighbours4((0, 0)).collect()), "bd");
+        assert_eq!(cells(g.neighbours8((1, 1)).collect()), "abcdfghi");
+        assert_eq!(cells(g.neighbours8((2, 2)).collect()), "efh");
+        assert_eq!(cells(g.ray((0, 0), (1, 1)).collect()), "ei");
+        assert_eq!(cells(g.ray((1, 2), (0, -1)).collect()), "ed");
+        assert_eq!(g.ray((0, 0), (-1, 0)).count(), 0);
+    }
+
+    #[test]
+    fn test_transform() {
+        let g = grid("abc\ndef");
+        assert_eq!(g.rotate().to_string(), "da\neb\nfc");
+        assert_eq!(g.rotate().rotate().rotate().rotate(), g);
+        assert_eq!(g.flip_vertical().to_string(), "def\nabc");
+        assert_eq!(g.flip_horizontal().to_string(), "cba\nfed");
+        assert_eq!(g.column(1), vec!['b', 'e']);
+        assert_eq!(g.pad(1, '.').to_string(), ".....\n.abc.\n.def.\n.....");
+        assert_eq!(g.row(1), &['d', 'e', 'f']);
+        assert_eq!(g.map(|c| c.to_ascii_uppercase()).to_string(), "ABC\nDEF");
+    }
+}
@@ -0,0 +1,409 @@
+//! Just enough HTTP to talk to the Advent of Code site.
+//!
+//! Plain `http://` URLs are handled here over a [`TcpStream`], which is what the tests
+//! use against a local stub. There is no TLS implementation, so `https://` requests
+//! are handed to `curl`, which has to be installed for them to work.
+//!
+//! The session token and URL end up in the request headers and in `curl`'s config, so
+//! they are refused if they contain whitespace or control characters, which could
+//! otherwise add headers or config of their own.
+
+use std::{
+    io::{self, Read, Write},
+    net::TcpStream,
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+/// How long to wait for a server before giving up.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+const USER_AGENT: &str = "github.com/nicholas-l/advent-of-code-2020";
+
+/// What came back from a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+/// The parts of an `http://` URL.
+#[derive(Debug, PartialEq, Eq)]
+struct Url<'a> {
+    host: &'a str,
+    port: u16,
+    path: &'a str,
+}
+
+fn parse_url(url: &str) -> Result<Url<'_>, String> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| format!("Expected an http:// URL, found {:?}", url))?;
+    let (authority, path) = match rest.find('/') {
+        Some(i) => rest.split_at(i),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (
+            host,
+            port.parse()
+                .map_err(|_| format!("Invalid port in {:?}", url))?,
+        ),
+        None => (authority, 80),
+    };
+    if host.is_empty() {
+        return Err(format!("Missing host in {:?}", url));
+    }
+    Ok(Url { host, port, path })
+}
+
+/// Percent-encodes `fields` as an `application/x-www-form-urlencoded` body.
+pub fn form(fields: &[(&str, &str)]) -> String {
+    let encode = |s: &str| -> String {
+        s.bytes()
+            .map(|b| match b {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                    (b as char).to_string()
+                }
+                b' ' => "+".to_string(),
+                _ => format!("%{:02X}", b),
+            })
+            .collect()
+    };
+    fields
+        .iter()
+        .map(|(name, value)| format!("{}={}", encode(name), encode(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Decodes a body sent with `Transfer-Encoding: chunked`.
+fn dechunk(mut body: &[u8]) -> Result<Vec<u8>, String> {
+    let mut decoded = Vec::new();
+    loop {
+        let line_end = body
+            .windows(2)
+            .position(|w| w == b"\r\n")
+            .ok_or("Truncated chunked body")?;
+        let size = String::from_utf8_lossy(&body[..line_end]);
+        let size = size.split(';').next().unwrap().trim();
+        let size = usize::from_str_radix(size, 16)
+            .map_err(|_| format!("Invalid chunk size {:?}", size))?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Ok(decoded);
+        }
+        if body.len() < size {
+            return Err("Truncated chunked body".to_string());
+        }
+        decoded.extend_from_slice(&body[..size]);
+        body = body[size..].strip_prefix(b"\r\n").unwrap_or(&body[size..]);
+    }
+}
+
+/// Splits a raw response into its status and body.
+fn parse_response(raw: &[u8]) -> Result<Response, String> {
+    let header_end = raw
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or("The response had no end to its headers")?;
+    let head = String::from_utf8_lossy(&raw[..header_end]);
+    let mut lines = head.split("\r\n");
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|status| status.parse().ok())
+        .ok_or("The response had no status")?;
+    let mut body = raw[header_end + 4..].to_vec();
+    let mut chunked = false;
+    for line in lines {
+        if let Some((name, value)) = line.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("transfer-encoding") {
+                chunked = value.eq_ignore_ascii_case("chunked");
+            } else if name.eq_ignore_ascii_case("content-length") {
+                if let Ok(length) = value.parse::<usize>() {
+                    body.truncate(length);
+                }
+            }
+        }
+    }
+    if chunked {
+        body = dechunk(&body)?;
+    }
+    let body = String::from_utf8(body).map_err(|_| "The response was not UTF-8")?;
+    Ok(Response { status, body })
+}
+
+fn send_plain(url: &str, session: &str, body: Option<&str>) -> Result<Response, String> {
+    let parsed = parse_url(url)?;
+    let mut stream = TcpStream::connect((parsed.host, parsed.port))
+        .map_err(|e| format!("Could not connect to {}: {}", url, e))?;
+    stream
+        .set_read_timeout(Some(TIMEOUT))
+        .and_then(|_| stream.set_write_timeout(Some(TIMEOUT)))
+        .map_err(|e| format!("Could not connect to {}: {}", url, e))?;
+    let mut request = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: {}\r\nCookie: session={}\r\nConnection: close\r\n",
+        if body.is_some() { "POST" } else { "GET" },
+        parsed.path,
+        parsed.host,
+        USER_AGENT,
+        session
+    );
+    if let Some(body) = body {
+        request += &format!(
+            "Content-Type: application/x-www-form-urlencoded\r\nContent-Length: {}\r\n",
+            body.len()
+        );
+    }
+    request += "\r\n";
+    request += body.unwrap_or("");
+    let mut raw = Vec::new();
+    stream
+        .write_all(request.as_bytes())
+        .and_then(|_| stream.read_to_end(&mut raw))
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+    parse_response(&raw)
+}
+
+/// Has `curl` make the request, passing the session through its config on stdin so
+/// that it does not show up in the process list.
+fn send_curl(url: &str, session: &str, body: Option<&str>) -> Result<Response, String> {
+    let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+    let mut config = format!(
+        "url = {}\nuser-agent = {}\nheader = {}\n",
+        quote(url),
+        quote(USER_AGENT),
+        quote(&format!("Cookie: session={}", session))
+    );
+    if let Some(body) = body {
+        config += &format!("data = {}\n", quote(body));
+    }
+    let mut child = Command::new("curl")
+        .args(["--silent", "--show-error", "--config", "-"])
+        .args(["--max-time", &TIMEOUT.as_secs().to_string()])
+        .args(["--write-out", "\n%{http_code}"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => format!(
+                "Could not find curl, which is needed for https:// URLs such as {}",
+                url
+            ),
+            _ => format!("Could not run curl for {}: {}", url, e),
+        })?;
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(config.as_bytes())
+        .map_err(|e| format!("Could not run curl for {}: {}", url, e))?;
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Could not run curl for {}: {}", url, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Request to {} failed: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let stdout = String::from_utf8(output.stdout).map_err(|_| "The response was not UTF-8")?;
+    let (body, status) = stdout.rsplit_once('\n').ok_or("curl gave no status")?;
+    Ok(Response {
+        status: status.parse().map_err(|_| "curl gave no status")?,
+        body: body.to_string(),
+    })
+}
+
+/// Refuses a session token or URL which could break out of the header or config line
+/// it is written to.
+fn check(url: &str, session: &str) -> Result<(), String> {
+    if url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(format!(
+            "The URL {:?} contains whitespace or control characters",
+            url
+        ));
+    }
+    if session.is_empty() || !session.chars().all(|c| c.is_ascii_graphic()) {
+        return Err(
+            "The session token must be a single word of printable ASCII characters".to_string(),
+        );
+    }
+    Ok(())
+}
+
+fn send(url: &str, session: &str, body: Option<&str>) -> Result<Response, String> {
+    check(url, session)?;
+    if url.starts_with("https://") {
+        send_curl(url, session, body)
+    } else {
+        send_plain(url, session, body)
+    }
+}
+
+/// Fetches `url` as the user with the given session cookie.
+pub fn get(url: &str, session: &str) -> Result<Response, String> {
+    send(url, session, None)
+}
+
+/// Posts a [form] to `url` as the user with the given session cookie.
+pub fn post(url: &str, session: &str, form: &str) -> Result<Response, String> {
+    send(url, session, Some(form))
+}
+
+/// A local server for tests, which answers each connection with the next response
+/// and hands back the raw requests it received once they have all been sent.
+#[cfg(test)]
+pub(crate) fn stub(responses: Vec<(u16, &str)>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+    use std::{io::BufRead, io::BufReader, net::TcpListener};
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let responses: Vec<(u16, String)> = responses
+        .into_iter()
+        .map(|(status, body)| (status, body.to_string()))
+        .collect();
+    let handle = std::thread::spawn(move || {
+        let mut requests = Vec::new();
+        for (status, body) in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.strip_prefix("Content-Length: ") {
+                    length = value.trim().parse().unwrap();
+                }
+                request += &line;
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body_bytes = vec![0; length];
+            reader.read_exact(&mut body_bytes).unwrap();
+            request += &String::from_utf8(body_bytes).unwrap();
+            requests.push(request);
+            write!(
+                stream,
+                "HTTP/1.1 {} Stub\r\nContent-Length: {}\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+        }
+        requests
+    });
+    (url, handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_url() {
+        assert_eq!(
+            parse_url("http://localhost:8080/2020/day/1/input"),
+            Ok(Url {
+                host: "localhost",
+                port: 8080,
+                path: "/2020/day/1/input"
+            })
+        );
+        assert_eq!(
+            parse_url("http://example.com"),
+            Ok(Url {
+                host: "example.com",
+                port: 80,
+                path: "/"
+            })
+        );
+        assert!(parse_url("ftp://example.com").is_err());
+        assert!(parse_url("http://example.com:port/").is_err());
+    }
+
+    #[test]
+    fn test_parse_response() {
+        assert_eq!(
+            parse_response(b"HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\n1\n2\n"),
+            Ok(Response {
+                status: 200,
+                body: "1\n2".to_string()
+            })
+        );
+        assert_eq!(
+            parse_response(
+                b"HTTP/1.1 404 Not Found\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n2;x\r\nde\r\n0\r\n\r\n"
+            ),
+            Ok(Response {
+                status: 404,
+                body: "abcde".to_string()
+            })
+        );
+        assert!(parse_response(b"HTTP/1.1 200 OK\r\n").is_err());
+    }
+
+    #[test]
+    fn test_form() {
+        assert_eq!(
+            form(&[("level", "2"), ("answer", "Merry Christmas!")]),
+            "level=2&answer=Merry+Christmas%21"
+        );
+    }
+
+    #[test]
+    fn test_stub() {
+        let (url, server) = stub(vec![(200, "input\n"), (302, "")]);
+        assert_eq!(
+            get(&format!("{}/2020/day/1/input", url), "abc"),
+            Ok(Response {
+                status: 200,
+                body: "input\n".to_string()
+            })
+        );
+        assert_eq!(
+            post(&format!("{}/2020/day/1/answer", url), "abc", "level=1")
+                .unwrap()
+                .status,
+            302
+        );
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("GET /2020/day/1/input HTTP/1.1\r\n"));
+        assert!(requests[0].contains("Cookie: session=abc\r\n"));
+        assert!(requests[1].starts_with("POST /2020/day/1/answer HTTP/1.1\r\n"));
+        assert!(requests[1].ends_with("\r\n\r\nlevel=1"));
+    }
+
+    #[test]
+    fn test_check() {
+        assert_eq!(
+            check("https://adventofcode.com/2020/day/1/input", "53616c7465"),
+            Ok(())
+        );
+        for session in [
+            "",
+            "abc\noutput = /tmp/x",
+            "abc\r\nX-Extra: 1",
+            "a b",
+            "abc\u{7f}",
+        ] {
+            assert!(
+                check("http://localhost/", session).is_err(),
+                "{:?}",
+                session
+            );
+        }
+        assert!(check("http://localhost/\nurl = http://elsewhere/", "abc").is_err());
+        // Refused before anything is sent.
+        assert!(get("http://127.0.0.1:9/", "abc\ndef")
+            .unwrap_err()
+            .contains("session token"));
+    }
+}
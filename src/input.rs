@@ -0,0 +1,180 @@
+//! Helpers shared by the day parsers for reading puzzle input.
+
+use std::io::{BufRead, Lines};
+
+use crate::ParseError;
+
+/// Parses each line of `input` with `f`, attaching the line number and text to any
+/// error it returns.
+pub fn parse_lines<T>(
+    input: impl BufRead,
+    mut f: impl FnMut(&str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let line = line?;
+            f(&line).map_err(|e| e.in_line(i + 1, &line))
+        })
+        .collect()
+}
+
+/// A run of non-blank lines, as returned by [`sections`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    start: usize,
+    lines: Vec<String>,
+}
+
+impl Section {
+    /// The line number of the first line.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Each line along with its line number.
+    pub fn lines(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.lines
+            .iter()
+            .enumerate()
+            .map(move |(i, line)| (self.start + i, line.as_str()))
+    }
+
+    /// The first line without its colon, if it is a header like `Player 1:`.
+    pub fn header(&self) -> Option<&str> {
+        self.lines[0].strip_suffix(':')
+    }
+
+    /// The lines after the header, checking that the header is `name:`.
+    pub fn expect_header(
+        &self,
+        name: &str,
+    ) -> Result<impl Iterator<Item = (usize, &str)>, ParseError> {
+        match self.header() {
+            Some(header) if header == name => Ok(self.lines().skip(1)),
+            _ => Err(ParseError::new(format!("Expected `{}:`", name))
+                .with_column(1)
+                .in_line(self.start, &self.lines[0])),
+        }
+    }
+}
+
+/// The iterator returned by [`sections`].
+pub struct Sections<R> {
+    lines: Lines<R>,
+    line: usize,
+}
+
+/// Reads `input` as blank line separated sections.
+///
+/// Lines may end in `\n` or `\r\n` and trailing whitespace is ignored, so a line
+/// holding only spaces counts as blank. Any number of blank lines separate two
+/// sections and those at the start or end of the input are skipped.
+pub fn sections<R: BufRead>(input: R) -> Sections<R> {
+    Sections {
+        lines: input.lines(),
+        line: 0,
+    }
+}
+
+impl<R: BufRead> Iterator for Sections<R> {
+    type Item = Result<Section, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut section: Option<Section> = None;
+        for line in &mut self.lines {
+            self.line += 1;
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return Some(Err(ParseError::from(e).in_line(self.line, ""))),
+            };
+            let line = line.trim_end();
+            match &mut section {
+                None if line.is_empty() => {}
+                None => {
+                    section = Some(Section {
+                        start: self.line,
+                        lines: vec![line.to_string()],
+                    })
+                }
+                Some(_) if line.is_empty() => break,
+                Some(section) => section.lines.push(line.to_string()),
+            }
+        }
+        section.map(Ok)
+    }
+}
+
+/// A 64-bit FNV-1a hash of `bytes`, used to tell puzzle inputs apart.
+pub fn hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_parse_lines() {
+        let parsed = parse_lines(Cursor::new("1\n2\n3"), |line| Ok(line.parse::<usize>()?));
+        assert_eq!(parsed, Ok(vec![1, 2, 3]));
+
+        let error =
+            parse_lines(Cursor::new("1\nx\n3"), |line| Ok(line.parse::<usize>()?)).unwrap_err();
+        assert_eq!(error.line(), Some(2));
+        assert_eq!(error.text(), Some("x"));
+    }
+
+    #[test]
+    fn test_hash() {
+        assert_eq!(hash(b""), 0xcbf29ce484222325);
+        assert_eq!(hash(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(hash(b"foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn test_sections() {
+        let input = "Player 1:\n9\n2\n\nPlayer 2:\n5";
+        let sections: Vec<Section> = sections(Cursor::new(input))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[1].start(), 5);
+        assert_eq!(sections[1].header(), Some("Player 2"));
+        let lines: Vec<(usize, &str)> = sections[1].lines().collect();
+        assert_eq!(lines, vec![(5, "Player 2:"), (6, "5")]);
+        let body: Vec<(usize, &str)> = sections[0].expect_header("Player 1").unwrap().collect();
+        assert_eq!(body, vec![(2, "9"), (3, "2")]);
+
+        let Err(error) = sections[1].expect_header("Player 3") else {
+            panic!("Expected the header to be rejected");
+        };
+        assert_eq!(error.line(), Some(5));
+        assert_eq!(error.message(), "Expected `Player 3:`");
+    }
+
+    #[test]
+    fn test_sections_whitespace() {
+        let input = "\r\na\r\nb  \r\n \r\n\r\n\r\nc\r\n\r\n\n";
+        let sections: Vec<Vec<(usize, String)>> = sections(Cursor::new(input))
+            .map(|section| {
+                let section = section.unwrap();
+                section
+                    .lines()
+                    .map(|(number, line)| (number, line.to_string()))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            sections,
+            vec![
+                vec![(2, "a".to_string()), (3, "b".to_string())],
+                vec![(7, "c".to_string())]
+            ]
+        );
+    }
+}
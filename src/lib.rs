@@ -1,11 +1,37 @@
-use std::{
-    io::BufRead,
-    path::{Path, PathBuf},
-};
-
 #[macro_use]
 extern crate lazy_static;
 
+#[macro_use]
+pub mod log;
+
+mod answer;
+pub use answer::Answer;
+mod error;
+pub use error::ParseError;
+pub mod alloc;
+pub mod batch;
+pub mod bench;
+pub mod cache;
+pub mod cancel;
+pub mod cli;
+pub mod differential;
+pub mod fetch;
+pub mod generate;
+pub mod grid;
+pub mod http;
+pub mod input;
+pub mod manifest;
+pub mod pool;
+pub mod progress;
+pub mod registry;
+pub mod report;
+pub mod runner;
+pub mod scaffold;
+pub mod solution;
+pub mod submit;
+pub mod watch;
+pub use solution::{Parsed, Puzzle, Solution};
+
 pub mod day01;
 pub mod day02;
 pub mod day03;
@@ -32,428 +58,219 @@ pub mod day23;
 pub mod day24;
 pub mod day25;
 
-type DayFn = fn(Box<dyn BufRead>) -> usize;
-
-pub fn get_day(day: usize) -> (DayFn, DayFn, PathBuf) {
-    match day {
-        1 => {
-            use day01::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day01.txt"),
-            )
-        }
-        2 => {
-            use day02::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day02.txt"),
-            )
-        }
-        3 => {
-            use day03::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day03.txt"),
-            )
-        }
-        4 => {
-            use day04::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day04.txt"),
-            )
-        }
-        5 => {
-            use day05::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day05.txt"),
-            )
-        }
-        6 => {
-            use day06::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day06.txt"),
-            )
-        }
-        7 => {
-            use day07::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day07.txt"),
-            )
-        }
-        8 => {
-            use day08::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day08.txt"),
-            )
-        }
-        9 => {
-            use day09::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day09.txt"),
-            )
-        }
-        10 => {
-            use day10::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day10.txt"),
-            )
-        }
-        11 => {
-            use day11::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day11.txt"),
-            )
-        }
-        12 => {
-            use day12::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day12.txt"),
-            )
-        }
-        13 => {
-            use day13::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day13.txt"),
-            )
-        }
-        14 => {
-            use day14::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day14.txt"),
-            )
-        }
-
-        15 => {
-            use day15::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day15.txt"),
-            )
-        }
-        16 => {
-            use day16::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day16.txt"),
-            )
-        }
-        17 => {
-            use day17::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day17.txt"),
-            )
-        }
-        18 => {
-            use day18::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day18.txt"),
-            )
-        }
-        19 => {
-            use day19::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day19.txt"),
-            )
-        }
-        20 => {
-            use day20::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day20.txt"),
-            )
-        }
-
-        21 => {
-            use day21::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day21.txt"),
-            )
-        }
-
-        22 => {
-            use day22::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day22.txt"),
-            )
-        }
-
-        23 => {
-            use day23::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day23.txt"),
-            )
-        }
-
-        24 => {
-            use day24::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day24.txt"),
-            )
-        }
-
-        25 => {
-            use day25::{star_one, star_two};
-            (
-                star_one as DayFn,
-                star_two as DayFn,
-                Path::new("data").join("day25.txt"),
-            )
-        }
-
-        x => {
-            unimplemented!("Have not implemented day {}", x);
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::fs;
     use std::io::BufReader;
 
-    fn get_data(filepath: &PathBuf) -> Box<dyn BufRead> {
-        let f = fs::File::open(filepath).unwrap();
-        let input = BufReader::new(f);
-        Box::new(input)
+    fn solve(day: usize) -> (Answer, Answer) {
+        solve_in(registry::DEFAULT_YEAR, day)
+    }
+
+    fn solve_in(year: usize, day: usize) -> (Answer, Answer) {
+        let puzzle = registry::find(year, day).unwrap();
+        let f = fs::File::open(puzzle.input_path()).unwrap();
+        let parsed = puzzle.parse(&mut BufReader::new(f)).unwrap();
+        (parsed.part_one(), parsed.part_two())
     }
 
     #[test]
     fn day01_complete() {
-        let (star_one, star_two, filepath) = get_day(1);
-        assert_eq!(star_one(get_data(&filepath)), 805731);
+        let (star_one, star_two) = solve(1);
+        assert_eq!(star_one, Answer::Number(805731));
 
-        assert_eq!(star_two(get_data(&filepath)), 192684960);
+        assert_eq!(star_two, Answer::Number(192684960));
     }
 
     #[test]
     fn day02_complete() {
-        let (star_one, star_two, filename) = get_day(2);
+        let (star_one, star_two) = solve(2);
 
-        assert_eq!(star_one(get_data(&filename)), 469);
-        assert_eq!(star_two(get_data(&filename)), 267);
+        assert_eq!(star_one, Answer::Number(469));
+        assert_eq!(star_two, Answer::Number(267));
     }
 
     #[test]
     fn day03_complete() {
-        let (star_one, star_two, filename) = get_day(3);
+        let (star_one, star_two) = solve(3);
 
-        assert_eq!(star_one(get_data(&filename)), 145);
-        assert_eq!(star_two(get_data(&filename)), 3424528800);
+        assert_eq!(star_one, Answer::Number(145));
+        assert_eq!(star_two, Answer::Number(3424528800));
     }
 
     #[test]
     fn day04_complete() {
-        let (star_one, star_two, filename) = get_day(4);
+        let (star_one, star_two) = solve(4);
 
-        assert_eq!(star_one(get_data(&filename)), 230);
-        assert_eq!(star_two(get_data(&filename)), 156);
+        assert_eq!(star_one, Answer::Number(230));
+        assert_eq!(star_two, Answer::Number(156));
     }
 
     #[test]
     fn day05_complete() {
-        let (star_one, star_two, filename) = get_day(5);
+        let (star_one, star_two) = solve(5);
 
-        assert_eq!(star_one(get_data(&filename)), 994);
-        assert_eq!(star_two(get_data(&filename)), 741);
+        assert_eq!(star_one, Answer::Number(994));
+        assert_eq!(star_two, Answer::Number(741));
     }
 
     #[test]
     fn day06_complete() {
-        let (star_one, star_two, filename) = get_day(6);
+        let (star_one, star_two) = solve(6);
 
-        assert_eq!(star_one(get_data(&filename)), 6382);
-        assert_eq!(star_two(get_data(&filename)), 3197);
+        assert_eq!(star_one, Answer::Number(6382));
+        assert_eq!(star_two, Answer::Number(3197));
     }
 
     #[test]
     fn day07_complete() {
-        let (star_one, star_two, filename) = get_day(7);
+        let (star_one, star_two) = solve(7);
 
-        assert_eq!(star_one(get_data(&filename)), 172);
-        assert_eq!(star_two(get_data(&filename)), 39645);
+        assert_eq!(star_one, Answer::Number(172));
+        assert_eq!(star_two, Answer::Number(39645));
     }
 
     #[test]
     fn day08_complete() {
-        let (star_one, star_two, filename) = get_day(8);
+        let (star_one, star_two) = solve(8);
 
-        assert_eq!(star_one(get_data(&filename)), 1816);
-        assert_eq!(star_two(get_data(&filename)), 1149);
+        assert_eq!(star_one, Answer::Number(1816));
+        assert_eq!(star_two, Answer::Number(1149));
     }
 
     #[test]
     fn day09_complete() {
-        let (star_one, star_two, filename) = get_day(9);
+        let (star_one, star_two) = solve(9);
 
-        assert_eq!(star_one(get_data(&filename)), 18272118);
-        assert_eq!(star_two(get_data(&filename)), 2186361);
+        assert_eq!(star_one, Answer::Number(18272118));
+        assert_eq!(star_two, Answer::Number(2186361));
     }
 
     #[test]
     fn day10_complete() {
-        let (star_one, star_two, filename) = get_day(10);
+        let (star_one, star_two) = solve(10);
 
-        assert_eq!(star_one(get_data(&filename)), 2201);
-        assert_eq!(star_two(get_data(&filename)), 169255295254528);
+        assert_eq!(star_one, Answer::Number(2201));
+        assert_eq!(star_two, Answer::Number(169255295254528));
     }
 
     #[test]
     fn day11_complete() {
-        let (star_one, star_two, filename) = get_day(11);
+        let (star_one, star_two) = solve(11);
 
-        assert_eq!(star_one(get_data(&filename)), 2412);
-        assert_eq!(star_two(get_data(&filename)), 2176);
+        assert_eq!(star_one, Answer::Number(2412));
+        assert_eq!(star_two, Answer::Number(2176));
     }
     #[test]
     fn day12_complete() {
-        let (star_one, star_two, filename) = get_day(12);
+        let (star_one, star_two) = solve(12);
 
-        assert_eq!(star_one(get_data(&filename)), 1457);
-        assert_eq!(star_two(get_data(&filename)), 106860);
+        assert_eq!(star_one, Answer::Number(1457));
+        assert_eq!(star_two, Answer::Number(106860));
     }
     #[test]
     fn day13_complete() {
-        let (star_one, star_two, filename) = get_day(13);
+        let (star_one, star_two) = solve(13);
 
-        assert_eq!(star_one(get_data(&filename)), 4315);
-        assert_eq!(star_two(get_data(&filename)), 556100168221141);
+        assert_eq!(star_one, Answer::Number(4315));
+        assert_eq!(star_two, Answer::BigNumber(556100168221141));
     }
 
     #[test]
     fn day14_complete() {
-        let (star_one, star_two, filename) = get_day(14);
+        let (star_one, star_two) = solve(14);
 
-        assert_eq!(star_one(get_data(&filename)), 7997531787333);
-        assert_eq!(star_two(get_data(&filename)), 3564822193820);
+        assert_eq!(star_one, Answer::Number(7997531787333));
+        assert_eq!(star_two, Answer::Number(3564822193820));
     }
 
     #[test]
     fn day15_complete() {
-        let (star_one, star_two, filename) = get_day(15);
+        let (star_one, star_two) = solve(15);
 
-        assert_eq!(star_one(get_data(&filename)), 639);
-        assert_eq!(star_two(get_data(&filename)), 266);
+        assert_eq!(star_one, Answer::Number(639));
+        assert_eq!(star_two, Answer::Number(266));
     }
     #[test]
     fn day16_complete() {
-        let (star_one, star_two, filename) = get_day(16);
+        let (star_one, star_two) = solve(16);
 
-        assert_eq!(star_one(get_data(&filename)), 25972);
-        assert_eq!(star_two(get_data(&filename)), 622670335901);
+        assert_eq!(star_one, Answer::Number(25972));
+        assert_eq!(star_two, Answer::Number(622670335901));
     }
 
     #[test]
     fn day17_complete() {
-        let (star_one, star_two, filename) = get_day(17);
+        let (star_one, star_two) = solve(17);
 
-        assert_eq!(star_one(get_data(&filename)), 375);
-        assert_eq!(star_two(get_data(&filename)), 2192);
+        assert_eq!(star_one, Answer::Number(375));
+        assert_eq!(star_two, Answer::Number(2192));
     }
     #[test]
     fn day18_complete() {
-        let (star_one, star_two, filename) = get_day(18);
+        let (star_one, star_two) = solve(18);
 
-        assert_eq!(star_one(get_data(&filename)), 5374004645253);
-        assert_eq!(star_two(get_data(&filename)), 88782789402798);
+        assert_eq!(star_one, Answer::Number(5374004645253));
+        assert_eq!(star_two, Answer::Number(88782789402798));
     }
 
     #[test]
     fn day19_complete() {
-        let (star_one, star_two, filename) = get_day(19);
+        let (star_one, star_two) = solve(19);
 
-        assert_eq!(star_one(get_data(&filename)), 115);
-        assert_eq!(star_two(get_data(&filename)), 237);
+        assert_eq!(star_one, Answer::Number(115));
+        assert_eq!(star_two, Answer::Number(237));
     }
 
     #[test]
     fn day20_complete() {
-        let (star_one, star_two, filename) = get_day(20);
+        let (star_one, star_two) = solve(20);
 
-        assert_eq!(star_one(get_data(&filename)), 108603771107737);
-        assert_eq!(star_two(get_data(&filename)), 2129);
+        assert_eq!(star_one, Answer::Number(108603771107737));
+        assert_eq!(star_two, Answer::Number(2129));
     }
 
     #[test]
     fn day21_complete() {
-        let (star_one, star_two, filename) = get_day(21);
+        let (star_one, star_two) = solve(21);
 
-        assert_eq!(star_one(get_data(&filename)), 2412);
-        assert_eq!(star_two(get_data(&filename)), 2412);
+        assert_eq!(star_one, Answer::Number(2412));
+        assert_eq!(
+            star_two,
+            Answer::from("mfp,mgvfmvp,nhdjth,hcdchl,dvkbjh,dcvrf,bcjz,mhnrqp")
+        );
     }
 
     #[test]
     fn day22_complete() {
-        let (star_one, star_two, filename) = get_day(22);
+        let (star_one, star_two) = solve(22);
 
-        assert_eq!(star_one(get_data(&filename)), 31308);
-        assert_eq!(star_two(get_data(&filename)), 33647);
+        assert_eq!(star_one, Answer::Number(31308));
+        assert_eq!(star_two, Answer::Number(33647));
     }
 
     #[test]
     fn day23_complete() {
-        let (star_one, star_two, filename) = get_day(23);
+        let (star_one, star_two) = solve(23);
 
-        assert_eq!(star_one(get_data(&filename)), 94238657);
-        assert_eq!(star_two(get_data(&filename)), 3072905352);
+        assert_eq!(star_one, Answer::Number(94238657));
+        assert_eq!(star_two, Answer::Number(3072905352));
     }
 
     #[test]
     fn day24_complete() {
-        let (star_one, star_two, filename) = get_day(24);
+        let (star_one, star_two) = solve(24);
 
-        assert_eq!(star_one(get_data(&filename)), 346);
-        assert_eq!(star_two(get_data(&filename)), 3802);
+        assert_eq!(star_one, Answer::Number(346));
+        assert_eq!(star_two, Answer::Number(3802));
     }
 
     #[test]
     fn day25_complete() {
-        let (star_one, star_two, filename) = get_day(25);
+        let (star_one, star_two) = solve(25);
 
-        assert_eq!(star_one(get_data(&filename)), 4968512);
-        assert_eq!(star_two(get_data(&filename)), 1);
+        assert_eq!(star_one, Answer::Number(4968512));
+        assert_eq!(star_two, Answer::from("Merry Christmas!"));
     }
 }
@@ -0,0 +1,289 @@
+//! Known answers for a set of puzzle inputs, used by `main verify`.
+//!
+//! A manifest is a text file with one answer per line:
+//!
+//! ```text
+//! # day  part  input      answer
+//! 1      1     day01.txt  805731
+//! ```
+//!
+//! Inputs are relative to the manifest and the answer is the rest of the line, so it
+//! may contain spaces. Blank lines and lines starting with `#` are ignored.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    cancel::Token,
+    cli::{Part, Source},
+    input::parse_lines,
+    pool, registry, runner, ParseError,
+};
+
+/// Where `verify` looks for the answers to a year's puzzles unless told otherwise.
+pub fn default_path(year: usize) -> PathBuf {
+    registry::data_dir(year).join("answers.txt")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub day: usize,
+    pub part: Part,
+    pub input: PathBuf,
+    pub answer: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    pub entries: Vec<Entry>,
+}
+
+impl Manifest {
+    /// Parses a manifest, resolving inputs relative to `base`.
+    pub fn parse(text: &str, base: &Path) -> Result<Self, ParseError> {
+        let entries = parse_lines(text.as_bytes(), |line| {
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                return Ok(None);
+            }
+            let mut rest = line;
+            let mut next = |name: &str| {
+                rest = rest.trim_start();
+                let (field, tail) =
+                    rest.split_at(rest.find(char::is_whitespace).unwrap_or(rest.len()));
+                rest = tail;
+                match field {
+                    "" => Err(ParseError::new(format!("Missing {}", name))
+                        .with_column(line.chars().count() + 1)),
+                    field => Ok(field),
+                }
+            };
+            let day_field = next("day")?;
+            let day = match day_field.parse::<usize>() {
+                Ok(day) if (1..=25).contains(&day) => day,
+                _ => {
+                    return Err(ParseError::at_token(
+                        line,
+                        day_field,
+                        "Expected a day from 1 to 25",
+                    ))
+                }
+            };
+            let part = match next("part")? {
+                "1" => Part::One,
+                "2" => Part::Two,
+                part => return Err(ParseError::at_token(line, part, "Expected part 1 or 2")),
+            };
+            let input = base.join(next("input")?);
+            let answer = match rest.trim() {
+                "" => {
+                    return Err(
+                        ParseError::new("Missing answer").with_column(line.chars().count() + 1)
+                    )
+                }
+                answer => answer.to_string(),
+            };
+            Ok(Some(Entry {
+                day,
+                part,
+                input,
+                answer,
+            }))
+        })?;
+        Ok(Manifest {
+            entries: entries.into_iter().flatten().collect(),
+        })
+    }
+
+    /// The days in `days` which have no entries, and so would not be checked.
+    pub fn missing(&self, days: &[usize]) -> Vec<usize> {
+        days.iter()
+            .copied()
+            .filter(|&day| !self.entries.iter().any(|entry| entry.day == day))
+            .collect()
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ParseError> {
+        let text = fs::read_to_string(path)?;
+        Manifest::parse(&text, path.parent().unwrap_or_else(|| Path::new("")))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    Mismatch {
+        actual: String,
+    },
+    /// The day could not be run, with the diagnostic explaining why.
+    Fail(String),
+}
+
+/// The result of checking one [`Entry`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check<'a> {
+    pub entry: &'a Entry,
+    pub outcome: Outcome,
+}
+
+impl Check<'_> {
+    pub fn to_text(&self) -> String {
+        let entry = self.entry;
+        let part = match entry.part {
+            Part::One => 1,
+            Part::Two => 2,
+        };
+        let outcome = match &self.outcome {
+            Outcome::Pass => "pass".to_string(),
+            Outcome::Mismatch { actual } => {
+                format!("MISMATCH expected {}, got {}", entry.answer, actual)
+            }
+            Outcome::Fail(report) => format!("FAIL\n{}", report.trim_end()),
+        };
+        format!(
+            "Day {} part {} ({}): {}",
+            entry.day,
+            part,
+            entry.input.display(),
+            outcome
+        )
+    }
+}
+
+/// Runs each entry for the given days of `year` on up to `jobs` threads and compares
+/// the answers, giving up on an input after `timeout`. Every day given must be
+/// implemented.
+///
+/// Entries for both parts of the same input share a single parse.
+pub fn verify<'a>(
+    manifest: &'a Manifest,
+    year: usize,
+    days: &[usize],
+    jobs: usize,
+    timeout: Option<Duration>,
+) -> Vec<Check<'a>> {
+    // Group the entries by input, keeping the order they were listed in.
+    let mut groups: Vec<Vec<&Entry>> = Vec::new();
+    for entry in manifest
+        .entries
+        .iter()
+        .filter(|entry| days.contains(&entry.day))
+    {
+        match groups
+            .iter_mut()
+            .find(|group| group[0].day == entry.day && group[0].input == entry.input)
+        {
+            Some(group) => group.push(entry),
+            None => groups.push(vec![entry]),
+        }
+    }
+
+    let mut checks = Vec::new();
+    pool::map_ordered(
+        &groups,
+        jobs,
+        |group| {
+            let part = if group.iter().all(|entry| entry.part == group[0].part) {
+                Some(group[0].part)
+            } else {
+                None
+            };
+            let puzzle = registry::find(year, group[0].day).unwrap();
+            let source = Source::Path(group[0].input.clone());
+            runner::run_until(puzzle, &source, part, &Token::with_timeout(timeout))
+        },
+        |group, result| {
+            for &entry in group {
+                let outcome = match &result {
+                    Ok(Ok(result)) => {
+                        let actual = match entry.part {
+                            Part::One => &result.part_one,
+                            Part::Two => &result.part_two,
+                        };
+                        let actual = actual.as_ref().unwrap().answer.to_string();
+                        if actual == entry.answer {
+                            Outcome::Pass
+                        } else {
+                            Outcome::Mismatch { actual }
+                        }
+                    }
+                    Ok(Err(e)) => Outcome::Fail(e.report()),
+                    Err(message) => Outcome::Fail(format!("error: panicked: {}\n", message)),
+                };
+                checks.push(Check { entry, outcome });
+            }
+        },
+    );
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let manifest = Manifest::parse(
+            "# comment\n\n1 1 day01.txt 805731\n25 2  day25.txt  Merry Christmas!\n",
+            Path::new("data"),
+        )
+        .unwrap();
+        assert_eq!(
+            manifest.entries,
+            vec![
+                Entry {
+                    day: 1,
+                    part: Part::One,
+                    input: PathBuf::from("data/day01.txt"),
+                    answer: "805731".to_string()
+                },
+                Entry {
+                    day: 25,
+                    part: Part::Two,
+                    input: PathBuf::from("data/day25.txt"),
+                    answer: "Merry Christmas!".to_string()
+                }
+            ]
+        );
+
+        let error = Manifest::parse("1 1 a.txt 5\n1 3 a.txt 5", Path::new("")).unwrap_err();
+        assert_eq!(error.line(), Some(2));
+        assert_eq!(error.column(), Some(3));
+        assert!(Manifest::parse("1 1 a.txt", Path::new("")).is_err());
+    }
+
+    #[test]
+    fn test_default_manifest() {
+        let manifest = Manifest::load(&default_path(2020)).unwrap();
+        assert_eq!(manifest.entries.len(), 50);
+        assert!(manifest.entries.iter().all(|entry| entry.input.exists()));
+    }
+
+    #[test]
+    fn test_verify() {
+        let manifest = Manifest::parse(
+            "1 1 day01.txt 805731\n1 2 day01.txt 5\n2 1 missing.txt 469",
+            Path::new("data"),
+        )
+        .unwrap();
+        let checks = verify(&manifest, 2020, &[1, 2], 2, None);
+        assert_eq!(checks[0].outcome, Outcome::Pass);
+        assert_eq!(
+            checks[1].outcome,
+            Outcome::Mismatch {
+                actual: "192684960".to_string()
+            }
+        );
+        assert!(matches!(checks[2].outcome, Outcome::Fail(_)));
+        assert_eq!(manifest.missing(&[1, 2]), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_verify_no_entries() {
+        let manifest = Manifest::parse("1 1 day01.txt 805731\n", Path::new("data")).unwrap();
+        assert_eq!(verify(&manifest, 2020, &[3, 4], 1, None), vec![]);
+        assert_eq!(manifest.missing(&[1, 3, 4]), vec![3, 4]);
+    }
+}
@@ -0,0 +1,113 @@
+//! A small worker pool for running puzzles side by side.
+
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+/// The message a thread panicked with, if it was a string.
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
+/// Calls `f` on each of `items` using up to `jobs` threads.
+///
+/// `report` is called on the calling thread with each result in the same order as
+/// `items`, as soon as that result and all those before it are ready. If `f` panics
+/// the panic is caught and reported as an `Err` holding the panic message, and the
+/// remaining items still run.
+pub fn map_ordered<I, T>(
+    items: &[I],
+    jobs: usize,
+    f: impl Fn(&I) -> T + Sync,
+    mut report: impl FnMut(&I, Result<T, String>),
+) where
+    I: Sync,
+    T: Send,
+{
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, items.len().max(1)) {
+            let sender = sender.clone();
+            let (next, f) = (&next, &f);
+            scope.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(i) else {
+                    break;
+                };
+                let result = panic::catch_unwind(AssertUnwindSafe(|| f(item)))
+                    .map_err(|payload| panic_message(payload.as_ref()));
+                if sender.send((i, result)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        let mut pending: Vec<Option<Result<T, String>>> = items.iter().map(|_| None).collect();
+        let mut reported = 0;
+        for (i, result) in receiver {
+            pending[i] = Some(result);
+            while let Some(result) = pending.get_mut(reported).and_then(Option::take) {
+                report(&items[reported], result);
+                reported += 1;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_map_ordered() {
+        let items: Vec<u64> = (0..20).collect();
+        let mut results = Vec::new();
+        map_ordered(
+            &items,
+            4,
+            |&i| {
+                // Finish out of order.
+                thread::sleep(Duration::from_millis((20 - i) % 7));
+                i * 2
+            },
+            |&i, result| results.push((i, result)),
+        );
+        let expected: Vec<(u64, Result<u64, String>)> = (0..20).map(|i| (i, Ok(i * 2))).collect();
+        assert_eq!(results, expected);
+    }
+
+    #[test]
+    fn test_map_ordered_isolates_panics() {
+        let mut results = Vec::new();
+        map_ordered(
+            &[1, 0, 3],
+            2,
+            |&i| {
+                if i == 0 {
+                    panic!("Day {} exploded", i);
+                }
+                i
+            },
+            |_, result| results.push(result),
+        );
+        assert_eq!(
+            results,
+            vec![Ok(1), Err("Day 0 exploded".to_string()), Ok(3)]
+        );
+    }
+}
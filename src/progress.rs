@@ -0,0 +1,227 @@
+//! Progress through long running solvers.
+//!
+//! Solvers call [`report`] as they go, which does nothing unless the thread is
+//! [running](run) with a callback. Calls are thinned out to about a thousand per
+//! loop before the callback sees them, so it is fine to report every iteration.
+//! [`Line`] draws the updates as a progress line with a rate and an ETA.
+
+use std::{
+    cell::{Cell, RefCell},
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+/// How far a solver has got.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Update {
+    pub done: usize,
+    /// How many there will be in all, if the solver knows.
+    pub total: Option<usize>,
+}
+
+type Callback = Box<dyn FnMut(Update)>;
+
+thread_local! {
+    static CALLBACK: RefCell<Option<Callback>> = const { RefCell::new(None) };
+    /// Whether there is a callback, which is quicker to check than the callback.
+    static ACTIVE: Cell<bool> = const { Cell::new(false) };
+    /// The count at which the callback is next called.
+    static NEXT: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Reports that `done` out of `total` steps have been done.
+pub fn report(done: usize, total: Option<usize>) {
+    if !ACTIVE.get() {
+        return;
+    }
+    let step = total.map_or(1, |total| (total / 1000).max(1));
+    let next = NEXT.get();
+    // A count below the last one reported means a new loop has started.
+    let restarted = done + step < next;
+    if done >= next || restarted || Some(done) == total {
+        NEXT.set(done + step);
+        CALLBACK.with(|callback| {
+            if let Some(callback) = callback.borrow_mut().as_mut() {
+                callback(Update { done, total });
+            }
+        });
+    }
+}
+
+/// Puts the previous callback back, even when unwinding.
+struct Restore(Option<Callback>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        ACTIVE.set(previous.is_some());
+        NEXT.set(0);
+        let _ = CALLBACK.try_with(|callback| *callback.borrow_mut() = previous);
+    }
+}
+
+/// Runs `f`, sending any progress it reports on this thread to `callback`.
+pub fn run<T>(callback: impl FnMut(Update) + 'static, f: impl FnOnce() -> T) -> T {
+    let previous = CALLBACK.with(|current| current.replace(Some(Box::new(callback))));
+    ACTIVE.set(true);
+    NEXT.set(0);
+    let _restore = Restore(previous);
+    f()
+}
+
+/// Formats a count with a metric suffix.
+fn count(n: f64) -> String {
+    if n >= 1e6 {
+        format!("{:.1}M", n / 1e6)
+    } else if n >= 1e3 {
+        format!("{:.1}k", n / 1e3)
+    } else {
+        format!("{:.0}", n)
+    }
+}
+
+/// Describes an update which came `elapsed` after the loop started.
+pub fn describe(update: Update, elapsed: Duration) -> String {
+    let rate = update.done as f64 / elapsed.as_secs_f64().max(1e-9);
+    match update.total {
+        Some(total) if total > 0 => {
+            let mut text = format!(
+                "{:.1}% ({}/{}), {}/s",
+                update.done as f64 * 100.0 / total as f64,
+                count(update.done as f64),
+                count(total as f64),
+                count(rate)
+            );
+            if update.done > 0 {
+                let left = total.saturating_sub(update.done) as f64 / rate;
+                text += &format!(", ETA {:.0}s", left.ceil());
+            }
+            text
+        }
+        _ => format!("{}, {}/s", count(update.done as f64), count(rate)),
+    }
+}
+
+/// A callback which keeps a single line on stderr up to date, at most ten times a
+/// second.
+pub struct Line {
+    label: String,
+    start: Instant,
+    last_done: usize,
+    drawn: Option<Instant>,
+}
+
+impl Line {
+    pub fn new(label: impl Into<String>) -> Self {
+        Line {
+            label: label.into(),
+            start: Instant::now(),
+            last_done: 0,
+            drawn: None,
+        }
+    }
+
+    pub fn update(&mut self, update: Update) {
+        let now = Instant::now();
+        if update.done < self.last_done {
+            self.start = now;
+        }
+        self.last_done = update.done;
+        let finished = update.total == Some(update.done);
+        if !finished
+            && self
+                .drawn
+                .is_some_and(|drawn| now - drawn < Duration::from_millis(100))
+        {
+            return;
+        }
+        self.drawn = Some(now);
+        let mut stderr = io::stderr().lock();
+        let _ = write!(
+            stderr,
+            "\r\x1b[2K{}: {}",
+            self.label,
+            describe(update, now - self.start)
+        );
+        let _ = stderr.flush();
+    }
+
+    /// Removes any line drawn so far.
+    pub fn clear() {
+        eprint!("\r\x1b[2K");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_run() {
+        let updates = Rc::new(RefCell::new(Vec::new()));
+        let seen = Rc::clone(&updates);
+        let total = run(
+            move |update| seen.borrow_mut().push(update),
+            || {
+                for i in 0..=100_000 {
+                    report(i, Some(100_000));
+                }
+                for i in 0..20 {
+                    report(i, None);
+                }
+                100_000
+            },
+        );
+        assert_eq!(total, 100_000);
+        let updates = updates.borrow();
+        assert_eq!(updates.len(), 1001 + 20);
+        assert_eq!(
+            updates[..2],
+            [
+                Update {
+                    done: 0,
+                    total: Some(100_000)
+                },
+                Update {
+                    done: 100,
+                    total: Some(100_000)
+                }
+            ]
+        );
+        assert_eq!(
+            updates[1000],
+            Update {
+                done: 100_000,
+                total: Some(100_000)
+            }
+        );
+        assert_eq!(
+            updates[1001],
+            Update {
+                done: 0,
+                total: None
+            }
+        );
+        // Nothing is listening any more.
+        report(5, None);
+        assert_eq!(updates.len(), 1021);
+    }
+
+    #[test]
+    fn test_describe() {
+        let update = Update {
+            done: 7_500_000,
+            total: Some(30_000_000),
+        };
+        assert_eq!(
+            describe(update, Duration::from_secs(2)),
+            "25.0% (7.5M/30.0M), 3.8M/s, ETA 6s"
+        );
+        let update = Update {
+            done: 57,
+            total: None,
+        };
+        assert_eq!(describe(update, Duration::from_secs(1)), "57, 57/s");
+    }
+}
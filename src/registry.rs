@@ -0,0 +1,90 @@
+use std::path::{Path, PathBuf};
+
+use crate::solution::Puzzle;
+use crate::*;
+
+/// The year of the puzzles that commands work with unless told otherwise.
+pub const DEFAULT_YEAR: usize = 2020;
+
+/// Every implemented day, in order of year then day.
+static PUZZLES: &[&dyn Puzzle] = &[
+    &day01::Day01,
+    &day02::Day02,
+    &day03::Day03,
+    &day04::Day04,
+    &day05::Day05,
+    &day06::Day06,
+    &day07::Day07,
+    &day08::Day08,
+    &day09::Day09,
+    &day10::Day10,
+    &day11::Day11,
+    &day12::Day12,
+    &day13::Day13,
+    &day14::Day14,
+    &day15::Day15,
+    &day16::Day16,
+    &day17::Day17,
+    &day18::Day18,
+    &day19::Day19,
+    &day20::Day20,
+    &day21::Day21,
+    &day22::Day22,
+    &day23::Day23,
+    &day24::Day24,
+    &day25::Day25,
+];
+
+/// All implemented puzzles ordered by year and day.
+pub fn all() -> impl Iterator<Item = &'static dyn Puzzle> {
+    PUZZLES.iter().copied()
+}
+
+/// The implemented puzzles from one year, in order.
+pub fn year(year: usize) -> impl Iterator<Item = &'static dyn Puzzle> {
+    all().filter(move |puzzle| puzzle.year() == year)
+}
+
+/// Looks up a single day of the [default year](DEFAULT_YEAR).
+pub fn get(day: usize) -> Option<&'static dyn Puzzle> {
+    find(DEFAULT_YEAR, day)
+}
+
+/// Looks up a single day of any year.
+pub fn find(year: usize, day: usize) -> Option<&'static dyn Puzzle> {
+    all().find(|puzzle| puzzle.year() == year && puzzle.day() == day)
+}
+
+/// Where the inputs for a year live. The 2020 inputs came first and sit directly in
+/// `data`, later years each have a directory of their own.
+pub fn data_dir(year: usize) -> PathBuf {
+    if year == DEFAULT_YEAR {
+        PathBuf::from("data")
+    } else {
+        Path::new("data").join(year.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_days_registered() {
+        let days: Vec<usize> = year(2020).map(|puzzle| puzzle.day()).collect();
+        assert_eq!(days, (1..=25).collect::<Vec<_>>());
+        let order: Vec<(usize, usize)> = all().map(|p| (p.year(), p.day())).collect();
+        assert!(order.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_get() {
+        let puzzle = get(20).unwrap();
+        assert_eq!(puzzle.name(), "Jurassic Jigsaw");
+        assert_eq!(puzzle.input_path(), std::path::Path::new("data/day20.txt"));
+        assert!(get(26).is_none());
+        assert_eq!(find(2020, 20).unwrap().name(), "Jurassic Jigsaw");
+        assert!(find(2019, 20).is_none());
+        assert_eq!(data_dir(2021), std::path::Path::new("data/2021"));
+    }
+}
@@ -0,0 +1,149 @@
+//! Writing the results of running days as a Markdown table, used by `main report`.
+//!
+//! The table has a row for each day with its title linking to the puzzle, both
+//! answers, the time taken by each phase, and the most memory any phase held when
+//! [allocations are counted](crate::alloc). Answers can be redacted, for sharing
+//! timings without giving away solutions.
+
+use std::{fmt::Write, time::Duration};
+
+use crate::{
+    alloc,
+    fetch::DEFAULT_BASE_URL,
+    runner::{DayResult, PartResult},
+};
+
+/// Makes `text` safe to put in a table cell, which is a single line.
+fn escape(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+fn answer(part: &Option<PartResult>, redact: bool) -> String {
+    match part {
+        Some(_) if redact => "*redacted*".to_string(),
+        Some(part) => escape(&part.answer.to_string()),
+        None => "-".to_string(),
+    }
+}
+
+fn time(part: &Option<PartResult>) -> String {
+    part.as_ref()
+        .map_or_else(|| "-".to_string(), |part| format!("{:.2?}", part.elapsed))
+}
+
+/// The most memory held by any phase of a day, if it was counted.
+fn peak(result: &DayResult) -> Option<usize> {
+    [&result.part_one, &result.part_two]
+        .into_iter()
+        .flatten()
+        .map(|part| part.memory)
+        .chain([result.parse_memory])
+        .map(|memory| memory.map(|memory| memory.peak))
+        .max()
+        .flatten()
+}
+
+/// A Markdown table of `results` for days of `year`, with the answers replaced if
+/// `redact` is set. The memory column is left out unless memory was counted.
+pub fn markdown(year: usize, results: &[DayResult], redact: bool) -> String {
+    let memory = results.iter().any(|result| peak(result).is_some());
+    let mut table =
+        String::from("| Day | Puzzle | Part One | Part Two | Parse | Solve One | Solve Two |");
+    let mut rule =
+        String::from("|----:|--------|----------|----------|------:|----------:|----------:|");
+    if memory {
+        table += " Peak Memory |";
+        rule += "------------:|";
+    }
+    table.push('\n');
+    table += &rule;
+    table.push('\n');
+    for result in results {
+        write!(
+            table,
+            "| {} | [{}]({}/{}/day/{}) | {} | {} | {:.2?} | {} | {} |",
+            result.day,
+            escape(result.name),
+            DEFAULT_BASE_URL,
+            year,
+            result.day,
+            answer(&result.part_one, redact),
+            answer(&result.part_two, redact),
+            result.parse_time,
+            time(&result.part_one),
+            time(&result.part_two)
+        )
+        .unwrap();
+        if memory {
+            let peak = peak(result).map_or_else(|| "-".to_string(), alloc::bytes);
+            write!(table, " {} |", peak).unwrap();
+        }
+        table.push('\n');
+    }
+    let total: Duration = results
+        .iter()
+        .map(|result| {
+            result.parse_time
+                + [&result.part_one, &result.part_two]
+                    .into_iter()
+                    .flatten()
+                    .map(|part| part.elapsed)
+                    .sum::<Duration>()
+        })
+        .sum();
+    writeln!(table, "\nTotal time: {:.2?}", total).unwrap();
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{alloc::Usage, Answer};
+
+    fn result() -> DayResult {
+        DayResult {
+            day: 21,
+            name: "Allergen Assessment",
+            input: "data/day21.txt".to_string(),
+            input_hash: 0,
+            parse_time: Duration::from_micros(3),
+            parse_memory: None,
+            part_one: Some(PartResult {
+                answer: Answer::Number(5),
+                elapsed: Duration::from_millis(2),
+                memory: None,
+                cached: false,
+            }),
+            part_two: Some(PartResult {
+                answer: Answer::Grid(vec!["#|".to_string(), ".#".to_string()]),
+                elapsed: Duration::from_millis(1),
+                memory: None,
+                cached: false,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_markdown() {
+        assert_eq!(
+            markdown(2020, &[result()], false),
+            "| Day | Puzzle | Part One | Part Two | Parse | Solve One | Solve Two |\n\
+             |----:|--------|----------|----------|------:|----------:|----------:|\n\
+             | 21 | [Allergen Assessment](https://adventofcode.com/2020/day/21) | 5 | \
+             #\\|<br>.# | 3.00µs | 2.00ms | 1.00ms |\n\
+             \n\
+             Total time: 3.00ms\n"
+        );
+        let mut result = result();
+        result.part_two = None;
+        result.parse_memory = Some(Usage {
+            peak: 2048,
+            total: 4096,
+        });
+        let table = markdown(2020, &[result], true);
+        let lines: Vec<_> = table.lines().collect();
+        assert!(lines[0].ends_with("| Solve Two | Peak Memory |"));
+        assert!(lines[1].ends_with("|------------:|"));
+        assert!(lines[2].ends_with("| *redacted* | - | 3.00µs | 2.00ms | - | 2.0 KiB |"));
+    }
+}
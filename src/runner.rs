@@ -0,0 +1,487 @@
+//! Solving a puzzle and presenting the results.
+
+use std::{
+    fmt::Write,
+    io::{self, Cursor, Read},
+    time::Duration,
+};
+
+use crate::{
+    alloc::{self, Usage},
+    bench::timed,
+    cache::{Cache, Cached, Key},
+    cancel::{Cancelled, Token},
+    cli::{Part, Source},
+    input, Answer, ParseError, Puzzle,
+};
+
+/// The answer to one part and how long it took to find.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartResult {
+    pub answer: Answer,
+    pub elapsed: Duration,
+    /// What solving allocated, when [counting allocations](alloc).
+    pub memory: Option<Usage>,
+    /// Whether the answer came from the [cache](crate::cache), in which case the time
+    /// is how long it took when it was cached.
+    pub cached: bool,
+}
+
+/// Everything found by running one day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayResult {
+    pub day: usize,
+    pub name: &'static str,
+    /// Where the input was read from.
+    pub input: String,
+    /// The [hash](input::hash) of the input.
+    pub input_hash: u64,
+    pub parse_time: Duration,
+    pub parse_memory: Option<Usage>,
+    pub part_one: Option<PartResult>,
+    pub part_two: Option<PartResult>,
+}
+
+/// Why a day could not be run.
+#[derive(Debug)]
+pub enum RunError {
+    Io {
+        input: String,
+        error: io::Error,
+    },
+    Parse {
+        input: String,
+        error: ParseError,
+    },
+    /// The [token](Token) was cancelled while parsing, or while solving a part.
+    TimedOut {
+        input: String,
+        part: Option<Part>,
+        /// Whether the solver gave up when it saw the token. Parsing and days that never
+        /// look at it run to the end however long they take.
+        stopped: bool,
+    },
+}
+
+impl RunError {
+    /// A diagnostic suitable for printing to stderr.
+    pub fn report(&self) -> String {
+        match self {
+            RunError::Io { input, error } => {
+                format!("error: Could not read {}: {}\n", input, error)
+            }
+            RunError::Parse { input, error } => error.report(input),
+            RunError::TimedOut {
+                input,
+                part,
+                stopped,
+            } => {
+                let phase = match part {
+                    None => "parsing",
+                    Some(Part::One) => "solving part one of",
+                    Some(Part::Two) => "solving part two of",
+                };
+                let mut report = format!("error: Timed out {} {}\n", phase, input);
+                if !stopped {
+                    let what = if part.is_some() {
+                        "This part"
+                    } else {
+                        "Parsing"
+                    };
+                    report += &format!(
+                        "note: {} can not be stopped early, so it ran to the end\n",
+                        what
+                    );
+                }
+                report
+            }
+        }
+    }
+}
+
+/// Reads the input for `puzzle` from `source` and solves the requested parts, or both
+/// if `part` is `None`.
+pub fn run(
+    puzzle: &dyn Puzzle,
+    source: &Source,
+    part: Option<Part>,
+) -> Result<DayResult, RunError> {
+    run_until(puzzle, source, part, &Token::new())
+}
+
+/// Like [`run`], but gives up once `token` is cancelled.
+pub fn run_until(
+    puzzle: &dyn Puzzle,
+    source: &Source,
+    part: Option<Part>,
+    token: &Token,
+) -> Result<DayResult, RunError> {
+    run_cached(puzzle, source, part, token, None)
+}
+
+/// Like [`run_until`], but takes the answers from `cache` instead when every part
+/// asked for is in it. Nothing is parsed or solved then.
+pub fn run_cached(
+    puzzle: &dyn Puzzle,
+    source: &Source,
+    part: Option<Part>,
+    token: &Token,
+    cache: Option<&Cache>,
+) -> Result<DayResult, RunError> {
+    let input = source.describe(puzzle);
+    let bytes = source
+        .open(puzzle)
+        .and_then(|mut reader| {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes)?;
+            Ok(bytes)
+        })
+        .map_err(|error| RunError::Io {
+            input: input.clone(),
+            error,
+        })?;
+    let input_hash = input::hash(&bytes);
+    if let Some(cache) = cache {
+        let cached = |this: Part| {
+            let key = Key {
+                year: puzzle.year(),
+                day: puzzle.day(),
+                part: this,
+                input_hash,
+            };
+            match part {
+                Some(part) if part != this => Some(None),
+                _ => cache.get(&key).map(Some),
+            }
+        };
+        if let (Some(one), Some(two)) = (cached(Part::One), cached(Part::Two)) {
+            let part = |cached: Option<&Cached>| {
+                cached.map(|cached| PartResult {
+                    answer: cached.answer.clone(),
+                    elapsed: cached.elapsed,
+                    memory: None,
+                    cached: true,
+                })
+            };
+            return Ok(DayResult {
+                day: puzzle.day(),
+                name: puzzle.name(),
+                input,
+                input_hash,
+                parse_time: one
+                    .or(two)
+                    .map_or(Duration::ZERO, |cached| cached.parse_time),
+                parse_memory: None,
+                part_one: part(one),
+                part_two: part(two),
+            });
+        }
+    }
+    let timed_out = |part, stopped| RunError::TimedOut {
+        input: input.clone(),
+        part,
+        stopped,
+    };
+    let ((parsed, parse_memory), parse_time) =
+        timed(|| alloc::measured(|| puzzle.parse(&mut Cursor::new(&bytes))));
+    let parsed = parsed.map_err(|error| RunError::Parse {
+        input: input.clone(),
+        error,
+    })?;
+    if token.is_cancelled() {
+        return Err(timed_out(None, false));
+    }
+    let solve = |this: Part, f: &dyn Fn() -> Result<Answer, Cancelled>| {
+        if part.is_some_and(|part| part != this) {
+            return Ok(None);
+        }
+        let ((answer, memory), elapsed) = timed(|| alloc::measured(f));
+        let answer = match answer {
+            Err(Cancelled) => return Err(timed_out(Some(this), true)),
+            Ok(_) if token.is_cancelled() => return Err(timed_out(Some(this), false)),
+            Ok(answer) => answer,
+        };
+        Ok(Some(PartResult {
+            answer,
+            elapsed,
+            memory,
+            cached: false,
+        }))
+    };
+    Ok(DayResult {
+        day: puzzle.day(),
+        name: puzzle.name(),
+        input: input.clone(),
+        input_hash,
+        parse_time,
+        parse_memory,
+        part_one: solve(Part::One, &|| parsed.part_one_until(token))?,
+        part_two: solve(Part::Two, &|| parsed.part_two_until(token))?,
+    })
+}
+
+/// Quotes `s` as a JSON string.
+fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// The memory used by a phase for text output, if it was counted.
+fn memory_text(memory: &Option<Usage>) -> String {
+    memory
+        .map(|memory| format!(" [{}]", memory))
+        .unwrap_or_default()
+}
+
+/// The memory used by a phase as extra JSON fields, if it was counted.
+fn memory_json(key: &str, memory: &Option<Usage>) -> String {
+    memory
+        .map(|memory| {
+            format!(
+                ",\"{}\":{{\"peak_bytes\":{},\"total_bytes\":{}}}",
+                key, memory.peak, memory.total
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// Marks a time for text output as coming from the cache.
+fn cached_text(cached: bool) -> &'static str {
+    if cached {
+        ", cached"
+    } else {
+        ""
+    }
+}
+
+impl DayResult {
+    /// Whether the answers came from the [cache](crate::cache) rather than being
+    /// solved.
+    pub fn is_cached(&self) -> bool {
+        [&self.part_one, &self.part_two]
+            .into_iter()
+            .flatten()
+            .any(|part| part.cached)
+    }
+
+    /// The human readable form printed by the runner.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("Day {}: {}\n", self.day, self.name);
+        writeln!(
+            text,
+            "Parsed in {:?}{}{}",
+            self.parse_time,
+            if self.is_cached() { " (cached)" } else { "" },
+            memory_text(&self.parse_memory)
+        )
+        .unwrap();
+        for (label, part) in [("Star One", &self.part_one), ("Star Two", &self.part_two)] {
+            match part {
+                Some(PartResult {
+                    answer,
+                    elapsed,
+                    memory,
+                    cached,
+                }) if answer.is_multiline() => writeln!(
+                    text,
+                    "{} ({:?}{}){}:\n{}",
+                    label,
+                    elapsed,
+                    cached_text(*cached),
+                    memory_text(memory),
+                    answer
+                )
+                .unwrap(),
+                Some(PartResult {
+                    answer,
+                    elapsed,
+                    memory,
+                    cached,
+                }) => writeln!(
+                    text,
+                    "{}: {} ({:?}{}){}",
+                    label,
+                    answer,
+                    elapsed,
+                    cached_text(*cached),
+                    memory_text(memory)
+                )
+                .unwrap(),
+                None => {}
+            }
+        }
+        text
+    }
+
+    /// A single line JSON object, with answers as strings and times in nanoseconds.
+    /// Memory is only included when it was counted, and whether an answer was cached
+    /// only when it was.
+    pub fn to_json(&self) -> String {
+        let part = |part: &Option<PartResult>| match part {
+            Some(PartResult {
+                answer,
+                elapsed,
+                memory,
+                cached,
+            }) => format!(
+                "{{\"answer\":{},\"time_ns\":{}{}{}}}",
+                json_string(&answer.to_string()),
+                elapsed.as_nanos(),
+                memory_json("memory", memory),
+                if *cached { ",\"cached\":true" } else { "" }
+            ),
+            None => "null".to_string(),
+        };
+        format!(
+            "{{\"day\":{},\"name\":{},\"input\":{},\"input_hash\":\"{:016x}\",\"parse_time_ns\":{}{},\"part_one\":{},\"part_two\":{}}}",
+            self.day,
+            json_string(self.name),
+            json_string(&self.input),
+            self.input_hash,
+            self.parse_time.as_nanos(),
+            memory_json("parse_memory", &self.parse_memory),
+            part(&self.part_one),
+            part(&self.part_two)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("a,b"), "\"a,b\"");
+        assert_eq!(json_string("#.\n\"x\"\\"), "\"#.\\n\\\"x\\\"\\\\\"");
+        assert_eq!(json_string("\u{1}"), "\"\\u0001\"");
+    }
+
+    #[test]
+    fn test_run_until() {
+        let puzzle = crate::registry::get(15).unwrap();
+        let token = Token::new();
+        let canceller = token.clone();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            canceller.cancel();
+        });
+        let error = run_until(puzzle, &Source::Default, Some(Part::Two), &token).unwrap_err();
+        handle.join().unwrap();
+        assert!(matches!(
+            error,
+            RunError::TimedOut {
+                part: Some(Part::Two),
+                stopped: true,
+                ..
+            }
+        ));
+        assert_eq!(
+            error.report(),
+            "error: Timed out solving part two of data/day15.txt\n"
+        );
+
+        let error = run_until(puzzle, &Source::Default, None, &token).unwrap_err();
+        assert_eq!(
+            error.report(),
+            "error: Timed out parsing data/day15.txt\n\
+             note: Parsing can not be stopped early, so it ran to the end\n"
+        );
+    }
+
+    #[test]
+    fn test_run_cached() {
+        let puzzle = crate::registry::get(1).unwrap();
+        let path = std::env::temp_dir().join(format!("run-cached-{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut cache = Cache::load(&path).unwrap();
+        let token = Token::new();
+        let result = run_cached(puzzle, &Source::Default, None, &token, Some(&cache)).unwrap();
+        assert!(!result.is_cached());
+        // A wrong answer shows that the cache is used rather than the day solved again.
+        let wrong = DayResult {
+            part_one: Some(PartResult {
+                answer: Answer::Number(5),
+                ..result.part_one.clone().unwrap()
+            }),
+            ..result.clone()
+        };
+        cache.store(2020, &wrong).unwrap();
+        let cached = run_cached(puzzle, &Source::Default, None, &token, Some(&cache)).unwrap();
+        assert!(cached.is_cached());
+        assert_eq!(cached.part_one.unwrap().answer, Answer::Number(5));
+        assert_eq!(cached.part_two.unwrap().answer, Answer::Number(192684960));
+        let part = run_cached(
+            puzzle,
+            &Source::Default,
+            Some(Part::Two),
+            &token,
+            Some(&cache),
+        );
+        assert!(part.unwrap().part_one.is_none());
+        let uncached = run_until(puzzle, &Source::Default, Some(Part::One), &token).unwrap();
+        assert_eq!(uncached.part_one.unwrap().answer, Answer::Number(805731));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_to_json() {
+        let result = DayResult {
+            day: 21,
+            name: "Allergen Assessment",
+            input: "data/day21.txt".to_string(),
+            input_hash: 0xab,
+            parse_time: Duration::from_micros(3),
+            parse_memory: None,
+            part_one: Some(PartResult {
+                answer: Answer::Number(5),
+                elapsed: Duration::from_nanos(70),
+                memory: None,
+                cached: false,
+            }),
+            part_two: None,
+        };
+        assert_eq!(
+            result.to_json(),
+            r#"{"day":21,"name":"Allergen Assessment","input":"data/day21.txt","input_hash":"00000000000000ab","parse_time_ns":3000,"part_one":{"answer":"5","time_ns":70},"part_two":null}"#
+        );
+
+        let memory = Some(Usage {
+            peak: 1024,
+            total: 4096,
+        });
+        let result = DayResult {
+            parse_memory: memory,
+            part_one: Some(PartResult {
+                answer: Answer::Number(5),
+                elapsed: Duration::from_nanos(70),
+                memory,
+                cached: false,
+            }),
+            ..result
+        };
+        assert_eq!(
+            result.to_json(),
+            r#"{"day":21,"name":"Allergen Assessment","input":"data/day21.txt","input_hash":"00000000000000ab","parse_time_ns":3000,"parse_memory":{"peak_bytes":1024,"total_bytes":4096},"part_one":{"answer":"5","time_ns":70,"memory":{"peak_bytes":1024,"total_bytes":4096}},"part_two":null}"#
+        );
+        assert_eq!(
+            result.to_text(),
+            "Day 21: Allergen Assessment\n\
+             Parsed in 3µs [peak 1.0 KiB, total 4.0 KiB]\n\
+             Star One: 5 (70ns) [peak 1.0 KiB, total 4.0 KiB]\n"
+        );
+    }
+}
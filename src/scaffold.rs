@@ -0,0 +1,323 @@
+//! Skeletons for new days, used by `main new`.
+//!
+//! Days from the [default year](registry::DEFAULT_YEAR) live in `src/dayNN.rs` and
+//! read `data/dayNN.txt`. Days from other years live in `src/yYYYY/dayNN.rs` and
+//! read `data/YYYY/dayNN.txt`. Besides the module itself a day needs a `mod`
+//! declaration, an entry in the [registry], a `_complete` test in `lib.rs` and a file
+//! for its input, which are all added here.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use regex::Regex;
+
+use crate::registry;
+
+lazy_static! {
+    static ref MODULE: Regex =
+        Regex::new(r"^pub mod (?:day(?P<day>\d{2})|y(?P<year>\d{4}));$").unwrap();
+    static ref ENTRY: Regex =
+        Regex::new(r"^&(?:y(?P<year>\d{4})::)?day(?P<day>\d{2})::Day\d{2},$").unwrap();
+}
+
+/// Where a line from `lib.rs` or a year's `mod.rs` sorts among the other modules,
+/// with a year's module coming before any of its days.
+fn module_key(line: &str) -> Option<(usize, usize)> {
+    let captures = MODULE.captures(line.trim())?;
+    Some(match (captures.name("year"), captures.name("day")) {
+        (Some(year), _) => (year.as_str().parse().unwrap(), 0),
+        (_, Some(day)) => (registry::DEFAULT_YEAR, day.as_str().parse().unwrap()),
+        _ => unreachable!(),
+    })
+}
+
+/// Where a line from the registry sorts among the other entries.
+fn entry_key(line: &str) -> Option<(usize, usize)> {
+    let captures = ENTRY.captures(line.trim())?;
+    let year = captures
+        .name("year")
+        .map_or(registry::DEFAULT_YEAR, |year| {
+            year.as_str().parse().unwrap()
+        });
+    Some((year, captures["day"].parse().unwrap()))
+}
+
+/// Inserts `line` among the lines that `key` recognises, keeping them in order.
+fn insert_ordered(
+    text: &str,
+    line: &str,
+    key: fn(&str) -> Option<(usize, usize)>,
+) -> Result<String, String> {
+    let new_key = key(line).expect("The line being inserted should have a key");
+    let mut lines: Vec<&str> = text.lines().collect();
+    let keys: Vec<(usize, Option<(usize, usize)>)> =
+        lines.iter().map(|line| key(line)).enumerate().collect();
+    let position = match keys.iter().find(|(_, k)| k.is_some_and(|k| k > new_key)) {
+        Some((i, _)) => *i,
+        None => match keys.iter().rev().find(|(_, k)| k.is_some()) {
+            Some((i, _)) => i + 1,
+            None => return Err(format!("Could not find where to add {:?}", line.trim())),
+        },
+    };
+    lines.insert(position, line);
+    Ok(lines.join("\n") + "\n")
+}
+
+/// The module and type names for a day, such as `y2021::day01` and `Day01`.
+fn names(year: usize, day: usize) -> (String, String) {
+    let module = if year == registry::DEFAULT_YEAR {
+        format!("day{:02}", day)
+    } else {
+        format!("y{}::day{:02}", year, day)
+    };
+    (module, format!("Day{:02}", day))
+}
+
+fn day_module(year: usize, day: usize) -> String {
+    let (_, name) = names(year, day);
+    let year_method = if year == registry::DEFAULT_YEAR {
+        String::new()
+    } else {
+        format!(
+            "
+    fn year(&self) -> usize {{
+        {}
+    }}
+",
+            year
+        )
+    };
+    format!(
+        r#"use std::io::BufRead;
+
+use crate::generate::Rng;
+use crate::input::parse_lines;
+use crate::{{Answer, ParseError, Solution}};
+
+pub fn parse(input: impl BufRead) -> Result<Vec<String>, ParseError> {{
+    parse_lines(input, |line| Ok(line.to_string()))
+}}
+
+pub fn star_one(_input: &[String]) -> usize {{
+    0
+}}
+
+pub fn star_two(_input: &[String]) -> usize {{
+    0
+}}
+
+/// Generates `size` lines of random words.
+pub fn generate(rng: &mut Rng, size: usize) -> String {{
+    (0..size).map(|_| rng.word(1..10) + "\n").collect()
+}}
+
+pub struct {name};
+
+impl Solution for {name} {{
+    type Input = Vec<String>;
+
+    fn name(&self) -> &'static str {{
+        "Untitled"
+    }}
+
+    fn day(&self) -> usize {{
+        {day}
+    }}
+{year_method}
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError> {{
+        parse(input)
+    }}
+
+    fn part_one(&self, input: &Self::Input) -> Answer {{
+        star_one(input).into()
+    }}
+
+    fn part_two(&self, input: &Self::Input) -> Answer {{
+        star_two(input).into()
+    }}
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {{
+        generate(rng, size.unwrap_or(100))
+    }}
+}}
+
+#[cfg(test)]
+mod tests {{
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    #[ignore = "needs the example from the puzzle"]
+    fn test_star_one() {{
+        let input = b"";
+        assert_eq!(star_one(&parse(Cursor::new(input)).unwrap()), 0);
+    }}
+
+    #[test]
+    #[ignore = "needs the example from the puzzle"]
+    fn test_star_two() {{
+        let input = b"";
+        assert_eq!(star_two(&parse(Cursor::new(input)).unwrap()), 0);
+    }}
+}}
+"#,
+        name = name,
+        day = day,
+        year_method = year_method
+    )
+}
+
+fn complete_test(year: usize, day: usize) -> String {
+    let (name, solve) = if year == registry::DEFAULT_YEAR {
+        (format!("day{:02}", day), format!("solve({})", day))
+    } else {
+        (
+            format!("y{}_day{:02}", year, day),
+            format!("solve_in({}, {})", year, day),
+        )
+    };
+    format!(
+        "
+    #[test]
+    #[ignore = \"needs the answers for the real input\"]
+    fn {}_complete() {{
+        let (star_one, star_two) = {};
+
+        assert_eq!(star_one, Answer::Number(0));
+        assert_eq!(star_two, Answer::Number(0));
+    }}
+",
+        name, solve
+    )
+}
+
+/// Adds the test to the end of the tests in `lib.rs`.
+fn add_complete_test(lib: &str, test: &str) -> Result<String, String> {
+    let end = lib
+        .trim_end()
+        .strip_suffix('}')
+        .ok_or("Could not find the end of the tests in lib.rs")?;
+    Ok(format!("{}{}}}\n", end, test))
+}
+
+/// Adds a skeleton for `day` of `year` to the crate in `root`, returning the files
+/// that were created or changed.
+///
+/// Every edit is worked out before anything is written, so a crate that can not be
+/// edited is left alone. The files are then written one at a time, and one that can
+/// not be written leaves those before it changed.
+pub fn scaffold(root: &Path, year: usize, day: usize) -> Result<Vec<PathBuf>, String> {
+    let read = |path: &Path| {
+        fs::read_to_string(root.join(path))
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))
+    };
+    let (module, name) = names(year, day);
+    let lib_path = PathBuf::from("src/lib.rs");
+    let registry_path = PathBuf::from("src/registry.rs");
+    let module_path = Path::new("src").join(module.replace("::", "/") + ".rs");
+    let data_path = registry::data_dir(year).join(format!("day{:02}.txt", day));
+    if root.join(&module_path).exists() {
+        return Err(format!("{} already exists", module_path.display()));
+    }
+
+    let mut files = vec![(module_path, day_module(year, day))];
+    let mut lib = read(&lib_path)?;
+    if year == registry::DEFAULT_YEAR {
+        lib = insert_ordered(&lib, &format!("pub mod day{:02};", day), module_key)?;
+    } else {
+        let year_path = Path::new("src").join(format!("y{}", year)).join("mod.rs");
+        let day_line = format!("pub mod day{:02};", day);
+        let year_module = if root.join(&year_path).exists() {
+            insert_ordered(&read(&year_path)?, &day_line, module_key)?
+        } else {
+            lib = insert_ordered(&lib, &format!("pub mod y{};", year), module_key)?;
+            format!("//! The puzzles from {}.\n\n{}\n", year, day_line)
+        };
+        files.push((year_path, year_module));
+    }
+    lib = add_complete_test(&lib, &complete_test(year, day))?;
+    files.push((lib_path, lib));
+    let entry = format!("    &{}::{},", module, name);
+    files.push((
+        registry_path.clone(),
+        insert_ordered(&read(&registry_path)?, &entry, entry_key)?,
+    ));
+    if !root.join(&data_path).exists() {
+        files.push((data_path, String::new()));
+    }
+
+    for (path, contents) in &files {
+        let path = root.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Could not create {}: {}", parent.display(), e))?;
+        }
+        fs::write(&path, contents)
+            .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+    }
+    Ok(files.into_iter().map(|(path, _)| path).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_insert_ordered() {
+        let lib = "pub mod cli;\npub mod day01;\npub mod day03;\n\n#[cfg(test)]\n";
+        assert_eq!(
+            insert_ordered(lib, "pub mod day02;", module_key).unwrap(),
+            "pub mod cli;\npub mod day01;\npub mod day02;\npub mod day03;\n\n#[cfg(test)]\n"
+        );
+        assert_eq!(
+            insert_ordered(lib, "pub mod y2021;", module_key).unwrap(),
+            "pub mod cli;\npub mod day01;\npub mod day03;\npub mod y2021;\n\n#[cfg(test)]\n"
+        );
+        let registry = "[\n    &day25::Day25,\n    &y2022::day01::Day01,\n];\n";
+        assert_eq!(
+            insert_ordered(registry, "    &y2021::day07::Day07,", entry_key).unwrap(),
+            "[\n    &day25::Day25,\n    &y2021::day07::Day07,\n    &y2022::day01::Day01,\n];\n"
+        );
+        assert!(insert_ordered("", "pub mod day01;", module_key).is_err());
+    }
+
+    #[test]
+    fn test_scaffold() {
+        let root = env::temp_dir().join(format!("scaffold-{}", std::process::id()));
+        fs::create_dir_all(root.join("src")).unwrap();
+        for file in ["src/lib.rs", "src/registry.rs"] {
+            fs::copy(file, root.join(file)).unwrap();
+        }
+
+        let files = scaffold(&root, 2021, 3).unwrap();
+        assert_eq!(
+            files,
+            [
+                "src/y2021/day03.rs",
+                "src/y2021/mod.rs",
+                "src/lib.rs",
+                "src/registry.rs",
+                "data/2021/day03.txt"
+            ]
+            .map(PathBuf::from)
+        );
+        scaffold(&root, 2021, 1).unwrap();
+        assert!(scaffold(&root, 2021, 1).is_err());
+
+        let read = |path: &str| fs::read_to_string(root.join(path)).unwrap();
+        assert!(read("src/y2021/day03.rs").contains("impl Solution for Day03"));
+        assert_eq!(
+            read("src/y2021/mod.rs"),
+            "//! The puzzles from 2021.\n\npub mod day01;\npub mod day03;\n"
+        );
+        assert!(read("src/lib.rs").contains("pub mod day25;\npub mod y2021;\n"));
+        assert!(read("src/lib.rs").contains("fn y2021_day01_complete() {"));
+        assert!(read("src/registry.rs")
+            .contains("&day25::Day25,\n    &y2021::day01::Day01,\n    &y2021::day03::Day03,\n"));
+        assert_eq!(read("data/2021/day01.txt"), "");
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
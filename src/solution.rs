@@ -0,0 +1,137 @@
+use std::{io::BufRead, path::PathBuf};
+
+use crate::{
+    cancel::{Cancelled, Token},
+    generate::Rng,
+    registry, Answer, ParseError,
+};
+
+/// A single day's puzzle.
+///
+/// Each `dayNN` module implements this on a unit struct which is then listed in the
+/// [registry](crate::registry). Days from years other than the
+/// [default](registry::DEFAULT_YEAR) live in a `yYYYY` module and override `year`.
+pub trait Solution {
+    /// What `parse` turns the puzzle input into, shared by both parts.
+    type Input;
+
+    /// The puzzle title.
+    fn name(&self) -> &'static str;
+
+    fn day(&self) -> usize;
+
+    fn year(&self) -> usize {
+        registry::DEFAULT_YEAR
+    }
+
+    /// Where the puzzle input lives when no other input is given.
+    fn input_path(&self) -> PathBuf {
+        registry::data_dir(self.year()).join(format!("day{:02}.txt", self.day()))
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Self::Input, ParseError>;
+
+    fn part_one(&self, input: &Self::Input) -> Answer;
+
+    fn part_two(&self, input: &Self::Input) -> Answer;
+
+    /// Like `part_one`, but gives up once `token` is [cancelled](crate::cancel). Days
+    /// with long running loops override this to check the token; the rest run to the
+    /// end whatever happens to it.
+    fn part_one_until(&self, input: &Self::Input, token: &Token) -> Result<Answer, Cancelled> {
+        let _ = token;
+        Ok(self.part_one(input))
+    }
+
+    /// Like `part_two`, but gives up once `token` is cancelled, as for `part_one_until`.
+    fn part_two_until(&self, input: &Self::Input, token: &Token) -> Result<Answer, Cancelled> {
+        let _ = token;
+        Ok(self.part_two(input))
+    }
+
+    /// A random input for the puzzle. What `size` controls depends on the day, and
+    /// without one the input is about as big as a real one.
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String;
+}
+
+/// An object safe view of a [`Solution`] so that days with different input types can
+/// be stored side by side.
+pub trait Puzzle: Sync {
+    fn name(&self) -> &'static str;
+
+    fn day(&self) -> usize;
+
+    fn year(&self) -> usize;
+
+    fn input_path(&self) -> PathBuf;
+
+    fn parse<'a>(&'a self, input: &mut dyn BufRead) -> Result<Box<dyn Parsed + 'a>, ParseError>;
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String;
+}
+
+/// Puzzle input that has been parsed and is ready to be solved.
+pub trait Parsed {
+    fn part_one(&self) -> Answer;
+
+    fn part_two(&self) -> Answer;
+
+    fn part_one_until(&self, token: &Token) -> Result<Answer, Cancelled>;
+
+    fn part_two_until(&self, token: &Token) -> Result<Answer, Cancelled>;
+}
+
+struct ParsedInput<'a, S: Solution> {
+    solution: &'a S,
+    input: S::Input,
+}
+
+impl<S: Solution> Parsed for ParsedInput<'_, S> {
+    fn part_one(&self) -> Answer {
+        self.solution.part_one(&self.input)
+    }
+
+    fn part_two(&self) -> Answer {
+        self.solution.part_two(&self.input)
+    }
+
+    fn part_one_until(&self, token: &Token) -> Result<Answer, Cancelled> {
+        self.solution.part_one_until(&self.input, token)
+    }
+
+    fn part_two_until(&self, token: &Token) -> Result<Answer, Cancelled> {
+        self.solution.part_two_until(&self.input, token)
+    }
+}
+
+impl<S> Puzzle for S
+where
+    S: Solution + Sync,
+{
+    fn name(&self) -> &'static str {
+        Solution::name(self)
+    }
+
+    fn day(&self) -> usize {
+        Solution::day(self)
+    }
+
+    fn year(&self) -> usize {
+        Solution::year(self)
+    }
+
+    fn input_path(&self) -> PathBuf {
+        Solution::input_path(self)
+    }
+
+    fn parse<'a>(&'a self, input: &mut dyn BufRead) -> Result<Box<dyn Parsed + 'a>, ParseError> {
+        Ok(Box::new(ParsedInput {
+            solution: self,
+            input: Solution::parse(self, input).map_err(|e| e.with_day(Solution::day(self)))?,
+        }))
+    }
+
+    fn generate(&self, rng: &mut Rng, size: Option<usize>) -> String {
+        Solution::generate(self, rng, size)
+    }
+}
@@ -0,0 +1,480 @@
+//! Sending answers to the site, used by `main submit`.
+//!
+//! Every attempt is appended to a history file, one line each:
+//!
+//! ```text
+//! <unix time> <year> <day> <part> <outcome> <seconds to wait> <answer>
+//! ```
+//!
+//! The history is checked before anything is sent, so that an answer is never sent
+//! twice, an answer on the wrong side of a "too high" or "too low" is never sent at
+//! all, and nothing is sent for a part while the site has asked us to wait.
+
+use std::{
+    fmt::{self, Display},
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    time::Duration,
+};
+
+use crate::{cli::Part, fetch, http, Answer};
+
+/// Where attempts are recorded unless told otherwise.
+pub const DEFAULT_HISTORY: &str = ".cache/submissions.txt";
+
+/// How long the site asks for after a wrong answer, when it does not say.
+const DEFAULT_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// What the site made of an answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Right,
+    Wrong,
+    TooHigh,
+    TooLow,
+    /// Another answer was sent too recently, so this one was not looked at.
+    Wait,
+    /// The part was already solved, or is not unlocked yet.
+    WrongLevel,
+    /// The response was not one we recognise.
+    Unknown,
+}
+
+impl Outcome {
+    const ALL: [Outcome; 7] = [
+        Outcome::Right,
+        Outcome::Wrong,
+        Outcome::TooHigh,
+        Outcome::TooLow,
+        Outcome::Wait,
+        Outcome::WrongLevel,
+        Outcome::Unknown,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Outcome::Right => "right",
+            Outcome::Wrong => "wrong",
+            Outcome::TooHigh => "too-high",
+            Outcome::TooLow => "too-low",
+            Outcome::Wait => "wait",
+            Outcome::WrongLevel => "wrong-level",
+            Outcome::Unknown => "unknown",
+        }
+    }
+
+    fn is_wrong(self) -> bool {
+        matches!(self, Outcome::Wrong | Outcome::TooHigh | Outcome::TooLow)
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Reads a wait such as `one minute`, `5 minutes` or `4m 47s`.
+fn parse_wait(text: &str) -> Option<Duration> {
+    let text = text.replace("one ", "1 ");
+    let mut words = text.split_whitespace().peekable();
+    words.peek()?;
+    let mut seconds = 0;
+    while let Some(word) = words.next() {
+        let digits = word
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(word.len());
+        let number: u64 = word[..digits].parse().ok()?;
+        let unit = match &word[digits..] {
+            "" => words.next()?,
+            unit => unit,
+        };
+        seconds += number
+            * match unit {
+                "h" | "hour" | "hours" => 3600,
+                "m" | "minute" | "minutes" => 60,
+                "s" | "second" | "seconds" => 1,
+                _ => return None,
+            };
+    }
+    Some(Duration::from_secs(seconds))
+}
+
+/// Works out the outcome from the page the site sends back, along with how long it
+/// wants us to wait before trying that part again.
+pub fn parse_response(page: &str) -> (Outcome, Duration) {
+    let lower = page.to_lowercase();
+    let wait = |marker: &str, end: &str| {
+        lower
+            .rfind(marker)
+            .map(|i| &lower[i + marker.len()..])
+            .and_then(|rest| parse_wait(&rest[..rest.find(end)?]))
+    };
+    if page.contains("That's the right answer") {
+        (Outcome::Right, Duration::ZERO)
+    } else if page.contains("You gave an answer too recently") {
+        let wait = wait("you have ", " left to wait").unwrap_or(DEFAULT_COOLDOWN);
+        (Outcome::Wait, wait)
+    } else if page.contains("That's not the right answer") {
+        let outcome = if page.contains("your answer is too high") {
+            Outcome::TooHigh
+        } else if page.contains("your answer is too low") {
+            Outcome::TooLow
+        } else {
+            Outcome::Wrong
+        };
+        let wait = wait("please wait ", " before trying again").unwrap_or(DEFAULT_COOLDOWN);
+        (outcome, wait)
+    } else if page.contains("You don't seem to be solving the right level") {
+        (Outcome::WrongLevel, Duration::ZERO)
+    } else {
+        (Outcome::Unknown, Duration::ZERO)
+    }
+}
+
+/// One line of the history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attempt {
+    /// When it was sent, in seconds since the Unix epoch.
+    pub time: u64,
+    pub year: usize,
+    pub day: usize,
+    pub part: Part,
+    pub outcome: Outcome,
+    pub wait: Duration,
+    pub answer: String,
+}
+
+impl Attempt {
+    fn to_line(&self) -> String {
+        format!(
+            "{} {} {} {} {} {} {}",
+            self.time,
+            self.year,
+            self.day,
+            match self.part {
+                Part::One => 1,
+                Part::Two => 2,
+            },
+            self.outcome,
+            self.wait.as_secs(),
+            self.answer
+        )
+    }
+
+    fn from_line(line: &str) -> Option<Attempt> {
+        let mut fields = line.splitn(7, ' ');
+        let mut next = || fields.next();
+        Some(Attempt {
+            time: next()?.parse().ok()?,
+            year: next()?.parse().ok()?,
+            day: next()?.parse().ok()?,
+            part: match next()? {
+                "1" => Part::One,
+                "2" => Part::Two,
+                _ => return None,
+            },
+            outcome: {
+                let name = next()?;
+                *Outcome::ALL.iter().find(|outcome| outcome.name() == name)?
+            },
+            wait: Duration::from_secs(next()?.parse().ok()?),
+            answer: next()?.to_string(),
+        })
+    }
+}
+
+/// Why an answer was not sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Refusal {
+    AlreadyRight(String),
+    KnownWrong(Attempt),
+    /// A previous answer was too high or too low, and this one is no better.
+    OutOfBounds(Attempt),
+    Cooldown(Duration),
+    /// Answers drawn on a grid have to be read by eye.
+    Multiline,
+}
+
+impl Display for Refusal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Refusal::AlreadyRight(answer) => write!(f, "Already solved with {}", answer),
+            Refusal::KnownWrong(attempt) => {
+                write!(
+                    f,
+                    "{} was already sent and was {}",
+                    attempt.answer, attempt.outcome
+                )
+            }
+            Refusal::OutOfBounds(attempt) => write!(
+                f,
+                "{} was already sent and was {}, so this answer is wrong too",
+                attempt.answer, attempt.outcome
+            ),
+            Refusal::Cooldown(wait) => write!(
+                f,
+                "The site asked us to wait, try again in {}s",
+                wait.as_secs()
+            ),
+            Refusal::Multiline => write!(f, "The answer needs reading and typing in by hand"),
+        }
+    }
+}
+
+/// Sends answers, keeping a history of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submitter {
+    pub base_url: String,
+    pub history: PathBuf,
+    pub session: Option<String>,
+}
+
+impl Submitter {
+    /// A submitter using the given settings, or else the ones [fetching](fetch) uses.
+    pub fn new(base_url: Option<String>, history: Option<PathBuf>) -> Self {
+        let fetcher = fetch::Fetcher::new(base_url, None);
+        Submitter {
+            base_url: fetcher.base_url,
+            history: history.unwrap_or_else(|| PathBuf::from(DEFAULT_HISTORY)),
+            session: fetcher.session,
+        }
+    }
+
+    /// Every attempt so far, skipping lines that cannot be read.
+    pub fn attempts(&self) -> Result<Vec<Attempt>, String> {
+        match fs::read_to_string(&self.history) {
+            Ok(history) => Ok(history.lines().filter_map(Attempt::from_line).collect()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(format!("Could not read {}: {}", self.history.display(), e)),
+        }
+    }
+
+    /// Whether the history rules out sending `answer` at `now`.
+    pub fn check(
+        &self,
+        year: usize,
+        day: usize,
+        part: Part,
+        answer: &Answer,
+        now: u64,
+    ) -> Result<Option<Refusal>, String> {
+        if answer.is_multiline() {
+            return Ok(Some(Refusal::Multiline));
+        }
+        let answer = answer.to_string();
+        let attempts = self.attempts()?;
+        let attempts: Vec<&Attempt> = attempts
+            .iter()
+            .filter(|a| (a.year, a.day, a.part) == (year, day, part))
+            .collect();
+        if let Some(right) = attempts.iter().find(|a| a.outcome == Outcome::Right) {
+            return Ok(Some(Refusal::AlreadyRight(right.answer.clone())));
+        }
+        if let Some(wrong) = attempts
+            .iter()
+            .find(|a| a.outcome.is_wrong() && a.answer == answer)
+        {
+            return Ok(Some(Refusal::KnownWrong((*wrong).clone())));
+        }
+        if let Ok(value) = answer.parse::<u128>() {
+            let bound = attempts.iter().find(|a| match a.answer.parse::<u128>() {
+                Ok(bound) if a.outcome == Outcome::TooHigh => value >= bound,
+                Ok(bound) if a.outcome == Outcome::TooLow => value <= bound,
+                _ => false,
+            });
+            if let Some(bound) = bound {
+                return Ok(Some(Refusal::OutOfBounds((*bound).clone())));
+            }
+        }
+        let until = attempts
+            .iter()
+            .map(|a| a.time + a.wait.as_secs())
+            .max()
+            .unwrap_or(0);
+        if until > now {
+            return Ok(Some(Refusal::Cooldown(Duration::from_secs(until - now))));
+        }
+        Ok(None)
+    }
+
+    /// Sends `answer` unless the history rules it out, recording what happened.
+    pub fn submit(
+        &self,
+        year: usize,
+        day: usize,
+        part: Part,
+        answer: &Answer,
+        now: u64,
+    ) -> Result<Result<Attempt, Refusal>, String> {
+        if let Some(refusal) = self.check(year, day, part, answer, now)? {
+            return Ok(Err(refusal));
+        }
+        let session = self.session.as_deref().ok_or_else(|| {
+            "There is no session token, set AOC_SESSION or write it to the session file".to_string()
+        })?;
+        let url = format!(
+            "{}/{}/day/{}/answer",
+            self.base_url.trim_end_matches('/'),
+            year,
+            day
+        );
+        let level = match part {
+            Part::One => "1",
+            Part::Two => "2",
+        };
+        let answer = answer.to_string();
+        let response = http::post(
+            &url,
+            session,
+            &http::form(&[("level", level), ("answer", &answer)]),
+        )?;
+        if response.status != 200 {
+            return Err(format!(
+                "Submitting to {} gave status {}",
+                url, response.status
+            ));
+        }
+        let (outcome, wait) = parse_response(&response.body);
+        let attempt = Attempt {
+            time: now,
+            year,
+            day,
+            part,
+            outcome,
+            wait,
+            answer,
+        };
+        if let Some(parent) = self.history.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Could not create {}: {}", parent.display(), e))?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.history)
+            .and_then(|mut file| writeln!(file, "{}", attempt.to_line()))
+            .map_err(|e| format!("Could not write {}: {}", self.history.display(), e))?;
+        Ok(Ok(attempt))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    const TOO_HIGH: &str = "<article><p>That's not the right answer; your answer is too high. \
+        If you're stuck, make sure you're using the full input data. Please wait one minute \
+        before trying again. [<a href=\"/2020/day/1\">Return to Day 1</a>]</p></article>";
+    const WAIT: &str = "<article><p>You gave an answer too recently; you have to wait after \
+        submitting an answer before trying again.  You have 4m 47s left to wait.</p></article>";
+    const RIGHT: &str =
+        "<article><p>That's the right answer!  You are <span>one gold star</span> closer.";
+
+    #[test]
+    fn test_parse_response() {
+        assert_eq!(
+            parse_response(TOO_HIGH),
+            (Outcome::TooHigh, Duration::from_secs(60))
+        );
+        assert_eq!(
+            parse_response(WAIT),
+            (Outcome::Wait, Duration::from_secs(287))
+        );
+        assert_eq!(parse_response(RIGHT), (Outcome::Right, Duration::ZERO));
+        assert_eq!(
+            parse_response(
+                "That's not the right answer. Please wait 5 minutes before trying again."
+            ),
+            (Outcome::Wrong, Duration::from_secs(300))
+        );
+        assert_eq!(
+            parse_response("You don't seem to be solving the right level."),
+            (Outcome::WrongLevel, Duration::ZERO)
+        );
+        assert_eq!(parse_response("<html>"), (Outcome::Unknown, Duration::ZERO));
+    }
+
+    #[test]
+    fn test_attempt_line() {
+        let attempt = Attempt {
+            time: 1608854400,
+            year: 2020,
+            day: 25,
+            part: Part::Two,
+            outcome: Outcome::TooLow,
+            wait: Duration::from_secs(60),
+            answer: "Merry Christmas!".to_string(),
+        };
+        assert_eq!(
+            attempt.to_line(),
+            "1608854400 2020 25 2 too-low 60 Merry Christmas!"
+        );
+        assert_eq!(Attempt::from_line(&attempt.to_line()), Some(attempt));
+        assert_eq!(Attempt::from_line("1608854400 2020 25 3 right 0 x"), None);
+    }
+
+    #[test]
+    fn test_submit() {
+        let (url, server) = http::stub(vec![(200, TOO_HIGH), (200, RIGHT)]);
+        let history = env::temp_dir().join(format!("submissions-{}.txt", std::process::id()));
+        let _ = fs::remove_file(&history);
+        let submitter = Submitter {
+            base_url: url,
+            history: history.clone(),
+            session: Some("abc".to_string()),
+        };
+        let submit = |answer: usize, now: u64| {
+            submitter
+                .submit(2020, 1, Part::One, &Answer::Number(answer), now)
+                .unwrap()
+        };
+
+        let attempt = submit(500, 1000).unwrap();
+        assert_eq!(attempt.outcome, Outcome::TooHigh);
+        assert_eq!(
+            submit(500, 2000).unwrap_err(),
+            Refusal::KnownWrong(attempt.clone())
+        );
+        assert_eq!(
+            submit(600, 2000).unwrap_err(),
+            Refusal::OutOfBounds(attempt)
+        );
+        assert_eq!(
+            submit(400, 1030).unwrap_err(),
+            Refusal::Cooldown(Duration::from_secs(30))
+        );
+        assert_eq!(submit(400, 1060).unwrap().outcome, Outcome::Right);
+        assert_eq!(
+            submit(300, 2000).unwrap_err(),
+            Refusal::AlreadyRight("400".to_string())
+        );
+        assert_eq!(
+            submitter
+                .check(
+                    2020,
+                    1,
+                    Part::Two,
+                    &Answer::from(vec!["#".into(), ".".into()]),
+                    0
+                )
+                .unwrap(),
+            Some(Refusal::Multiline)
+        );
+        assert_eq!(
+            submitter.check(2020, 1, Part::Two, &Answer::Number(400), 0),
+            Ok(None)
+        );
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("POST /2020/day/1/answer HTTP/1.1\r\n"));
+        assert!(requests[0].ends_with("\r\n\r\nlevel=1&answer=500"));
+        assert_eq!(
+            fs::read_to_string(&history).unwrap(),
+            "1000 2020 1 1 too-high 60 500\n1060 2020 1 1 right 0 400\n"
+        );
+        fs::remove_file(&history).unwrap();
+    }
+}
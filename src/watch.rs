@@ -0,0 +1,300 @@
+//! Re-running a day whenever its inputs change, used by `main watch`.
+//!
+//! Besides the day's own input, any examples saved next to it as `dayNN_<name>.txt`,
+//! such as `data/day20_example.txt`, are watched and run too. Files are polled for
+//! changes to their modification time or length. Only inputs are watched, so a change
+//! to a solver still needs the binary rebuilding and the watch restarting.
+
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    cancel::Token,
+    cli::Source,
+    runner::{self, DayResult, PartResult, RunError},
+    Puzzle,
+};
+
+/// How often to look for changes unless told otherwise.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The examples saved next to `input`, which must be named like `dayNN.txt`.
+pub fn examples(input: &Path) -> Vec<PathBuf> {
+    let (Some(dir), Some(stem)) = (input.parent(), input.file_stem()) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}_", stem.to_string_lossy());
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let mut examples: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with(&prefix) && name.ends_with(".txt"))
+        .map(|name| input.with_file_name(name))
+        .collect();
+    examples.sort_unstable();
+    examples
+}
+
+/// Notices when files change between polls.
+#[derive(Debug, Default)]
+pub struct Watcher {
+    seen: HashMap<PathBuf, (SystemTime, u64)>,
+}
+
+impl Watcher {
+    /// The files in `files` which exist and have changed since the last poll, which is
+    /// all of them the first time.
+    pub fn poll(&mut self, files: &[PathBuf]) -> Vec<PathBuf> {
+        files
+            .iter()
+            .filter(|file| {
+                let Ok(stamp) = fs::metadata(file).and_then(|m| Ok((m.modified()?, m.len())))
+                else {
+                    self.seen.remove(*file);
+                    return false;
+                };
+                self.seen.insert((*file).clone(), stamp) != Some(stamp)
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// How a time compares to the one before.
+fn timing(now: Duration, was: Duration) -> String {
+    if was.is_zero() {
+        return format!("{:?}, was {:?}", now, was);
+    }
+    let change = (now.as_secs_f64() / was.as_secs_f64() - 1.0) * 100.0;
+    format!("{:?}, was {:?}, {:+.0}%", now, was, change)
+}
+
+/// What changed between two runs of a day on the same input.
+pub fn diff(previous: &DayResult, current: &DayResult) -> String {
+    let mut text = format!("Day {}: {}\n", current.day, current.name);
+    writeln!(
+        text,
+        "Parsed in {}",
+        timing(current.parse_time, previous.parse_time)
+    )
+    .unwrap();
+    for (label, was, now) in [
+        ("Star One", &previous.part_one, &current.part_one),
+        ("Star Two", &previous.part_two, &current.part_two),
+    ] {
+        let (
+            Some(was),
+            Some(PartResult {
+                answer, elapsed, ..
+            }),
+        ) = (was, now)
+        else {
+            continue;
+        };
+        let timing = timing(*elapsed, was.elapsed);
+        if answer.is_multiline() {
+            let change = if *answer == was.answer {
+                "unchanged"
+            } else {
+                "changed"
+            };
+            writeln!(text, "{} ({}, {}):\n{}", label, change, timing, answer).unwrap()
+        } else if *answer == was.answer {
+            writeln!(text, "{}: {} (unchanged, {})", label, answer, timing).unwrap()
+        } else {
+            writeln!(text, "{}: {} -> {} ({})", label, was.answer, answer, timing).unwrap()
+        }
+    }
+    text
+}
+
+/// One run of a day on an input which changed.
+#[derive(Debug)]
+pub struct Run {
+    pub file: PathBuf,
+    pub result: Result<DayResult, RunError>,
+    /// How the result differs from the last time the same file was run, if it was.
+    pub diff: Option<String>,
+}
+
+/// Runs a day on its inputs as they change, remembering the last results to compare
+/// the next ones with.
+pub struct Session<'a> {
+    puzzle: &'a dyn Puzzle,
+    input: PathBuf,
+    timeout: Option<Duration>,
+    watcher: Watcher,
+    previous: HashMap<PathBuf, DayResult>,
+}
+
+impl<'a> Session<'a> {
+    /// A session running `puzzle` on `input` and the examples next to it, giving up on
+    /// a run after `timeout`.
+    pub fn new(puzzle: &'a dyn Puzzle, input: PathBuf, timeout: Option<Duration>) -> Self {
+        Session {
+            puzzle,
+            input,
+            timeout,
+            watcher: Watcher::default(),
+            previous: HashMap::new(),
+        }
+    }
+
+    /// Runs the day on each of its inputs which changed since the last poll, which is
+    /// all of them the first time, handing each run to `f`. Stops at the first error
+    /// from `f`.
+    pub fn poll<E>(&mut self, mut f: impl FnMut(&Run) -> Result<(), E>) -> Result<(), E> {
+        let mut files = vec![self.input.clone()];
+        files.extend(examples(&self.input));
+        for file in self.watcher.poll(&files) {
+            let token = Token::with_timeout(self.timeout);
+            let result = runner::run_until(self.puzzle, &Source::Path(file.clone()), None, &token);
+            let diff = match (&result, self.previous.get(&file)) {
+                (Ok(result), Some(previous)) => Some(diff(previous, result)),
+                _ => None,
+            };
+            let run = Run { file, result, diff };
+            f(&run)?;
+            if let Ok(result) = run.result {
+                self.previous.insert(run.file, result);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Answer;
+    use std::{env, slice};
+
+    #[test]
+    fn test_watcher() {
+        let dir = env::temp_dir().join(format!("watch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("day20.txt");
+        let example = dir.join("day20_example.txt");
+        fs::write(&input, "1\n").unwrap();
+        fs::write(&example, "2\n").unwrap();
+        fs::write(dir.join("day2_example.txt"), "").unwrap();
+        assert_eq!(examples(&input), slice::from_ref(&example));
+
+        let files = [
+            input.clone(),
+            example.clone(),
+            dir.join("day20_missing.txt"),
+        ];
+        let mut watcher = Watcher::default();
+        assert_eq!(watcher.poll(&files), [input.clone(), example.clone()]);
+        assert!(watcher.poll(&files).is_empty());
+        fs::File::options()
+            .write(true)
+            .open(&example)
+            .and_then(|file| file.set_modified(SystemTime::UNIX_EPOCH))
+            .unwrap();
+        assert_eq!(watcher.poll(&files), slice::from_ref(&example));
+        fs::remove_file(&example).unwrap();
+        assert!(watcher.poll(&files).is_empty());
+        fs::write(&example, "3\n").unwrap();
+        assert_eq!(watcher.poll(&files), [example]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_diff() {
+        let part = |answer: usize, micros: u64| {
+            Some(PartResult {
+                answer: Answer::Number(answer),
+                elapsed: Duration::from_micros(micros),
+                memory: None,
+                cached: false,
+            })
+        };
+        let previous = DayResult {
+            day: 20,
+            name: "Jurassic Jigsaw",
+            input: "data/day20.txt".to_string(),
+            input_hash: 0,
+            parse_time: Duration::ZERO,
+            parse_memory: None,
+            part_one: part(5, 100),
+            part_two: part(7, 100),
+        };
+        let current = DayResult {
+            parse_time: Duration::from_micros(3),
+            part_one: part(5, 150),
+            part_two: part(8, 50),
+            ..previous.clone()
+        };
+        assert_eq!(
+            diff(&previous, &current),
+            "Day 20: Jurassic Jigsaw\n\
+             Parsed in 3µs, was 0ns\n\
+             Star One: 5 (unchanged, 150µs, was 100µs, +50%)\n\
+             Star Two: 7 -> 8 (50µs, was 100µs, -50%)\n"
+        );
+    }
+
+    #[test]
+    fn test_session() {
+        let dir = env::temp_dir().join(format!("watch-session-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("day01.txt");
+        let example = dir.join("day01_example.txt");
+        fs::write(&input, "1721\n979\n366\n299\n675\n1456\n").unwrap();
+        fs::write(&example, "x\n").unwrap();
+        let puzzle = crate::registry::get(1).unwrap();
+        let mut session = Session::new(puzzle, input.clone(), None);
+
+        let mut runs = Vec::new();
+        session
+            .poll(|run| {
+                runs.push((run.file.clone(), run.result.is_ok(), run.diff.is_some()));
+                Ok::<_, ()>(())
+            })
+            .unwrap();
+        assert_eq!(
+            runs,
+            [
+                (input.clone(), true, false),
+                (example.clone(), false, false)
+            ]
+        );
+        assert_eq!(session.poll(|_| Err(())), Ok(()));
+
+        fs::write(&input, "1721\n979\n366\n299\n675\n1456\n3\n").unwrap();
+        let mut diff = None;
+        session
+            .poll(|run| {
+                diff = run.diff.clone();
+                Ok::<_, ()>(())
+            })
+            .unwrap();
+        assert!(diff.unwrap().contains("Star One: 514579 (unchanged"));
+        // An error from the callback stops the poll.
+        fs::write(&input, "1721\n979\n366\n299\n675\n1456\n").unwrap();
+        fs::write(&example, "y\n").unwrap();
+        let mut seen = 0;
+        assert_eq!(
+            session.poll(|_| {
+                seen += 1;
+                Err("stop")
+            }),
+            Err("stop")
+        );
+        assert_eq!(seen, 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}